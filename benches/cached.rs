@@ -0,0 +1,26 @@
+//! Compares the uncached directory functions with their `OnceLock` backed counterparts.
+//!
+//! Run with `cargo bench --bench cached`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 100_000;
+
+fn bench(name: &str, f: impl Fn()) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let per_call: Duration = start.elapsed() / ITERATIONS;
+    println!("{name:<20} {per_call:>10.2?} per call");
+}
+
+fn main() {
+    bench("config_dir", || {
+        black_box(dirs_lite::config_dir());
+    });
+    bench("config_dir_cached", || {
+        black_box(dirs_lite::config_dir_cached());
+    });
+}
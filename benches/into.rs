@@ -0,0 +1,64 @@
+//! Compares building a path in a loop with `config_dir` against reusing a buffer with
+//! `config_dir_into`, by time and by heap allocations per call.
+//!
+//! Run with `cargo bench --bench into`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 100_000;
+
+/// Counts the allocations, including reallocations, made through the system allocator.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn bench(name: &str, mut f: impl FnMut()) {
+    // Warm up, so that a reused buffer has grown to its final capacity
+    f();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let per_call: Duration = start.elapsed() / ITERATIONS;
+    let allocations =
+        (ALLOCATIONS.load(Ordering::Relaxed) - allocations) as f64 / f64::from(ITERATIONS);
+    println!("{name:<20} {per_call:>10.2?} per call {allocations:>6.2} allocations per call");
+}
+
+fn main() {
+    bench("config_dir", || {
+        let path = dirs_lite::config_dir().map(|dir| dir.join("myapp/config.toml"));
+        black_box(path);
+    });
+    let mut buf = PathBuf::new();
+    bench("config_dir_into", || {
+        if dirs_lite::config_dir_into(&mut buf) {
+            buf.push("myapp/config.toml");
+        }
+        black_box(&buf);
+    });
+}
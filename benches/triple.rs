@@ -0,0 +1,36 @@
+//! Compares resolving config, data and cache at once with `resolve_triple` against calling
+//! `config_dir`, `data_dir` and `cache_dir` separately.
+//!
+//! Run with `cargo bench --bench triple`.
+
+#[cfg(not(target_family = "wasm"))]
+mod bench {
+    use criterion::Criterion;
+    use std::hint::black_box;
+
+    pub fn triple(c: &mut Criterion) {
+        let mut group = c.benchmark_group("triple");
+        group.bench_function("separately", |b| {
+            b.iter(|| {
+                black_box((
+                    dirs_lite::config_dir(),
+                    dirs_lite::data_dir(),
+                    dirs_lite::cache_dir(),
+                ))
+            })
+        });
+        group.bench_function("resolve_triple", |b| {
+            b.iter(|| black_box(dirs_lite::resolve_triple()))
+        });
+        group.finish();
+    }
+
+    criterion::criterion_group!(benches, triple);
+}
+
+#[cfg(not(target_family = "wasm"))]
+criterion::criterion_main!(bench::benches);
+
+// Criterion does not support wasm, the benchmark only runs on native targets
+#[cfg(target_family = "wasm")]
+fn main() {}
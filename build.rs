@@ -0,0 +1,26 @@
+//! Bakes the default of `$XDG_CONFIG_DIRS` into the crate, `/etc/xdg` unless the packager
+//! overrides it with `DIRS_LITE_DEFAULT_XDG_CONFIG_DIRS`, e.g. `/etc/xdg:/usr/share/xdg`.
+
+use std::env;
+
+#[path = "src/default_dirs.rs"]
+mod default_dirs;
+
+const KEY: &str = "DIRS_LITE_DEFAULT_XDG_CONFIG_DIRS";
+
+fn main() {
+    println!("cargo::rerun-if-env-changed={KEY}");
+    println!("cargo::rerun-if-changed=src/default_dirs.rs");
+    println!("cargo::rustc-check-cfg=cfg(dirs_lite_custom_xdg_config_dirs)");
+    let dirs = match env::var(KEY) {
+        Ok(dirs) if !dirs.is_empty() => {
+            if let Err(err) = default_dirs::parse_default_dirs(&dirs) {
+                panic!("invalid {KEY}={dirs:?}: {err}");
+            }
+            println!("cargo::rustc-cfg=dirs_lite_custom_xdg_config_dirs");
+            dirs
+        }
+        _ => "/etc/xdg".to_string(),
+    };
+    println!("cargo::rustc-env={KEY}={dirs}");
+}
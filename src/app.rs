@@ -0,0 +1,666 @@
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use crate::cache_usage::{clear_app_cache_dir, prune_app_cache_dir};
+use crate::ensure::create;
+use crate::env_override::override_var;
+use crate::{Env, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with};
+
+/// The config, data and cache directories of a single application.
+///
+/// Each directory is the respective base directory with an application specific path
+/// appended:
+///
+/// |Platform | Appended path                          | Example                                                       |
+/// | ------- | -------------------------------------- | ------------------------------------------------------------- |
+/// | Linux   | `application`, lowercase               | /home/alice/.config/myapp                                     |
+/// | macOS   | `qualifier.organization.application`   | /Users/Alice/Library/Application Support/com.example.My-App  |
+/// | Windows | `organization\application`            | C:\Users\Alice\AppData\Roaming\Example\My App                |
+///
+/// Whitespace is removed from the Linux name and replaced by `-` in the macOS name.
+/// Empty parts are skipped. Every part that is used must be a plain name, so that the
+/// directories stay inside the base directories: names like `..` or `a/b` are rejected.
+///
+/// With the feature `serde` enabled, it is serialized like [`Dirs`](crate::Dirs). Deserializing
+/// fails if a directory is not an absolute path, as a resolved one always is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "UncheckedAppDirs"))]
+pub struct AppDirs {
+    config: PathBuf,
+    data: PathBuf,
+    cache: PathBuf,
+}
+
+/// The deserialized fields of [`AppDirs`], before they are checked to be absolute paths.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedAppDirs {
+    config: PathBuf,
+    data: PathBuf,
+    cache: PathBuf,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<UncheckedAppDirs> for AppDirs {
+    type Error = String;
+
+    fn try_from(dirs: UncheckedAppDirs) -> Result<Self, Self::Error> {
+        let fields = [
+            ("config", &dirs.config),
+            ("data", &dirs.data),
+            ("cache", &dirs.cache),
+        ];
+        if let Some((name, dir)) = fields.iter().find(|(_, dir)| !dir.is_absolute()) {
+            return Err(format!(
+                "the {name} directory {} is not an absolute path",
+                dir.display()
+            ));
+        }
+        Ok(AppDirs {
+            config: dirs.config,
+            data: dirs.data,
+            cache: dirs.cache,
+        })
+    }
+}
+
+impl AppDirs {
+    /// Resolves the directories of the given application.
+    ///
+    /// Returns `None` if any of the base directories cannot be resolved, or if the name of the
+    /// application is empty or not a plain name, see [`AppDirs`].
+    pub fn new(qualifier: &str, organization: &str, application: &str) -> Option<AppDirs> {
+        Self::from_env(&ProcessEnv, qualifier, organization, application)
+    }
+
+    /// Like [`AppDirs::new`], but resolves against the given environment instead of the one of the current process.
+    pub fn from_env<E: Env + ?Sized>(
+        env: &E,
+        qualifier: &str,
+        organization: &str,
+        application: &str,
+    ) -> Option<AppDirs> {
+        let app_path = app_path(qualifier, organization, application)?;
+        Some(AppDirs {
+            config: config_dir_with(env)?.join(&app_path),
+            data: data_dir_with(env)?.join(&app_path),
+            cache: cache_dir_with(env)?.join(&app_path),
+        })
+    }
+
+    /// Roots all directories under the environment variable `var` if it is set, like `git` and
+    /// many other tools do with e.g. `MYAPP_HOME`.
+    ///
+    /// With `MYAPP_HOME=/opt/myapp`, the directories become `/opt/myapp/config`,
+    /// `/opt/myapp/data` and `/opt/myapp/cache`, on every platform. Like
+    /// [`config_dir_with_override`](crate::config_dir_with_override), a leading `~` is expanded
+    /// and the variable is ignored if it is empty or not an absolute path, keeping the
+    /// directories as resolved.
+    ///
+    /// ```
+    /// use dirs_lite::AppDirs;
+    ///
+    /// let dirs = AppDirs::new("com", "Example", "MyApp").map(|dirs| dirs.with_home_var("MYAPP_HOME"));
+    /// ```
+    pub fn with_home_var(self, var: &str) -> AppDirs {
+        self.with_home_var_with(&ProcessEnv, var)
+    }
+
+    /// Like [`AppDirs::with_home_var`], but resolves against the given environment instead of the one of the current process.
+    pub fn with_home_var_with<E: Env + ?Sized>(self, env: &E, var: &str) -> AppDirs {
+        match override_var(env, var) {
+            Some(home) => AppDirs {
+                config: home.join("config"),
+                data: home.join("data"),
+                cache: home.join("cache"),
+            },
+            None => self,
+        }
+    }
+
+    /// Returns the directories of the profile `name`, e.g. of a browser-like app with isolated
+    /// profiles, with `profiles/<name>` appended to each directory.
+    ///
+    /// Returns `None` if `name` is not a plain directory name, e.g. `../../etc` or `a/b`, so that
+    /// a profile cannot escape the application's directories.
+    ///
+    /// ```
+    /// use dirs_lite::AppDirs;
+    ///
+    /// if let Some(work) = AppDirs::new("com", "Example", "MyApp").and_then(|dirs| dirs.profile("work")) {
+    ///     assert!(work.config_dir().ends_with("profiles/work"));
+    /// }
+    /// ```
+    pub fn profile(&self, name: &str) -> Option<AppDirs> {
+        if !is_plain_name(name) {
+            return None;
+        }
+        let profile = Path::new("profiles").join(name);
+        Some(AppDirs {
+            config: self.config.join(&profile),
+            data: self.data.join(&profile),
+            cache: self.cache.join(&profile),
+        })
+    }
+
+    /// Returns the application's config directory.
+    pub fn config_dir(&self) -> &Path {
+        &self.config
+    }
+
+    /// Returns the application's data directory.
+    pub fn data_dir(&self) -> &Path {
+        &self.data
+    }
+
+    /// Returns the application's cache directory.
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache
+    }
+
+    /// Returns the application's cache directory for the given version, e.g. `1.2.3`, so that
+    /// caches of other versions are not picked up.
+    ///
+    /// The directories of all versions are kept apart from other cached data in
+    /// `<cache>/versions`, e.g. `/home/alice/.cache/myapp/versions/1.2.3`. The directory is not
+    /// created. Remove the ones of other versions with [`AppDirs::purge_old_cache_versions`].
+    ///
+    /// Returns `None` if `version` is not a plain directory name, e.g. `..` or `1.2/3`, so that
+    /// it cannot escape the application's cache directory.
+    pub fn versioned_cache_dir(&self, version: &str) -> Option<PathBuf> {
+        is_plain_name(version).then(|| self.versions_dir().join(version))
+    }
+
+    /// Removes the cache directories of all versions but `keep`, see
+    /// [`AppDirs::versioned_cache_dir`].
+    ///
+    /// Only the directories in `<cache>/versions` are removed, other cached data, e.g. the
+    /// entries of [`AppDirs::cache_shard_path`], is left untouched. Files and symbolic links
+    /// are left untouched as well, so nothing outside of the application's cache directory is
+    /// removed. Does nothing if the directory does not exist.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`] if `keep` is not a plain
+    /// directory name, e.g. `..` or `1.2/3`.
+    pub fn purge_old_cache_versions(&self, keep: &str) -> io::Result<()> {
+        if !is_plain_name(keep) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("`{keep}` is not a plain version name"),
+            ));
+        }
+        let entries = match fs::read_dir(self.versions_dir()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() && entry.file_name() != keep {
+                fs::remove_dir_all(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the directory holding the cache directories of the versions.
+    fn versions_dir(&self) -> PathBuf {
+        self.cache.join("versions")
+    }
+
+    /// Returns the application's data directory, creating it first if it does not exist, and
+    /// excludes it from backups.
+    ///
+    /// On macOS, this sets the `NSURLIsExcludedFromBackupKey` resource value of the directory,
+    /// so that neither iCloud nor Time Machine back it up, also if it already existed. Only the
+    /// application's own directory is excluded, never the shared `Library/Application Support`
+    /// holding the data of all applications. On Unix, a newly created directory gets the mode
+    /// `0700`. On other platforms, the directory is only created.
+    pub fn ensure_data_dir_no_backup(&self) -> io::Result<PathBuf> {
+        if !self.data.is_dir() {
+            create(&self.data, 0o700)?;
+        }
+        #[cfg(target_os = "macos")]
+        crate::macos::set_excluded_from_backup(&self.data)?;
+        Ok(self.data.clone())
+    }
+
+    /// Removes the contents of the application's cache directory, but not the directory
+    /// itself, e.g. for a "clear cache" button.
+    ///
+    /// As a safeguard against deleting the wrong directory when the environment is hostile or
+    /// misconfigured, this refuses with an error of kind [`io::ErrorKind::InvalidInput`] unless
+    /// the directory is below one of the platform defaults of the base cache directory, i.e.
+    /// `.cache`, `Library/Caches` or `AppData\Local`. The base cache directory itself, shared by
+    /// all applications, is never cleared, and neither are directories rooted elsewhere, e.g.
+    /// with [`AppDirs::with_home_var`].
+    ///
+    /// Symbolic links in the directory are removed, not followed. Does nothing if the directory
+    /// does not exist.
+    pub fn clear_cache_dir(&self) -> io::Result<()> {
+        clear_app_cache_dir(&self.cache)
+    }
+
+    /// Removes the files in the application's cache directory that were not accessed for
+    /// longer than `max_age`, and returns how many were removed.
+    ///
+    /// The last access time is used where the file system records it, otherwise the last
+    /// modification time. Directories emptied by the removal are removed too, the cache
+    /// directory itself is kept. Symbolic links are judged and removed as themselves, never
+    /// followed, so nothing outside of the cache directory is touched. Like
+    /// [`AppDirs::clear_cache_dir`], this refuses with an error of kind
+    /// [`io::ErrorKind::InvalidInput`] unless the directory belongs to the application alone.
+    ///
+    /// Does nothing if the directory does not exist.
+    pub fn prune_cache_older_than(&self, max_age: Duration) -> io::Result<u64> {
+        prune_app_cache_dir(&self.cache, max_age)
+    }
+
+    /// Takes an exclusive advisory lock on the application's cache directory, so that multiple
+    /// instances of the application do not write to its cache at the same time.
+    ///
+    /// The cache directory is created first if it does not exist. The lock is held on the file
+    /// `.dirs-lite.lock` in it, with `flock` on Unix and `LockFileEx` on Windows, until the
+    /// returned guard is dropped, also if the process ends without dropping it. The file itself
+    /// is left in place. As the lock belongs to the application's own directory, other
+    /// applications using this crate do not contend for it.
+    ///
+    /// Fails with an error of kind [`io::ErrorKind::WouldBlock`] if another guard holds the
+    /// lock, in this or another process, and with [`io::ErrorKind::Unsupported`] on other
+    /// platforms. The lock is advisory: it only keeps out processes that take it as well.
+    ///
+    /// NOTE: requires the feature `cache-lock`.
+    #[cfg(feature = "cache-lock")]
+    pub fn lock_cache_dir(&self) -> io::Result<crate::CacheGuard> {
+        crate::cache_lock::lock_dir(&self.cache)
+    }
+
+    /// Returns the path of the cache entry `key`, sharded into subdirectories like the objects
+    /// of `git`, so that no single directory grows huge.
+    ///
+    /// The key is hashed with the 64-bit FNV-1a hash, whose 16 hex digits make up the path
+    /// `<cache>/<first 2 digits>/<remaining 14 digits>`, spreading the entries over 256 shard
+    /// directories. The hash is fixed, so the path of a key stays the same across runs, builds
+    /// and platforms. It is not cryptographic: keys from untrusted sources may be crafted to
+    /// collide.
+    ///
+    /// ```
+    /// use dirs_lite::AppDirs;
+    ///
+    /// if let Some(dirs) = AppDirs::new("com", "Example", "MyApp") {
+    ///     let path = dirs.cache_shard_path("https://example.com/logo.png");
+    ///     assert!(path.ends_with("09/63bcd6f1c1e6ca"));
+    /// }
+    /// ```
+    pub fn cache_shard_path(&self, key: &str) -> PathBuf {
+        let hash = format!("{:016x}", fnv1a(key.as_bytes()));
+        let (shard, rest) = hash.split_at(2);
+        self.cache.join(shard).join(rest)
+    }
+
+    /// Like [`AppDirs::cache_shard_path`], but creates the shard directory and its missing
+    /// parents first, so that the entry can be written right away.
+    ///
+    /// The entry itself is not created. On Unix, newly created directories get the mode `0700`.
+    pub fn ensure_cache_shard(&self, key: &str) -> io::Result<PathBuf> {
+        let path = self.cache_shard_path(key);
+        if let Some(shard) = path.parent().filter(|shard| !shard.is_dir()) {
+            create(shard, 0o700)?;
+        }
+        Ok(path)
+    }
+}
+
+/// Returns the 64-bit FNV-1a hash of `bytes`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Returns whether `name` is a single, normal path component, i.e. neither empty, `.` or `..`,
+/// nor containing a separator.
+fn is_plain_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(component)), None) if component == name
+    )
+}
+
+/// Returns the path appended to the base directories for the given application, or `None` if
+/// it would be empty or a part of it is not a plain name.
+fn app_path(qualifier: &str, organization: &str, application: &str) -> Option<PathBuf> {
+    if cfg!(target_os = "macos") {
+        // macOS: Use a reverse-DNS style bundle identifier
+        let parts = [qualifier, organization, application]
+            .iter()
+            .map(|part| part.trim().replace(char::is_whitespace, "-"))
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>();
+        let name = parts.join(".");
+        (parts.iter().all(|part| is_plain_name(part)) && is_plain_name(&name))
+            .then(|| PathBuf::from(name))
+    } else if cfg!(target_os = "windows") {
+        // Windows: Use organization\application
+        let parts = [organization, application]
+            .iter()
+            .map(|part| part.trim())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>();
+        (!parts.is_empty() && parts.iter().all(|part| is_plain_name(part)))
+            .then(|| parts.iter().collect())
+    } else {
+        // Linux and others: Use the lowercase application name
+        let name = application.replace(char::is_whitespace, "").to_lowercase();
+        is_plain_name(&name).then(|| PathBuf::from(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_app_dirs_use_lowercase_application() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+
+        let dirs = AppDirs::from_env(&env, "com", "Example", "My App").unwrap();
+        assert_eq!(dirs.config_dir(), Path::new("/home/testuser/.config/myapp"));
+        assert_eq!(
+            dirs.data_dir(),
+            Path::new("/home/testuser/.local/share/myapp")
+        );
+        assert_eq!(dirs.cache_dir(), Path::new("/home/testuser/.cache/myapp"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_profile_appends_profile_dir() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+
+        let dirs = AppDirs::from_env(&env, "com", "Example", "My App")
+            .unwrap()
+            .profile("work")
+            .unwrap();
+        assert_eq!(
+            dirs.config_dir(),
+            Path::new("/home/testuser/.config/myapp/profiles/work")
+        );
+        assert_eq!(
+            dirs.data_dir(),
+            Path::new("/home/testuser/.local/share/myapp/profiles/work")
+        );
+        assert_eq!(
+            dirs.cache_dir(),
+            Path::new("/home/testuser/.cache/myapp/profiles/work")
+        );
+    }
+
+    #[test]
+    fn profile_rejects_names_escaping_the_app_dirs() {
+        let dirs = AppDirs {
+            config: PathBuf::from("config"),
+            data: PathBuf::from("data"),
+            cache: PathBuf::from("cache"),
+        };
+        for name in ["../../etc", "..", ".", "", "a/b", "/etc", "work/"] {
+            assert_eq!(dirs.profile(name), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn cache_shard_path_is_stable() {
+        let dirs = AppDirs {
+            config: PathBuf::from("config"),
+            data: PathBuf::from("data"),
+            cache: PathBuf::from("cache"),
+        };
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(
+            dirs.cache_shard_path("https://example.com/logo.png"),
+            Path::new("cache").join("09").join("63bcd6f1c1e6ca")
+        );
+
+        // Both hash to 3c0f...
+        let first = dirs.cache_shard_path("entry-0");
+        let second = dirs.cache_shard_path("entry-1");
+        assert_ne!(first, second);
+        assert_eq!(first.parent(), second.parent());
+        assert_eq!(
+            first.parent(),
+            Some(Path::new("cache").join("3c").as_path())
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_ensure_data_dir_no_backup_excludes_only_app_dir() {
+        let home = tempfile::tempdir().unwrap();
+        let env = MapEnv::new().with("HOME", home.path());
+
+        let dirs = AppDirs::from_env(&env, "com", "Example", "MyApp").unwrap();
+        let data = dirs.ensure_data_dir_no_backup().unwrap();
+        assert_eq!(data, dirs.data_dir());
+        assert!(data.is_dir());
+        assert!(crate::macos::backup_exclusion(&data).is_some());
+        assert!(crate::macos::backup_exclusion(data.parent().unwrap()).is_none());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_ensure_data_dir_no_backup_creates_app_data_dir() {
+        let home = tempfile::tempdir().unwrap();
+        let env = MapEnv::new().with("HOME", home.path());
+
+        let dirs = AppDirs::from_env(&env, "com", "Example", "MyApp").unwrap();
+        let data = dirs.ensure_data_dir_no_backup().unwrap();
+        assert_eq!(data, home.path().join(".local/share/myapp"));
+        assert!(data.is_dir());
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn ensure_cache_shard_creates_shard_dir() {
+        let base = tempfile::tempdir().unwrap();
+        let dirs = AppDirs {
+            config: base.path().join("config"),
+            data: base.path().join("data"),
+            cache: base.path().join("cache"),
+        };
+        let path = dirs.ensure_cache_shard("entry-0").unwrap();
+        assert_eq!(path, dirs.cache_shard_path("entry-0"));
+        assert!(path.parent().unwrap().is_dir());
+        assert!(!path.exists());
+        assert_eq!(
+            dirs.ensure_cache_shard("entry-1").unwrap().parent(),
+            path.parent()
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_home_var_roots_app_dirs() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("MYAPP_HOME", "/opt/myapp");
+
+        let dirs = AppDirs::from_env(&env, "com", "Example", "My App")
+            .unwrap()
+            .with_home_var_with(&env, "MYAPP_HOME");
+        assert_eq!(dirs.config_dir(), Path::new("/opt/myapp/config"));
+        assert_eq!(dirs.data_dir(), Path::new("/opt/myapp/data"));
+        assert_eq!(dirs.cache_dir(), Path::new("/opt/myapp/cache"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_home_var_unset_keeps_app_dirs() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("RELATIVE_HOME", "relative/myapp");
+        let dirs = AppDirs::from_env(&env, "com", "Example", "My App").unwrap();
+
+        assert_eq!(dirs.clone().with_home_var_with(&env, "MYAPP_HOME"), dirs);
+        assert_eq!(dirs.clone().with_home_var_with(&env, "RELATIVE_HOME"), dirs);
+    }
+
+    #[test]
+    #[cfg(all(
+        target_os = "macos",
+        not(feature = "favor-xdg-style"),
+        not(feature = "macos-split")
+    ))]
+    fn macos_app_dirs_use_bundle_identifier() {
+        let env = MapEnv::new().with("HOME", "/Users/testuser");
+
+        let dirs = AppDirs::from_env(&env, "com", "Example", "My App").unwrap();
+        assert_eq!(
+            dirs.config_dir(),
+            Path::new("/Users/testuser/Library/Application Support/com.Example.My-App")
+        );
+        assert_eq!(
+            dirs.data_dir(),
+            Path::new("/Users/testuser/Library/Application Support/com.Example.My-App")
+        );
+        assert_eq!(
+            dirs.cache_dir(),
+            Path::new("/Users/testuser/Library/Caches/com.Example.My-App")
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_app_dirs_use_organization_and_application() {
+        let env = MapEnv::new()
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming")
+            .with("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local");
+
+        let dirs = AppDirs::from_env(&env, "com", "Example", "My App").unwrap();
+        assert_eq!(
+            dirs.config_dir(),
+            Path::new("C:\\Users\\testuser\\AppData\\Roaming\\Example\\My App")
+        );
+        assert_eq!(
+            dirs.data_dir(),
+            Path::new("C:\\Users\\testuser\\AppData\\Local\\Example\\My App")
+        );
+        assert_eq!(
+            dirs.cache_dir(),
+            Path::new("C:\\Users\\testuser\\AppData\\Local\\Example\\My App")
+        );
+    }
+
+    #[test]
+    fn app_dirs_propagate_unresolvable_base() {
+        assert_eq!(
+            AppDirs::from_env(&MapEnv::default(), "com", "Example", "My App"),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde", target_os = "linux"))]
+    fn linux_app_dirs_round_trip_through_json() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        let dirs = AppDirs::from_env(&env, "com", "Example", "My App").unwrap();
+
+        let json = serde_json::to_string(&dirs).unwrap();
+        assert_eq!(serde_json::from_str::<AppDirs>(&json).unwrap(), dirs);
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde", target_os = "linux"))]
+    fn linux_app_dirs_reject_relative_paths_in_json() {
+        let json =
+            r#"{"config":"/home/testuser/.config/myapp","data":"myapp","cache":"/tmp/myapp"}"#;
+        let err = serde_json::from_str::<AppDirs>(json).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("the data directory myapp is not an absolute path"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_app_path_skips_empty_parts() {
+        assert_eq!(app_path("", "", "myapp"), Some(PathBuf::from("myapp")));
+    }
+
+    #[test]
+    fn app_dirs_reject_names_that_are_not_plain() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming")
+            .with("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local");
+        for (organization, application) in [
+            ("Example", ".."),
+            ("..", ".."),
+            ("Example", "../../etc"),
+            ("Example", "/etc"),
+            ("", ""),
+            ("Example", " "),
+        ] {
+            assert_eq!(
+                AppDirs::from_env(&env, "", organization, application),
+                None,
+                "{organization:?} {application:?}"
+            );
+        }
+        assert!(AppDirs::from_env(&env, "com", "Example", "My App").is_some());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_purge_old_cache_versions_keeps_only_current() {
+        let home = tempfile::tempdir().unwrap();
+        let env = MapEnv::new().with("HOME", home.path());
+        let dirs = AppDirs::from_env(&env, "com", "Example", "My App").unwrap();
+        let current = dirs.versioned_cache_dir("1.2.0").unwrap();
+        assert_eq!(current, home.path().join(".cache/myapp/versions/1.2.0"));
+
+        for version in ["1.0.0", "1.1.0", "1.2.0"] {
+            let dir = dirs.versioned_cache_dir(version).unwrap();
+            fs::create_dir_all(dir.join("objects")).unwrap();
+        }
+        let versions = current.parent().unwrap();
+        fs::write(versions.join("index"), "").unwrap();
+        let outside = home.path().join("outside");
+        fs::create_dir(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, versions.join("0.9.0")).unwrap();
+        let shard = dirs.ensure_cache_shard("entry-0").unwrap();
+        fs::write(&shard, "").unwrap();
+
+        dirs.purge_old_cache_versions("1.2.0").unwrap();
+        let mut remaining = fs::read_dir(versions)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        remaining.sort();
+        assert_eq!(remaining, ["0.9.0", "1.2.0", "index"]);
+        assert!(current.join("objects").is_dir());
+        assert!(outside.is_dir());
+        assert!(shard.is_file());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_purge_old_cache_versions_rejects_paths() {
+        let home = tempfile::tempdir().unwrap();
+        let env = MapEnv::new().with("HOME", home.path());
+        let dirs = AppDirs::from_env(&env, "com", "Example", "My App").unwrap();
+
+        for keep in ["", "..", "1.2/3", "/"] {
+            let err = dirs.purge_old_cache_versions(keep).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert_eq!(dirs.versioned_cache_dir(keep), None);
+        }
+        dirs.purge_old_cache_versions("1.2.0").unwrap();
+    }
+}
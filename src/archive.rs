@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A source of the current time, used to name archives.
+///
+/// [`SystemClock`] reads the system time. A [`SystemTime`] is a clock frozen at that
+/// instant, which makes the names reproducible, e.g. in tests.
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> SystemTime;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> SystemTime {
+        (**self).now()
+    }
+}
+
+impl Clock for SystemTime {
+    fn now(&self) -> SystemTime {
+        *self
+    }
+}
+
+/// The system time, see [`SystemTime::now`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Returns the path `dir` is moved to when it is archived, next to `dir`.
+///
+/// The archive is named `<name>.bak.<timestamp>`, where the timestamp is the UTC time of
+/// `clock` in the basic ISO 8601 format, e.g. `config.bak.20240101T000000Z`. The name
+/// contains no colons and is therefore a valid file name on all platforms.
+///
+/// Returns `None` if `dir` has no file name, e.g. for `/` or paths ending in `..`.
+pub fn archive_path<C: Clock + ?Sized>(dir: &Path, clock: &C) -> Option<PathBuf> {
+    let mut name = dir.file_name()?.to_os_string();
+    name.push(".bak.");
+    name.push(timestamp(clock.now()));
+    Some(dir.with_file_name(name))
+}
+
+/// Formats `time` as `YYYYMMDDTHHMMSSZ` in UTC, times before the epoch are clamped to it.
+fn timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let (year, month, day) = civil_from_days(secs / 86_400);
+    let secs_of_day = secs % 86_400;
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
+
+/// Converts days since the epoch to a `(year, month, day)` date in the proleptic Gregorian calendar.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn archive_path_uses_frozen_clock() {
+        // 2024-01-01T00:00:00Z
+        let clock = UNIX_EPOCH + Duration::from_secs(1_704_067_200);
+        assert_eq!(
+            archive_path(Path::new("/home/alice/.config"), &clock),
+            Some(PathBuf::from("/home/alice/.config.bak.20240101T000000Z"))
+        );
+        assert_eq!(
+            archive_path(Path::new("config"), &clock),
+            Some(PathBuf::from("config.bak.20240101T000000Z"))
+        );
+    }
+
+    #[test]
+    fn timestamp_formats_time_of_day_and_leap_days() {
+        // 2024-02-29T13:45:30Z
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_214_330);
+        assert_eq!(timestamp(time), "20240229T134530Z");
+        assert_eq!(timestamp(UNIX_EPOCH), "19700101T000000Z");
+    }
+
+    #[test]
+    fn archive_path_requires_file_name() {
+        assert_eq!(archive_path(Path::new("/"), &SystemClock), None);
+    }
+}
@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::{Env, ProcessEnv, config_dir_with};
+
+/// Returns [`config_dir`](crate::config_dir) if querying its metadata completes within
+/// `timeout`, or `None` if it does not or the directory cannot be resolved.
+///
+/// This protects latency-sensitive startup against `$XDG_CONFIG_HOME` pointing at a network
+/// mount that is currently unreachable, where the first access can block for minutes. The
+/// query runs on a thread of its own, which is left behind if it does not finish in time. A
+/// directory that does not exist yet counts as available, as the query completed.
+pub fn config_dir_available(timeout: Duration) -> Option<PathBuf> {
+    config_dir_available_with(&ProcessEnv, timeout)
+}
+
+/// Like [`config_dir_available`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_available_with<E: Env + ?Sized>(env: &E, timeout: Duration) -> Option<PathBuf> {
+    let dir = config_dir_with(env)?;
+    responds_within(&dir, timeout, |dir| {
+        let _ = fs::metadata(dir);
+    })
+    .then_some(dir)
+}
+
+/// Returns whether `probe` finishes on `dir` within `timeout`.
+///
+/// If no thread can be spawned, e.g. on `wasm32-unknown-unknown`, `dir` is probed on the
+/// current thread instead.
+fn responds_within(dir: &Path, timeout: Duration, probe: fn(&Path)) -> bool {
+    let (done, finished) = mpsc::channel();
+    let path = dir.to_path_buf();
+    let spawned = thread::Builder::new()
+        .name("dirs-lite-probe".into())
+        .spawn(move || {
+            probe(&path);
+            let _ = done.send(());
+        });
+    if spawned.is_err() {
+        probe(dir);
+        return true;
+    }
+    finished.recv_timeout(timeout).is_ok()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    fn linux_reachable_dir_is_available() {
+        let home = tempfile::tempdir().unwrap();
+        let env = MapEnv::new().with("HOME", home.path());
+        assert_eq!(
+            config_dir_available_with(&env, Duration::from_secs(5)),
+            Some(home.path().join(".config"))
+        );
+    }
+
+    #[test]
+    fn linux_unresolved_dir_is_unavailable() {
+        assert_eq!(
+            config_dir_available_with(&MapEnv::new(), Duration::from_secs(5)),
+            None
+        );
+    }
+
+    #[test]
+    fn linux_hanging_probe_times_out() {
+        // Stands in for a stat on an unreachable network mount
+        let hang = |_: &Path| thread::sleep(Duration::from_secs(5));
+        assert!(!responds_within(
+            Path::new("/mnt/nfs/.config"),
+            Duration::from_millis(50),
+            hang
+        ));
+        assert!(responds_within(
+            Path::new("/mnt/nfs/.config"),
+            Duration::from_secs(5),
+            |_| {}
+        ));
+    }
+}
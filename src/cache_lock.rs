@@ -0,0 +1,91 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::ensure::create_cache;
+#[cfg(unix)]
+use crate::unix::try_lock_exclusive;
+#[cfg(windows)]
+use crate::windows::try_lock_exclusive;
+
+/// The name of the lock file in the cache directory of an application.
+const LOCK_FILE: &str = ".dirs-lite.lock";
+
+/// An exclusive lock on the cache directory of an application, released when dropped, see
+/// [`AppDirs::lock_cache_dir`](crate::AppDirs::lock_cache_dir).
+#[derive(Debug)]
+pub struct CacheGuard {
+    path: PathBuf,
+    // Closing the file releases the lock
+    _file: File,
+}
+
+impl CacheGuard {
+    /// Returns the path of the lock file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Takes an exclusive advisory lock on the file `.dirs-lite.lock` in `dir`, creating `dir`
+/// first if it does not exist.
+pub(crate) fn lock_dir(dir: &Path) -> io::Result<CacheGuard> {
+    if !dir.is_dir() {
+        create_cache(dir)?;
+    }
+    let path = dir.join(LOCK_FILE);
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)?;
+    try_lock_exclusive(&file)?;
+    Ok(CacheGuard { path, _file: file })
+}
+
+#[cfg(not(any(unix, windows)))]
+fn try_lock_exclusive(_file: &File) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "no file locking on this platform",
+    ))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::{AppDirs, MapEnv};
+
+    #[test]
+    fn linux_lock_is_exclusive_until_dropped() {
+        let home = tempfile::tempdir().unwrap();
+        let env = MapEnv::new().with("HOME", home.path());
+        let dirs = AppDirs::from_env(&env, "com", "Example", "MyApp").unwrap();
+
+        let guard = dirs.lock_cache_dir().unwrap();
+        assert_eq!(
+            guard.path(),
+            home.path().join(".cache/myapp/.dirs-lite.lock")
+        );
+        assert_eq!(
+            dirs.lock_cache_dir().unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+
+        drop(guard);
+        let guard = dirs.lock_cache_dir().unwrap();
+        assert!(guard.path().is_file());
+    }
+
+    #[test]
+    fn linux_locks_of_different_apps_do_not_contend() {
+        let home = tempfile::tempdir().unwrap();
+        let env = MapEnv::new().with("HOME", home.path());
+        let mine = AppDirs::from_env(&env, "com", "Example", "MyApp").unwrap();
+        let other = AppDirs::from_env(&env, "org", "Other", "OtherTool").unwrap();
+
+        let _mine = mine.lock_cache_dir().unwrap();
+        let _other = other.lock_cache_dir().unwrap();
+    }
+}
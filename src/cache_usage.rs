@@ -0,0 +1,338 @@
+use std::fs;
+use std::io;
+use std::path::{Component, Path};
+use std::time::{Duration, SystemTime};
+
+use crate::ensure::not_found;
+use crate::{Env, ProcessEnv, cache_dir_result_with};
+
+/// Returns the space occupied by [`cache_dir`](crate::cache_dir) in bytes, e.g. to show it
+/// next to a "clear cache" button.
+///
+/// Like `du --apparent-size --bytes`, this sums the lengths of all files below the directory,
+/// counting symbolic links as themselves instead of following them, and files with several
+/// hard links in it only once on Unix. Unlike `du`, the directories themselves are not
+/// counted.
+///
+/// Returns `Ok(0)` if the directory does not exist, and an error of kind
+/// [`io::ErrorKind::NotFound`] if it cannot be resolved.
+pub fn cache_dir_size() -> io::Result<u64> {
+    cache_dir_size_with(&ProcessEnv)
+}
+
+/// Like [`cache_dir_size`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_size_with<E: Env + ?Sized>(env: &E) -> io::Result<u64> {
+    let dir = cache_dir_result_with(env).map_err(not_found)?;
+    match fs::symlink_metadata(&dir) {
+        Ok(metadata) if metadata.is_dir() => dir_size(&dir, &mut Vec::new()),
+        Ok(_) => Ok(0),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e),
+    }
+}
+
+/// The paths the base cache directory, shared by all applications, ends in on the supported
+/// platforms, see [`is_app_cache_dir`].
+const BASE_CACHE_SUFFIXES: [&str; 4] =
+    [".cache", "Library/Caches", "AppData/Local", "config/cache"];
+
+/// Returns whether `dir` is below one of the platform defaults of the base cache directory,
+/// e.g. `$HOME/.cache/myapp`, so that it belongs to a single application.
+///
+/// The base cache directory itself is never accepted, as it holds the caches of all
+/// applications, and on Windows, `%LOCALAPPDATA%`, their local data as well. Neither is a
+/// path with `.` or `..` components, as `$HOME/.cache/..` would pass the check by its text
+/// but point to the home directory.
+fn is_app_cache_dir(dir: &Path) -> bool {
+    let normalized = dir
+        .components()
+        .all(|component| !matches!(component, Component::CurDir | Component::ParentDir));
+    normalized
+        && dir.ancestors().skip(1).any(|base| {
+            BASE_CACHE_SUFFIXES
+                .iter()
+                .any(|suffix| base.ends_with(suffix))
+        })
+}
+
+/// Removes the contents of the application's cache directory `dir`, but not `dir` itself.
+///
+/// Refuses with an error of kind [`io::ErrorKind::InvalidInput`] unless `dir` is inside one of
+/// the platform defaults of the base cache directory, see [`is_app_cache_dir`].
+pub(crate) fn clear_app_cache_dir(dir: &Path) -> io::Result<()> {
+    if !is_app_cache_dir(dir) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "refusing to clear {}, which is not the cache directory of an application",
+                dir.display()
+            ),
+        ));
+    }
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            fs::remove_dir_all(entry.path())?;
+        } else {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes the files in the application's cache directory `dir` that were not accessed for
+/// longer than `max_age`, and returns how many were removed.
+///
+/// Refuses with an error of kind [`io::ErrorKind::InvalidInput`] unless `dir` is inside one of
+/// the platform defaults of the base cache directory, see [`is_app_cache_dir`].
+pub(crate) fn prune_app_cache_dir(dir: &Path, max_age: Duration) -> io::Result<u64> {
+    if !is_app_cache_dir(dir) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "refusing to prune {}, which is not the cache directory of an application",
+                dir.display()
+            ),
+        ));
+    }
+    let Some(cutoff) = SystemTime::now().checked_sub(max_age) else {
+        return Ok(0);
+    };
+    match fs::symlink_metadata(dir) {
+        Ok(metadata) if metadata.is_dir() => prune(dir, cutoff),
+        Ok(_) => Ok(0),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e),
+    }
+}
+
+/// Removes the files below `dir` last used before `cutoff`, and the directories emptied by that.
+fn prune(dir: &Path, cutoff: SystemTime) -> io::Result<u64> {
+    let mut removed = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let metadata = fs::symlink_metadata(&path)?;
+        if metadata.is_dir() {
+            let removed_below = prune(&path, cutoff)?;
+            if removed_below > 0 && fs::read_dir(&path)?.next().is_none() {
+                fs::remove_dir(&path)?;
+            }
+            removed += removed_below;
+        } else if metadata.accessed().or_else(|_| metadata.modified())? < cutoff {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Sums the lengths of the files below `dir`, skipping the hard links already in `seen`.
+fn dir_size(dir: &Path, seen: &mut Vec<(u64, u64)>) -> io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            size += dir_size(&entry.path(), seen)?;
+        } else if first_link(&metadata, seen) {
+            size += metadata.len();
+        }
+    }
+    Ok(size)
+}
+
+/// Returns whether `metadata` belongs to a file that was not counted yet.
+#[cfg(unix)]
+fn first_link(metadata: &fs::Metadata, seen: &mut Vec<(u64, u64)>) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    if metadata.nlink() < 2 {
+        return true;
+    }
+    let id = (metadata.dev(), metadata.ino());
+    let first = !seen.contains(&id);
+    if first {
+        seen.push(id);
+    }
+    first
+}
+
+/// Returns whether `metadata` belongs to a file that was not counted yet.
+#[cfg(not(unix))]
+fn first_link(_metadata: &fs::Metadata, _seen: &mut Vec<(u64, u64)>) -> bool {
+    true
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    fn cache_dir_size_sums_files_recursively() {
+        let root = tempfile::tempdir().unwrap();
+        let cache = root.path().join("cache");
+        fs::create_dir_all(cache.join("nested/deeper")).unwrap();
+        fs::write(cache.join("a"), [0; 100]).unwrap();
+        fs::write(cache.join("nested/b"), [0; 2000]).unwrap();
+        fs::write(cache.join("nested/deeper/c"), [0; 30]).unwrap();
+        fs::hard_link(cache.join("a"), cache.join("nested/a")).unwrap();
+
+        let outside = root.path().join("outside");
+        fs::write(&outside, [0; 50_000]).unwrap();
+        std::os::unix::fs::symlink(&outside, cache.join("link")).unwrap();
+        std::os::unix::fs::symlink(root.path(), cache.join("loop")).unwrap();
+
+        let env = MapEnv::new().with("XDG_CACHE_HOME", &cache);
+        let links = fs::symlink_metadata(cache.join("link")).unwrap().len()
+            + fs::symlink_metadata(cache.join("loop")).unwrap().len();
+        assert_eq!(cache_dir_size_with(&env).unwrap(), 2130 + links);
+    }
+
+    #[test]
+    fn prune_removes_only_stale_files() {
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path().join(".cache");
+        let cache = base.join("myapp");
+        fs::create_dir_all(cache.join("stale")).unwrap();
+        fs::create_dir_all(cache.join("mixed")).unwrap();
+        let old = SystemTime::now() - Duration::from_secs(3 * 24 * 60 * 60);
+        let age = |path: &Path| {
+            let times = fs::FileTimes::new().set_accessed(old).set_modified(old);
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_times(times)
+                .unwrap();
+        };
+        for file in ["old", "stale/a", "stale/b", "mixed/old", "mixed/new", "new"] {
+            fs::write(cache.join(file), "").unwrap();
+            if !file.ends_with("new") {
+                age(&cache.join(file));
+            }
+        }
+        let outside = root.path().join("outside");
+        fs::write(&outside, "").unwrap();
+        age(&outside);
+        std::os::unix::fs::symlink(root.path(), cache.join("escape")).unwrap();
+
+        let env = MapEnv::new()
+            .with("HOME", root.path())
+            .with("XDG_CACHE_HOME", &base);
+        let dirs = crate::AppDirs::from_env(&env, "com", "Example", "MyApp").unwrap();
+        assert_eq!(
+            dirs.prune_cache_older_than(Duration::from_secs(24 * 60 * 60))
+                .unwrap(),
+            4
+        );
+        assert!(!cache.join("old").exists());
+        assert!(!cache.join("stale").exists());
+        assert!(!cache.join("mixed/old").exists());
+        assert!(cache.join("mixed/new").exists());
+        assert!(cache.join("new").exists());
+        assert!(cache.join("escape").exists());
+        assert!(outside.exists());
+    }
+
+    #[test]
+    fn missing_cache_dir_has_no_size() {
+        let root = tempfile::tempdir().unwrap();
+        let env = MapEnv::new().with("XDG_CACHE_HOME", root.path().join("missing"));
+        assert_eq!(cache_dir_size_with(&env).unwrap(), 0);
+    }
+
+    #[test]
+    fn unresolved_cache_dir_is_not_found() {
+        let err = cache_dir_size_with(&MapEnv::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn clear_app_cache_dir_removes_contents_only() {
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path().join(".cache");
+        let cache = base.join("myapp");
+        fs::create_dir_all(cache.join("nested")).unwrap();
+        fs::write(cache.join("nested/file"), "cached").unwrap();
+        fs::write(cache.join("file"), "cached").unwrap();
+        fs::write(base.join("other"), "cached by another app").unwrap();
+        let outside = root.path().join("outside");
+        fs::create_dir(&outside).unwrap();
+        fs::write(outside.join("keep"), "").unwrap();
+        std::os::unix::fs::symlink(&outside, cache.join("link")).unwrap();
+
+        let env = MapEnv::new()
+            .with("HOME", root.path())
+            .with("XDG_CACHE_HOME", &base);
+        let dirs = crate::AppDirs::from_env(&env, "com", "Example", "MyApp").unwrap();
+        dirs.clear_cache_dir().unwrap();
+        assert!(cache.is_dir());
+        assert_eq!(fs::read_dir(&cache).unwrap().count(), 0);
+        assert!(base.join("other").exists());
+        assert!(outside.join("keep").exists());
+    }
+
+    #[test]
+    fn clear_app_cache_dir_ignores_missing_dir() {
+        let root = tempfile::tempdir().unwrap();
+        clear_app_cache_dir(&root.path().join(".cache/myapp")).unwrap();
+    }
+
+    #[test]
+    fn clear_app_cache_dir_refuses_shared_and_unexpected_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path().join(".cache");
+        fs::create_dir(&base).unwrap();
+        fs::write(base.join("important"), "").unwrap();
+        fs::write(root.path().join("important"), "").unwrap();
+
+        for dir in [base.as_path(), root.path()] {
+            let err = clear_app_cache_dir(dir).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            let err = prune_app_cache_dir(dir, Duration::ZERO).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+        assert!(base.join("important").exists());
+        assert!(root.path().join("important").exists());
+    }
+
+    #[test]
+    fn app_cache_dir_is_below_base_cache_dir() {
+        assert!(is_app_cache_dir(Path::new("/home/alice/.cache/myapp")));
+        assert!(is_app_cache_dir(Path::new(
+            "/Users/Alice/Library/Caches/com.example.MyApp"
+        )));
+        assert!(is_app_cache_dir(Path::new(
+            "C:/Users/Alice/AppData/Local/Example/MyApp"
+        )));
+        assert!(!is_app_cache_dir(Path::new("/home/alice/.cache")));
+        assert!(!is_app_cache_dir(Path::new("C:/Users/Alice/AppData/Local")));
+        assert!(!is_app_cache_dir(Path::new("/home/alice/myapp")));
+        assert!(!is_app_cache_dir(Path::new("/home/alice/.cache/..")));
+        assert!(!is_app_cache_dir(Path::new(
+            "/home/alice/.cache/myapp/../.."
+        )));
+        assert!(!is_app_cache_dir(Path::new("./.cache/myapp")));
+    }
+
+    #[test]
+    fn clear_app_cache_dir_refuses_parent_components() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join(".cache")).unwrap();
+        fs::write(root.path().join("important"), "").unwrap();
+
+        let dir = root.path().join(".cache/..");
+        let err = clear_app_cache_dir(&dir).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = prune_app_cache_dir(&dir, Duration::ZERO).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(root.path().join("important").exists());
+    }
+}
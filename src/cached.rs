@@ -0,0 +1,203 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::{cache_dir, config_dir, data_dir};
+
+/// A value resolved on first use and kept until [`invalidate_cache`] clears it.
+struct Memo<T>(RwLock<Option<T>>);
+
+impl<T: Clone> Memo<T> {
+    const fn new() -> Self {
+        Memo(RwLock::new(None))
+    }
+
+    /// Returns the memoized value, resolving it with `init` first if there is none.
+    ///
+    /// `init` runs without holding the lock, so a slow resolution, e.g. of a home directory on
+    /// a network mount, does not block readers or [`invalidate_cache`]. If several threads
+    /// resolve concurrently, the first value stored wins and is returned to all of them.
+    fn get_or_init(&self, init: impl FnOnce() -> T) -> T {
+        if let Some(value) = &*self.0.read().unwrap_or_else(PoisonError::into_inner) {
+            return value.clone();
+        }
+        let value = init();
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert(value)
+            .clone()
+    }
+
+    fn clear(&self) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+
+static CONFIG_DIR_CACHED: Memo<Option<&'static Path>> = Memo::new();
+static DATA_DIR_CACHED: Memo<Option<&'static Path>> = Memo::new();
+static CACHE_DIR_CACHED: Memo<Option<&'static Path>> = Memo::new();
+static CONFIG_DIR_SHARED: Memo<Option<Arc<Path>>> = Memo::new();
+static DATA_DIR_SHARED: Memo<Option<Arc<Path>>> = Memo::new();
+static CACHE_DIR_SHARED: Memo<Option<Arc<Path>>> = Memo::new();
+
+/// Returns `dir` borrowed for the lifetime of the process.
+///
+/// The path is leaked, so that it stays valid after [`invalidate_cache`].
+fn leaked(dir: Option<PathBuf>) -> Option<&'static Path> {
+    dir.map(|dir| &*Box::leak(dir.into_boxed_path()))
+}
+
+/// Returns [`config_dir`](crate::config_dir), resolved once and then borrowed for the lifetime
+/// of the process.
+///
+/// The first call snapshots the environment, later changes to it are not picked up until
+/// [`invalidate_cache`] is called. Use the uncached function if the environment may change
+/// while the process runs.
+pub fn config_dir_cached() -> Option<&'static Path> {
+    CONFIG_DIR_CACHED.get_or_init(|| leaked(config_dir()))
+}
+
+/// Returns [`data_dir`](crate::data_dir), resolved once and then borrowed for the lifetime
+/// of the process.
+///
+/// See [`config_dir_cached`] for details.
+pub fn data_dir_cached() -> Option<&'static Path> {
+    DATA_DIR_CACHED.get_or_init(|| leaked(data_dir()))
+}
+
+/// Returns [`cache_dir`](crate::cache_dir), resolved once and then borrowed for the lifetime
+/// of the process.
+///
+/// See [`config_dir_cached`] for details.
+pub fn cache_dir_cached() -> Option<&'static Path> {
+    CACHE_DIR_CACHED.get_or_init(|| leaked(cache_dir()))
+}
+
+/// Returns [`config_dir`](crate::config_dir) as a shared `Arc<Path>`, resolved once and then
+/// cloned cheaply, e.g. into many tasks.
+///
+/// Every call returns the same allocation. Like [`config_dir_cached`], the first call snapshots
+/// the environment and later changes to it are not picked up until [`invalidate_cache`] is called.
+pub fn config_dir_shared() -> Option<Arc<Path>> {
+    CONFIG_DIR_SHARED.get_or_init(|| config_dir().map(Arc::from))
+}
+
+/// Returns [`data_dir`](crate::data_dir) as a shared `Arc<Path>`, resolved once.
+///
+/// See [`config_dir_shared`] for details.
+pub fn data_dir_shared() -> Option<Arc<Path>> {
+    DATA_DIR_SHARED.get_or_init(|| data_dir().map(Arc::from))
+}
+
+/// Returns [`cache_dir`](crate::cache_dir) as a shared `Arc<Path>`, resolved once.
+///
+/// See [`config_dir_shared`] for details.
+pub fn cache_dir_shared() -> Option<Arc<Path>> {
+    CACHE_DIR_SHARED.get_or_init(|| cache_dir().map(Arc::from))
+}
+
+/// Forgets the directories memoized by the `_cached` and `_shared` functions, e.g.
+/// [`config_dir_cached`], so that the next call resolves them again.
+///
+/// Call this when the environment the directories were resolved from has changed while the
+/// process runs, e.g. after an automounter replaced the target of a home directory that is a
+/// symlink. [`set_home_override`](crate::set_home_override) calls it itself. Paths returned
+/// before stay valid but are not updated; the ones of the `_cached` functions are kept
+/// allocated for the lifetime of the process, so invalidating often leaks memory.
+pub fn invalidate_cache() {
+    for memo in [&CONFIG_DIR_CACHED, &DATA_DIR_CACHED, &CACHE_DIR_CACHED] {
+        memo.clear();
+    }
+    for memo in [&CONFIG_DIR_SHARED, &DATA_DIR_SHARED, &CACHE_DIR_SHARED] {
+        memo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cached_dirs_agree_with_uncached() {
+        assert_eq!(config_dir_cached(), config_dir().as_deref());
+        assert_eq!(data_dir_cached(), data_dir().as_deref());
+        assert_eq!(cache_dir_cached(), cache_dir().as_deref());
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn concurrent_inits_keep_the_first_value() {
+        let memo = &Memo::new();
+        let values: Vec<usize> = std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..8)
+                .map(|id| scope.spawn(move || memo.get_or_init(|| id)))
+                .collect();
+            threads.into_iter().map(|t| t.join().unwrap()).collect()
+        });
+        assert!(values.iter().all(|&value| value == values[0]));
+        assert_eq!(memo.get_or_init(|| usize::MAX), values[0]);
+    }
+
+    #[test]
+    fn cached_dir_is_computed_once() {
+        let first = config_dir_cached();
+        let second = config_dir_cached();
+        assert!(match (first, second) {
+            (Some(first), Some(second)) => std::ptr::eq(first, second),
+            (first, second) => first == second,
+        });
+    }
+
+    #[test]
+    fn shared_dirs_share_one_allocation() {
+        assert_eq!(config_dir_shared().as_deref(), config_dir_cached());
+        assert!(match (config_dir_shared(), config_dir_shared()) {
+            (Some(first), Some(second)) => Arc::ptr_eq(&first, &second),
+            (first, second) => first == second,
+        });
+        assert_eq!(data_dir_shared().as_deref(), data_dir_cached());
+        assert_eq!(cache_dir_shared().as_deref(), cache_dir_cached());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn invalidated_cache_resolves_again() {
+        use crate::env_guard::EnvGuard;
+
+        let before = config_dir_cached();
+        let shared_before = config_dir_shared();
+
+        let after = {
+            // SAFETY: Tests run single-threaded with --test-threads=1
+            let _env =
+                unsafe { EnvGuard::new().set("XDG_CONFIG_HOME", "/tmp/dirs-lite-invalidate") };
+            assert_eq!(config_dir_cached(), before);
+            invalidate_cache();
+            (config_dir_cached(), config_dir_shared())
+        };
+        invalidate_cache();
+
+        assert_eq!(after.0, Some(Path::new("/tmp/dirs-lite-invalidate")));
+        assert_eq!(after.1.as_deref(), after.0);
+        assert_eq!(before, config_dir().as_deref());
+        assert_eq!(shared_before, config_dir().map(Arc::from));
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn home_override_refreshes_cached_dirs() {
+        let home = PathBuf::from("/tmp/dirs-lite-override");
+        let before = config_dir_cached();
+
+        crate::set_home_override(Some(home.clone()));
+        let overridden = (config_dir_cached(), config_dir_shared(), config_dir());
+        crate::set_home_override(None);
+
+        assert_eq!(overridden.0, overridden.2.as_deref());
+        assert_eq!(overridden.1.as_deref(), overridden.0);
+        if std::env::var_os("XDG_CONFIG_HOME").is_none() {
+            assert!(overridden.0.is_some_and(|dir| dir.starts_with(&home)));
+        }
+        assert_eq!(config_dir_cached(), before);
+    }
+}
@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+
+use crate::{BaseDir, DirLayout, DirSource, DirStyle, Env, ProcessEnv, base_dir_sources};
+
+/// A location considered for a directory, see [`config_dir_candidates`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    /// Where the location comes from, e.g. `$XDG_CONFIG_HOME` or `$HOME/.config`.
+    pub source: &'static str,
+    /// The location, or `None` if the source is unset or rejected, e.g. an empty or relative
+    /// `$XDG_CONFIG_HOME`.
+    pub path: Option<PathBuf>,
+    /// Whether this location is the one [`config_dir`](crate::config_dir) returns, i.e. the
+    /// first one with a path.
+    pub chosen: bool,
+}
+
+/// Returns every location [`config_dir`](crate::config_dir) considers on the current platform,
+/// in order, e.g. for a "where would this look?" diagnostic.
+///
+/// Unlike [`config_dir_trace`](crate::config_dir_trace), the locations after the chosen one are
+/// listed as well. Sources of features that are not enabled are left out.
+pub fn config_dir_candidates() -> Vec<Candidate> {
+    config_dir_candidates_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_candidates`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_candidates_with<E: Env + ?Sized>(env: &E) -> Vec<Candidate> {
+    let style = DirStyle::default();
+    let layout = DirLayout::default();
+    let mut chosen = false;
+    base_dir_sources(style)
+        .iter()
+        .filter(|source| source.is_enabled())
+        .map(|&source| {
+            let mut path = PathBuf::new();
+            let path = matches!(
+                source.resolve(env, &BaseDir::CONFIG, style, &layout, &mut path),
+                Some(Ok(()))
+            )
+            .then_some(path);
+            let is_chosen = !chosen && path.is_some();
+            chosen |= is_chosen;
+            Candidate {
+                source: source_name(source, style),
+                path,
+                chosen: is_chosen,
+            }
+        })
+        .collect()
+}
+
+/// Returns where the config dir from `source` comes from, for [`Candidate::source`].
+fn source_name(source: DirSource, style: DirStyle) -> &'static str {
+    match source {
+        DirSource::GlobalPrefix => "$DIRS_LITE_PREFIX/config",
+        DirSource::Portable => "<exe_dir>/config",
+        DirSource::Snap => "$SNAP_USER_DATA/.config",
+        DirSource::AppImage => "$APPIMAGE/../.<name>/config",
+        DirSource::HostXdg => "$HOST_XDG_CONFIG_HOME",
+        DirSource::Xdg => "$XDG_CONFIG_HOME",
+        DirSource::XdgHome => "$HOME/.config",
+        DirSource::Macos if style == DirStyle::Xdg => "$HOME/.config",
+        DirSource::Macos => "$HOME/Library/Application Support",
+        DirSource::KnownFolder => "FOLDERID_RoamingAppData",
+        DirSource::WindowsVar => "%APPDATA%",
+        DirSource::WindowsProfile => "%USERPROFILE%\\AppData\\Roaming",
+        DirSource::Haiku => "$HOME/config/settings",
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::{MapEnv, config_dir_with};
+
+    fn candidate<'a>(candidates: &'a [Candidate], source: &str) -> &'a Candidate {
+        candidates
+            .iter()
+            .find(|candidate| candidate.source == source)
+            .unwrap()
+    }
+
+    #[test]
+    fn linux_empty_xdg_is_rejected_and_home_chosen() {
+        let env = MapEnv::new()
+            .with("XDG_CONFIG_HOME", "")
+            .with("HOME", "/home/testuser");
+        let candidates = config_dir_candidates_with(&env);
+        assert_eq!(
+            candidate(&candidates, "$XDG_CONFIG_HOME"),
+            &Candidate {
+                source: "$XDG_CONFIG_HOME",
+                path: None,
+                chosen: false,
+            }
+        );
+        assert_eq!(
+            candidate(&candidates, "$HOME/.config"),
+            &Candidate {
+                source: "$HOME/.config",
+                path: Some(PathBuf::from("/home/testuser/.config")),
+                chosen: true,
+            }
+        );
+        assert_eq!(candidates.last().map(|c| c.source), Some("$HOME/.config"));
+    }
+
+    #[test]
+    fn linux_candidates_after_the_chosen_one_are_listed() {
+        let env = MapEnv::new()
+            .with("XDG_CONFIG_HOME", "/tmp/config")
+            .with("HOME", "/home/testuser");
+        let candidates = config_dir_candidates_with(&env);
+        assert!(candidate(&candidates, "$XDG_CONFIG_HOME").chosen);
+        let home = candidate(&candidates, "$HOME/.config");
+        assert!(!home.chosen);
+        assert_eq!(home.path, Some(PathBuf::from("/home/testuser/.config")));
+
+        let chosen: Vec<_> = candidates.iter().filter(|c| c.chosen).collect();
+        assert_eq!(chosen.len(), 1);
+        assert_eq!(chosen[0].path, config_dir_with(&env));
+    }
+}
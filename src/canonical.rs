@@ -0,0 +1,71 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{Env, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with};
+
+/// Returns [`config_dir`](crate::config_dir) with all symbolic links resolved, see [`fs::canonicalize`].
+///
+/// Unlike the plain functions, this touches the file system. Returns `None` if the directory
+/// cannot be resolved, does not exist, or cannot be canonicalized.
+pub fn config_dir_canonical() -> Option<PathBuf> {
+    config_dir_canonical_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_canonical`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_canonical_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    fs::canonicalize(config_dir_with(env)?).ok()
+}
+
+/// Returns [`data_dir`](crate::data_dir) with all symbolic links resolved, see [`config_dir_canonical`].
+pub fn data_dir_canonical() -> Option<PathBuf> {
+    data_dir_canonical_with(&ProcessEnv)
+}
+
+/// Like [`data_dir_canonical`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_canonical_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    fs::canonicalize(data_dir_with(env)?).ok()
+}
+
+/// Returns [`cache_dir`](crate::cache_dir) with all symbolic links resolved, see [`config_dir_canonical`].
+pub fn cache_dir_canonical() -> Option<PathBuf> {
+    cache_dir_canonical_with(&ProcessEnv)
+}
+
+/// Like [`cache_dir_canonical`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_canonical_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    fs::canonicalize(cache_dir_with(env)?).ok()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linux_canonical_dirs_resolve_symlinked_home() {
+        let root = tempfile::tempdir().unwrap();
+        let real = root.path().join("real");
+        fs::create_dir_all(real.join(".config")).unwrap();
+        fs::create_dir_all(real.join(".cache")).unwrap();
+        let home = root.path().join("home");
+        std::os::unix::fs::symlink(&real, &home).unwrap();
+        let env = crate::MapEnv::new().with("HOME", &home);
+
+        let raw = config_dir_with(&env).unwrap();
+        let canonical = config_dir_canonical_with(&env).unwrap();
+        assert_eq!(raw, home.join(".config"));
+        assert_ne!(canonical, raw);
+        assert_eq!(canonical, fs::canonicalize(&real).unwrap().join(".config"));
+        assert_eq!(
+            cache_dir_canonical_with(&env),
+            Some(fs::canonicalize(&real).unwrap().join(".cache"))
+        );
+    }
+
+    #[test]
+    fn linux_canonical_dir_is_none_if_missing() {
+        let home = tempfile::tempdir().unwrap();
+        let env = crate::MapEnv::new().with("HOME", home.path());
+        assert_eq!(data_dir_canonical_with(&env), None);
+        assert_eq!(config_dir_canonical_with(&crate::MapEnv::new()), None);
+    }
+}
@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+
+use crate::{DirKind, Env, ProcessEnv, home_dir_result_with, xdg_var};
+
+/// A place a [`FallbackChain`] looks for a directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// The environment variable of the given name, e.g. of the application.
+    EnvVar(String),
+    /// The XDG variable of the kind of the chain, e.g. `XDG_CONFIG_HOME` for config, or
+    /// `XDG_RUNTIME_DIR` for runtime.
+    XdgStandard,
+    /// The home directory with `suffix` appended, e.g. `.config`.
+    Home {
+        /// The path relative to the home directory.
+        suffix: PathBuf,
+    },
+    /// A fixed path, e.g. a default compiled into the application.
+    Literal(PathBuf),
+}
+
+/// An ordered list of [`Source`]s, resolved to the first one that yields a directory.
+///
+/// Like the XDG variables, every source is skipped if it is unset, empty or not an absolute
+/// path, so an application can put its own variable in front and a compiled default last:
+///
+/// ```
+/// use dirs_lite::{DirKind, FallbackChain, Source};
+///
+/// let config = FallbackChain::new(DirKind::Config)
+///     .then(Source::EnvVar("MYAPP_CONFIG_DIR".into()))
+///     .then(Source::XdgStandard)
+///     .then(Source::Home { suffix: ".config".into() })
+///     .then(Source::Literal("/etc/myapp".into()))
+///     .resolve();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FallbackChain {
+    kind: DirKind,
+    sources: Vec<Source>,
+}
+
+impl FallbackChain {
+    /// Creates a chain without sources for the directories of the given kind, which selects
+    /// the variable of [`Source::XdgStandard`].
+    pub fn new(kind: DirKind) -> Self {
+        FallbackChain {
+            kind,
+            sources: Vec::new(),
+        }
+    }
+
+    /// Returns the chain the functions of this crate follow on Linux and the BSDs, e.g.
+    /// `$XDG_CONFIG_HOME`, then `$HOME/.config` for [`config_dir`](crate::config_dir).
+    ///
+    /// The runtime directory has no fallback in the home directory.
+    pub fn xdg(kind: DirKind) -> Self {
+        let chain = FallbackChain::new(kind).then(Source::XdgStandard);
+        let suffix = match kind {
+            DirKind::Config => ".config",
+            DirKind::Data => ".local/share",
+            DirKind::Cache => ".cache",
+            DirKind::State => ".local/state",
+            DirKind::Runtime => return chain,
+        };
+        chain.then(Source::Home {
+            suffix: suffix.into(),
+        })
+    }
+
+    /// Appends `source`, to be tried after the ones added before.
+    pub fn then(mut self, source: Source) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Returns the sources in the order they are tried.
+    pub fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    /// Returns the directory of the first source that yields one, or `None` if none does.
+    pub fn resolve(&self) -> Option<PathBuf> {
+        self.resolve_with(&ProcessEnv)
+    }
+
+    /// Like [`FallbackChain::resolve`], but resolves against the given environment instead of the one of the current process.
+    pub fn resolve_with<E: Env + ?Sized>(&self, env: &E) -> Option<PathBuf> {
+        self.sources.iter().find_map(|source| {
+            let dir = match source {
+                Source::EnvVar(key) => env.var_os(key).map(PathBuf::from),
+                Source::XdgStandard => xdg_var(env, self.xdg_key()).ok(),
+                Source::Home { suffix } => home_dir_result_with(env).ok().map(|home| {
+                    if suffix.as_os_str().is_empty() {
+                        home
+                    } else {
+                        home.join(suffix)
+                    }
+                }),
+                Source::Literal(dir) => Some(dir.clone()),
+            };
+            dir.filter(|dir| dir.is_absolute())
+        })
+    }
+
+    /// Returns the XDG variable of the kind of the chain.
+    fn xdg_key(&self) -> &'static str {
+        match self.kind {
+            DirKind::Config => "XDG_CONFIG_HOME",
+            DirKind::Data => "XDG_DATA_HOME",
+            DirKind::Cache => "XDG_CACHE_HOME",
+            DirKind::State => "XDG_STATE_HOME",
+            DirKind::Runtime => "XDG_RUNTIME_DIR",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn later_source_wins_if_earlier_ones_are_unset() {
+        let chain = FallbackChain::new(DirKind::Config)
+            .then(Source::EnvVar("MYAPP_CONFIG_DIR".into()))
+            .then(Source::XdgStandard)
+            .then(Source::Home {
+                suffix: ".myapp".into(),
+            })
+            .then(Source::Literal("/etc/myapp".into()));
+
+        let env = MapEnv::new()
+            .with("MYAPP_CONFIG_DIR", "")
+            .with("XDG_CONFIG_HOME", "relative/config");
+        assert_eq!(chain.resolve_with(&env), Some(PathBuf::from("/etc/myapp")));
+
+        let env = env.with("HOME", "/home/testuser");
+        assert_eq!(
+            chain.resolve_with(&env),
+            Some(PathBuf::from("/home/testuser/.myapp"))
+        );
+
+        let env = env.with("XDG_CONFIG_HOME", "/xdg/config");
+        assert_eq!(chain.resolve_with(&env), Some(PathBuf::from("/xdg/config")));
+
+        let env = env.with("MYAPP_CONFIG_DIR", "/custom/config");
+        assert_eq!(
+            chain.resolve_with(&env),
+            Some(PathBuf::from("/custom/config"))
+        );
+    }
+
+    #[test]
+    fn empty_chain_resolves_nothing() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(FallbackChain::new(DirKind::Cache).resolve_with(&env), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_xdg_preset_matches_dir_functions() {
+        for env in [
+            MapEnv::new().with("HOME", "/home/testuser"),
+            MapEnv::new()
+                .with("HOME", "/home/testuser")
+                .with("XDG_CONFIG_HOME", "/xdg/config")
+                .with("XDG_DATA_HOME", "/xdg/data")
+                .with("XDG_CACHE_HOME", "/xdg/cache")
+                .with("XDG_STATE_HOME", "/xdg/state")
+                .with("XDG_RUNTIME_DIR", "/run/user/1000"),
+        ] {
+            let resolve = |kind| FallbackChain::xdg(kind).resolve_with(&env);
+            assert_eq!(resolve(DirKind::Config), crate::config_dir_with(&env));
+            assert_eq!(resolve(DirKind::Data), crate::data_dir_with(&env));
+            assert_eq!(resolve(DirKind::Cache), crate::cache_dir_with(&env));
+            assert_eq!(resolve(DirKind::State), crate::state_dir_with(&env));
+            assert_eq!(resolve(DirKind::Runtime), crate::runtime_dir_with(&env));
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// The variable that tells the re-invoked test binary which scenario to run.
+const SCENARIO_VAR: &str = "DIRS_LITE_TEST_SCENARIO";
+
+/// Precedes the result of a scenario in the output of the child process, to tell it apart
+/// from the output of the test harness on the same line.
+const RESULT_MARKER: &str = "dirs-lite-scenario-result:";
+
+/// Runs `scenario` in a child process whose environment consists of `env` only, and returns the
+/// directory it resolved.
+///
+/// The child is the current test binary, re-invoked to run only [`scenario_entry`]. As the
+/// environment of the test process is never changed, tests using this can run in parallel.
+///
+/// ```ignore
+/// let config = run_in_child(&[("HOME", "/home/testuser")], "config_dir");
+/// assert_eq!(config, Some(PathBuf::from("/home/testuser/.config")));
+/// ```
+pub(crate) fn run_in_child(env: &[(&str, &str)], scenario: &str) -> Option<PathBuf> {
+    let output = Command::new(env::current_exe().expect("test binary has a path"))
+        .args(["--exact", "child::scenario_entry", "--nocapture"])
+        .args(["--test-threads", "1"])
+        .env_clear()
+        .envs(env.iter().copied())
+        .env(SCENARIO_VAR, scenario)
+        .output()
+        .expect("test binary can be re-invoked");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "scenario {scenario} failed:\n{stdout}\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result = stdout
+        .lines()
+        .find_map(|line| Some(line.split_once(RESULT_MARKER)?.1))
+        .unwrap_or_else(|| panic!("scenario {scenario} reported no result:\n{stdout}"));
+    result.strip_prefix("Some:").map(PathBuf::from)
+}
+
+/// Runs the scenario requested by [`run_in_child`], and does nothing in a regular test run.
+#[test]
+fn scenario_entry() {
+    let Some(scenario) = env::var_os(SCENARIO_VAR) else {
+        return;
+    };
+    let dir = match scenario.to_str() {
+        Some("config_dir") => crate::config_dir(),
+        Some("data_dir") => crate::data_dir(),
+        Some("cache_dir") => crate::cache_dir(),
+        Some("state_dir") => crate::state_dir(),
+        Some("runtime_dir") => crate::runtime_dir(),
+        _ => panic!("unknown scenario {scenario:?}"),
+    };
+    match dir {
+        Some(dir) => println!("{RESULT_MARKER}Some:{}", dir.display()),
+        None => println!("{RESULT_MARKER}None"),
+    }
+}
@@ -0,0 +1,45 @@
+//! The functions of this crate under the names of the [`dirs`](https://docs.rs/dirs) crate, to
+//! migrate with a near-mechanical swap:
+//!
+//! ```
+//! use dirs_lite::compat as dirs;
+//!
+//! let config = dirs::config_dir();
+//! let music = dirs::audio_dir();
+//! ```
+//!
+//! Enable the feature `dirs-compat` to use it. Every function of `dirs` 6 has an equivalent,
+//! with the same signature. The resolved paths follow this crate, which differs from `dirs` in
+//! a few places, e.g. `$XDG_CONFIG_HOME` is ignored on macOS unless `favor-xdg-style` is
+//! enabled, and relative `$XDG_*` values are ignored on Linux. Like in `dirs`, `data_dir` is
+//! the roaming `%APPDATA%` on Windows, see [`roaming_data_dir`](crate::roaming_data_dir).
+
+pub use crate::{
+    cache_dir, config_dir, config_local_dir, data_local_dir, desktop_dir, download_dir,
+    executable_dir, font_dir, home_dir, preference_dir, public_dir, runtime_dir, state_dir,
+};
+
+pub use crate::documents_dir as document_dir;
+pub use crate::music_dir as audio_dir;
+pub use crate::pictures_dir as picture_dir;
+pub use crate::roaming_data_dir as data_dir;
+pub use crate::templates_dir as template_dir;
+pub use crate::videos_dir as video_dir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compat_names_resolve_like_native_ones() {
+        assert_eq!(config_dir(), crate::config_dir());
+        assert_eq!(data_dir(), crate::roaming_data_dir());
+        assert_eq!(data_local_dir(), crate::data_local_dir());
+        assert_eq!(cache_dir(), crate::cache_dir());
+        assert_eq!(audio_dir(), crate::music_dir());
+        assert_eq!(document_dir(), crate::documents_dir());
+        assert_eq!(picture_dir(), crate::pictures_dir());
+        assert_eq!(template_dir(), crate::templates_dir());
+        assert_eq!(video_dir(), crate::videos_dir());
+    }
+}
@@ -0,0 +1,198 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::{Env, KnownFolder, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with};
+
+/// A step considered while resolving a directory, see [`config_dir_trace`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TraceStep {
+    /// The environment variable `key` was read, and its value used if `accepted`.
+    CheckedEnvVar {
+        /// The name of the variable.
+        key: &'static str,
+        /// The value of the variable, lossily converted to UTF-8, or `None` if it is unset.
+        value: Option<String>,
+        /// Whether the value was used.
+        accepted: bool,
+    },
+    /// The home directory was looked up in the password database, as `$HOME` is unusable.
+    UsedPasswdHome {
+        /// The home directory found, if any.
+        home: Option<PathBuf>,
+    },
+    /// The Windows known folder `folder` was queried.
+    QueriedKnownFolder {
+        /// The folder queried.
+        folder: KnownFolder,
+        /// The path of the folder, or `None` if it is unknown.
+        path: Option<PathBuf>,
+    },
+    /// The directory was derived from the home directory `home`.
+    FellBackToHome {
+        /// The home directory.
+        home: PathBuf,
+    },
+}
+
+impl TraceStep {
+    /// Returns a [`TraceStep::CheckedEnvVar`] for the current value of `key` in `env`.
+    pub(crate) fn env_var<E: Env + ?Sized>(env: &E, key: &'static str, accepted: bool) -> Self {
+        TraceStep::CheckedEnvVar {
+            key,
+            value: env
+                .var_os(key)
+                .map(|value| value.to_string_lossy().into_owned()),
+            accepted,
+        }
+    }
+}
+
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceStep::CheckedEnvVar {
+                key,
+                value: Some(value),
+                accepted,
+            } => {
+                let verdict = if *accepted { "accepted" } else { "rejected" };
+                write!(f, "${key} = {value:?}: {verdict}")
+            }
+            TraceStep::CheckedEnvVar {
+                key, value: None, ..
+            } => write!(f, "${key} is unset"),
+            TraceStep::UsedPasswdHome { home: Some(home) } => {
+                write!(f, "home from password database: {}", home.display())
+            }
+            TraceStep::UsedPasswdHome { home: None } => f.write_str("no home in password database"),
+            TraceStep::QueriedKnownFolder {
+                folder,
+                path: Some(path),
+            } => write!(f, "known folder {folder:?}: {}", path.display()),
+            TraceStep::QueriedKnownFolder { folder, path: None } => {
+                write!(f, "known folder {folder:?} is unknown")
+            }
+            TraceStep::FellBackToHome { home } => {
+                write!(f, "fell back to home {}", home.display())
+            }
+        }
+    }
+}
+
+thread_local! {
+    /// The steps recorded so far, or `None` if no trace is being taken on this thread.
+    static STEPS: RefCell<Option<Vec<TraceStep>>> = const { RefCell::new(None) };
+}
+
+/// Records the step returned by `step`, if a trace is being taken on the current thread.
+///
+/// `step` is only called while tracing, so resolving without a trace costs nothing extra.
+pub(crate) fn record(step: impl FnOnce() -> TraceStep) {
+    STEPS.with_borrow_mut(|steps| {
+        if let Some(steps) = steps {
+            steps.push(step());
+        }
+    });
+}
+
+/// Runs `f` without recording its steps, e.g. for lookups that only feed a warning.
+pub(crate) fn paused<T>(f: impl FnOnce() -> T) -> T {
+    let steps = STEPS.take();
+    let value = f();
+    STEPS.set(steps);
+    value
+}
+
+/// Runs `f` and returns its result together with the steps it recorded.
+fn recording<T>(f: impl FnOnce() -> T) -> (T, Vec<TraceStep>) {
+    let outer = STEPS.replace(Some(Vec::new()));
+    let value = f();
+    let steps = STEPS.replace(outer).unwrap_or_default();
+    (value, steps)
+}
+
+/// Returns [`config_dir`](crate::config_dir) together with the steps considered to resolve it,
+/// in order, e.g. to be pasted into an issue when the path is not the expected one.
+///
+/// Each step implements [`Display`](fmt::Display) for a human-readable report.
+pub fn config_dir_trace() -> (Option<PathBuf>, Vec<TraceStep>) {
+    config_dir_trace_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_trace`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_trace_with<E: Env + ?Sized>(env: &E) -> (Option<PathBuf>, Vec<TraceStep>) {
+    recording(|| config_dir_with(env))
+}
+
+/// Returns [`data_dir`](crate::data_dir) together with the steps considered to resolve it.
+pub fn data_dir_trace() -> (Option<PathBuf>, Vec<TraceStep>) {
+    data_dir_trace_with(&ProcessEnv)
+}
+
+/// Like [`data_dir_trace`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_trace_with<E: Env + ?Sized>(env: &E) -> (Option<PathBuf>, Vec<TraceStep>) {
+    recording(|| data_dir_with(env))
+}
+
+/// Returns [`cache_dir`](crate::cache_dir) together with the steps considered to resolve it.
+pub fn cache_dir_trace() -> (Option<PathBuf>, Vec<TraceStep>) {
+    cache_dir_trace_with(&ProcessEnv)
+}
+
+/// Like [`cache_dir_trace`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_trace_with<E: Env + ?Sized>(env: &E) -> (Option<PathBuf>, Vec<TraceStep>) {
+    recording(|| cache_dir_with(env))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    fn linux_trace_rejects_empty_xdg_then_falls_back_to_home() {
+        let env = MapEnv::new()
+            .with("XDG_CONFIG_HOME", "")
+            .with("HOME", "/home/testuser");
+        let (dir, steps) = config_dir_trace_with(&env);
+        assert_eq!(dir, Some(PathBuf::from("/home/testuser/.config")));
+        // Optional features like `container-host` may check further variables first
+        assert!(
+            steps.ends_with(&[
+                TraceStep::CheckedEnvVar {
+                    key: "XDG_CONFIG_HOME",
+                    value: Some(String::new()),
+                    accepted: false,
+                },
+                TraceStep::CheckedEnvVar {
+                    key: "HOME",
+                    value: Some("/home/testuser".into()),
+                    accepted: true,
+                },
+                TraceStep::FellBackToHome {
+                    home: PathBuf::from("/home/testuser"),
+                },
+            ]),
+            "{steps:?}"
+        );
+    }
+
+    #[test]
+    fn linux_trace_records_accepted_xdg_var() {
+        let env = MapEnv::new()
+            .with("XDG_CACHE_HOME", "/tmp/cache")
+            .with("HOME", "/home/testuser");
+        let (dir, steps) = cache_dir_trace_with(&env);
+        assert_eq!(dir, Some(PathBuf::from("/tmp/cache")));
+        assert_eq!(
+            steps.last(),
+            Some(&TraceStep::CheckedEnvVar {
+                key: "XDG_CACHE_HOME",
+                value: Some("/tmp/cache".into()),
+                accepted: true,
+            })
+        );
+    }
+}
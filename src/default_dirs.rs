@@ -0,0 +1,36 @@
+//! Parses the build-time default of `$XDG_CONFIG_DIRS`, shared with `build.rs`, which rejects an
+//! invalid `DIRS_LITE_DEFAULT_XDG_CONFIG_DIRS` before it is baked into the crate.
+
+/// Splits `dirs` on `:`, skipping empty entries.
+///
+/// Returns an error if an entry is not absolute or no entry remains, e.g. for `::`.
+pub(crate) fn parse_default_dirs(dirs: &str) -> Result<Vec<&str>, String> {
+    let dirs: Vec<&str> = dirs.split(':').filter(|dir| !dir.is_empty()).collect();
+    if let Some(dir) = dirs.iter().find(|dir| !dir.starts_with('/')) {
+        return Err(format!("`{dir}` is not an absolute path"));
+    }
+    if dirs.is_empty() {
+        return Err("no directory is given".to_string());
+    }
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_default_dirs_skips_empty_entries() {
+        assert_eq!(
+            parse_default_dirs(":/etc/xdg::/usr/share/xdg:"),
+            Ok(vec!["/etc/xdg", "/usr/share/xdg"])
+        );
+    }
+
+    #[test]
+    fn parse_default_dirs_rejects_invalid_lists() {
+        assert!(parse_default_dirs("::").is_err());
+        assert!(parse_default_dirs("/etc/xdg:etc/xdg").is_err());
+        assert!(parse_default_dirs("~/xdg").is_err());
+    }
+}
@@ -0,0 +1,117 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::ensure::create;
+use crate::subpath::is_confined;
+
+/// Extension methods for the `Option<PathBuf>` returned by the resolvers, to join and create
+/// a subdirectory in one call:
+///
+/// ```no_run
+/// use dirs_lite::{DirExt, config_dir};
+///
+/// let settings = config_dir().ensure_join("myapp/settings")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub trait DirExt {
+    /// Returns the directory with `name` appended, or `None` if it cannot be resolved.
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use dirs_lite::DirExt;
+    ///
+    /// let dir = Some(PathBuf::from("/home/alice/.config"));
+    /// assert_eq!(dir.join_or_none("myapp"), Some(PathBuf::from("/home/alice/.config/myapp")));
+    /// assert_eq!(None::<PathBuf>.join_or_none("myapp"), None);
+    /// ```
+    fn join_or_none(self, name: impl AsRef<Path>) -> Option<PathBuf>;
+
+    /// Returns the directory with `name` appended, creating it and its missing parents first
+    /// if it does not exist.
+    ///
+    /// On Unix, a newly created directory gets the mode `0700`, like the ones created by
+    /// [`ensure_config_dir`](crate::ensure_config_dir).
+    ///
+    /// Returns an error of kind [`io::ErrorKind::NotFound`] if the directory cannot be resolved,
+    /// and of kind [`io::ErrorKind::InvalidInput`] if `name` would escape it, i.e. contains a
+    /// `..` component or is absolute, see [`config_subpath`](crate::config_subpath).
+    fn ensure_join(self, name: impl AsRef<Path>) -> io::Result<PathBuf>;
+}
+
+impl DirExt for Option<PathBuf> {
+    fn join_or_none(self, name: impl AsRef<Path>) -> Option<PathBuf> {
+        self.map(|mut dir| {
+            dir.push(name);
+            dir
+        })
+    }
+
+    fn ensure_join(self, name: impl AsRef<Path>) -> io::Result<PathBuf> {
+        if !is_confined(name.as_ref()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the name would escape the directory",
+            ));
+        }
+        let dir = self.join_or_none(name).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "the directory cannot be resolved")
+        })?;
+        if !dir.is_dir() {
+            create(&dir, 0o700)?;
+        }
+        Ok(dir)
+    }
+}
+
+#[cfg(all(test, not(target_family = "wasm")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn none_propagates() {
+        assert_eq!(None::<PathBuf>.join_or_none("myapp"), None);
+        let err = None::<PathBuf>.ensure_join("myapp").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn ensure_join_creates_dir() {
+        let base = tempfile::tempdir().unwrap();
+        let dir = Some(base.path().to_path_buf())
+            .ensure_join("myapp/settings")
+            .unwrap();
+        assert_eq!(dir, base.path().join("myapp/settings"));
+        assert!(dir.is_dir());
+        // An existing directory is returned as-is
+        assert_eq!(
+            Some(base.path().to_path_buf())
+                .ensure_join("myapp/settings")
+                .unwrap(),
+            dir
+        );
+    }
+
+    #[test]
+    fn ensure_join_rejects_escaping_names() {
+        let base = tempfile::tempdir().unwrap();
+        let dir = Some(base.path().join("config"));
+        for name in ["../escaped", "myapp/../../escaped", "/tmp/escaped"] {
+            let err = dir.clone().ensure_join(name).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{name}");
+        }
+        assert!(!base.path().join("escaped").exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn ensure_join_creates_private_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let base = tempfile::tempdir().unwrap();
+        let dir = Some(base.path().to_path_buf())
+            .ensure_join("myapp")
+            .unwrap();
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+    }
+}
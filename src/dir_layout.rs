@@ -0,0 +1,197 @@
+use std::borrow::Cow;
+use std::path::{Component, Path, PathBuf};
+
+use crate::{
+    CACHE_DIR, CONFIG_DIR, DATA_DIR, DirStyle, Env, ProcessEnv, cache_dir_result_styled,
+    config_dir_result_styled, data_dir_result_styled,
+};
+
+/// The subdirectories of the home directory the config, data and cache directories fall back to.
+///
+/// The default layout is the one of the free functions, i.e. `.config`, `.local/share` and
+/// `.cache`. The subdirectories are used wherever the free functions append them to a base
+/// directory, i.e. on Linux if the `$XDG_*` variable is unset, and on macOS with the XDG style.
+///
+/// ```
+/// use dirs_lite::DirLayout;
+///
+/// let layout = DirLayout::default()
+///     .config_subdir(".myconfig")
+///     .cache_subdir(".mycache");
+/// let config = layout.config_dir();
+/// ```
+///
+/// A subdirectory has to be relative and stay below the home directory, e.g. `.myconfig` or
+/// `.local/myconfig`, the setters panic on `/etc`, `../shared` or an empty subdirectory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirLayout {
+    pub(crate) config: Cow<'static, str>,
+    pub(crate) data: Cow<'static, str>,
+    pub(crate) cache: Cow<'static, str>,
+}
+
+impl Default for DirLayout {
+    fn default() -> Self {
+        DirLayout {
+            config: Cow::Borrowed(CONFIG_DIR),
+            data: Cow::Borrowed(DATA_DIR),
+            cache: Cow::Borrowed(CACHE_DIR),
+        }
+    }
+}
+
+/// Returns `subdir` if it names a directory below the one it is joined to.
+///
+/// # Panics
+///
+/// If `subdir` is absolute, contains `..` or names no directory, e.g. `""` or `.`.
+#[track_caller]
+fn checked_subdir(subdir: Cow<'static, str>) -> Cow<'static, str> {
+    let path = Path::new(subdir.as_ref());
+    let names_dir = path
+        .components()
+        .any(|component| matches!(component, Component::Normal(_)));
+    assert!(
+        names_dir && crate::subpath::is_confined(path),
+        "the layout subdirectory {subdir:?} has to be relative and stay below the home directory"
+    );
+    subdir
+}
+
+impl DirLayout {
+    /// Returns the layout with the config subdirectory set to `subdir`.
+    ///
+    /// # Panics
+    ///
+    /// If `subdir` is absolute, contains `..` or is empty.
+    #[track_caller]
+    pub fn config_subdir(mut self, subdir: impl Into<Cow<'static, str>>) -> Self {
+        self.config = checked_subdir(subdir.into());
+        self
+    }
+
+    /// Returns the layout with the data subdirectory set to `subdir`.
+    ///
+    /// # Panics
+    ///
+    /// If `subdir` is absolute, contains `..` or is empty.
+    #[track_caller]
+    pub fn data_subdir(mut self, subdir: impl Into<Cow<'static, str>>) -> Self {
+        self.data = checked_subdir(subdir.into());
+        self
+    }
+
+    /// Returns the layout with the cache subdirectory set to `subdir`.
+    ///
+    /// # Panics
+    ///
+    /// If `subdir` is absolute, contains `..` or is empty.
+    #[track_caller]
+    pub fn cache_subdir(mut self, subdir: impl Into<Cow<'static, str>>) -> Self {
+        self.cache = checked_subdir(subdir.into());
+        self
+    }
+
+    /// Like [`config_dir`](crate::config_dir), but with the config subdirectory of this layout.
+    pub fn config_dir(&self) -> Option<PathBuf> {
+        self.config_dir_with(&ProcessEnv)
+    }
+
+    /// Like [`DirLayout::config_dir`], but resolves against the given environment instead of the one of the current process.
+    pub fn config_dir_with<E: Env + ?Sized>(&self, env: &E) -> Option<PathBuf> {
+        config_dir_result_styled(env, DirStyle::default(), self).ok()
+    }
+
+    /// Like [`data_dir`](crate::data_dir), but with the data subdirectory of this layout.
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        self.data_dir_with(&ProcessEnv)
+    }
+
+    /// Like [`DirLayout::data_dir`], but resolves against the given environment instead of the one of the current process.
+    pub fn data_dir_with<E: Env + ?Sized>(&self, env: &E) -> Option<PathBuf> {
+        data_dir_result_styled(env, DirStyle::default(), self).ok()
+    }
+
+    /// Like [`cache_dir`](crate::cache_dir), but with the cache subdirectory of this layout.
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        self.cache_dir_with(&ProcessEnv)
+    }
+
+    /// Like [`DirLayout::cache_dir`], but resolves against the given environment instead of the one of the current process.
+    pub fn cache_dir_with<E: Env + ?Sized>(&self, env: &E) -> Option<PathBuf> {
+        cache_dir_result_styled(env, DirStyle::default(), self).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    fn default_layout_matches_free_functions() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        let layout = DirLayout::default();
+        assert_eq!(layout.config_dir_with(&env), crate::config_dir_with(&env));
+        assert_eq!(layout.data_dir_with(&env), crate::data_dir_with(&env));
+        assert_eq!(layout.cache_dir_with(&env), crate::cache_dir_with(&env));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_layout_overrides_subdirs() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        let layout = DirLayout::default()
+            .config_subdir(".myconfig")
+            .cache_subdir(String::from(".mycache"));
+        assert_eq!(
+            layout.config_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.myconfig"))
+        );
+        assert_eq!(
+            layout.data_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.local/share"))
+        );
+        assert_eq!(
+            layout.cache_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.mycache"))
+        );
+    }
+
+    #[test]
+    fn layout_accepts_nested_subdirs() {
+        let layout = DirLayout::default().data_subdir("./.local/myapp-data");
+        assert_eq!(layout.data, "./.local/myapp-data");
+    }
+
+    #[test]
+    #[should_panic(expected = "has to be relative")]
+    fn layout_rejects_absolute_subdirs() {
+        let _ = DirLayout::default().config_subdir("/etc");
+    }
+
+    #[test]
+    #[should_panic(expected = "has to be relative")]
+    fn layout_rejects_escaping_subdirs() {
+        let _ = DirLayout::default().cache_subdir(".cache/../../shared");
+    }
+
+    #[test]
+    #[should_panic(expected = "has to be relative")]
+    fn layout_rejects_empty_subdirs() {
+        let _ = DirLayout::default().data_subdir(".");
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_layout_keeps_xdg_vars() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CONFIG_HOME", "/custom/config");
+        let layout = DirLayout::default().config_subdir(".myconfig");
+        assert_eq!(
+            layout.config_dir_with(&env),
+            Some(PathBuf::from("/custom/config"))
+        );
+    }
+}
@@ -0,0 +1,381 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::ensure::{create, create_cache, failed};
+use crate::{
+    DirError, DirKind, Env, ProcessEnv, cache_dir_result_with, config_dir_result_with,
+    data_dir_result_with,
+};
+
+/// The config, data and cache directories of the current user.
+///
+/// See [`config_dir`](crate::config_dir), [`data_dir`](crate::data_dir) and
+/// [`cache_dir`](crate::cache_dir) for the values on each platform.
+///
+/// With the feature `serde` enabled, the directories are serialized as strings. Serializing
+/// fails if one of them is not valid Unicode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dirs {
+    /// See [`config_dir`](crate::config_dir).
+    pub config: PathBuf,
+    /// See [`data_dir`](crate::data_dir).
+    pub data: PathBuf,
+    /// See [`cache_dir`](crate::cache_dir).
+    pub cache: PathBuf,
+}
+
+impl Dirs {
+    /// Resolves all directories.
+    ///
+    /// Returns `None` if any of the directories cannot be resolved, see [`Dirs::try_new`] for the reason.
+    pub fn new() -> Option<Dirs> {
+        Self::from_env(&ProcessEnv)
+    }
+
+    /// Like [`Dirs::new`], but resolves against the given environment instead of the one of the current process.
+    pub fn from_env<E: Env + ?Sized>(env: &E) -> Option<Dirs> {
+        Self::try_from_env(env).ok()
+    }
+
+    /// Resolves all directories.
+    ///
+    /// Returns the first error, wrapped in [`DirError::Unresolved`] naming the directory
+    /// that failed, e.g. `cannot resolve the config directory: home directory is unknown`.
+    pub fn try_new() -> Result<Dirs, DirError> {
+        Self::try_from_env(&ProcessEnv)
+    }
+
+    /// Like [`Dirs::try_new`], but resolves against the given environment instead of the one of the current process.
+    pub fn try_from_env<E: Env + ?Sized>(env: &E) -> Result<Dirs, DirError> {
+        DirsBuilder::default().build_from_env(env)
+    }
+
+    /// Returns a builder to set some of the directories explicitly, e.g. in tests, and resolve
+    /// the others.
+    pub fn builder() -> DirsBuilder {
+        DirsBuilder::default()
+    }
+
+    /// Creates the directories that do not exist yet, e.g. after resolving them once with
+    /// [`Dirs::new`].
+    ///
+    /// Like [`ensure_config_dir`](crate::ensure_config_dir), missing parents are created as well,
+    /// new directories get the mode `0700` on Unix, and a new cache directory is excluded from
+    /// Time Machine backups on macOS. Directories nested in each other are fine in any order.
+    ///
+    /// Stops at the first directory that fails, with an error wrapping an
+    /// [`EnsureDirError`](crate::EnsureDirError), see [`ensure_all`](crate::ensure_all).
+    pub fn ensure(&self) -> io::Result<()> {
+        if !self.config.is_dir() {
+            create(&self.config, 0o700).map_err(|e| failed(DirKind::Config, e))?;
+        }
+        if !self.data.is_dir() {
+            create(&self.data, 0o700).map_err(|e| failed(DirKind::Data, e))?;
+        }
+        if !self.cache.is_dir() {
+            create_cache(&self.cache).map_err(|e| failed(DirKind::Cache, e))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the directories that differ between `self` and `other`, as the lowercase name
+    /// of the kind, e.g. `config`, followed by the path in `self` and the one in `other`.
+    ///
+    /// This shows how the directories would change under another environment:
+    ///
+    /// ```
+    /// use dirs_lite::{Dirs, MapEnv};
+    ///
+    /// let before = Dirs::from_env(&MapEnv::new().with("HOME", "/home/alice"));
+    /// let after = Dirs::from_env(&MapEnv::new().with("HOME", "/home/bob"));
+    /// if let (Some(before), Some(after)) = (before, after) {
+    ///     for (kind, old, new) in before.diff(&after) {
+    ///         println!("{kind}: {} -> {}", old.display(), new.display());
+    ///     }
+    /// }
+    /// ```
+    pub fn diff<'a>(&'a self, other: &'a Dirs) -> Vec<(&'static str, &'a Path, &'a Path)> {
+        [
+            ("config", &self.config, &other.config),
+            ("data", &self.data, &other.data),
+            ("cache", &self.cache, &other.cache),
+        ]
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(kind, old, new)| (kind, old.as_path(), new.as_path()))
+        .collect()
+    }
+}
+
+/// Builds [`Dirs`] with some directories set explicitly and the others resolved, see
+/// [`Dirs::builder`].
+///
+/// This constructs `Dirs` for tests without changing the environment, like
+/// [`set_home_override`](crate::set_home_override) but for single directories:
+///
+/// ```
+/// use dirs_lite::Dirs;
+///
+/// let dirs = Dirs::builder().config("/tmp/test/config").build();
+/// if let Ok(dirs) = dirs {
+///     assert_eq!(dirs.config, std::path::Path::new("/tmp/test/config"));
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirsBuilder {
+    config: Option<PathBuf>,
+    data: Option<PathBuf>,
+    cache: Option<PathBuf>,
+}
+
+impl DirsBuilder {
+    /// Sets the config directory instead of resolving it.
+    pub fn config(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config = Some(path.into());
+        self
+    }
+
+    /// Sets the data directory instead of resolving it.
+    pub fn data(mut self, path: impl Into<PathBuf>) -> Self {
+        self.data = Some(path.into());
+        self
+    }
+
+    /// Sets the cache directory instead of resolving it.
+    pub fn cache(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cache = Some(path.into());
+        self
+    }
+
+    /// Returns the directories, resolving those that were not set.
+    ///
+    /// Fails like [`Dirs::try_new`] if a directory was not set and cannot be resolved.
+    pub fn build(self) -> Result<Dirs, DirError> {
+        self.build_from_env(&ProcessEnv)
+    }
+
+    /// Like [`DirsBuilder::build`], but resolves against the given environment instead of the one of the current process.
+    pub fn build_from_env<E: Env + ?Sized>(self, env: &E) -> Result<Dirs, DirError> {
+        let unresolved = |kind| {
+            move |source| DirError::Unresolved {
+                kind,
+                source: Box::new(source),
+            }
+        };
+        Ok(Dirs {
+            config: match self.config {
+                Some(config) => config,
+                None => config_dir_result_with(env).map_err(unresolved(DirKind::Config))?,
+            },
+            data: match self.data {
+                Some(data) => data,
+                None => data_dir_result_with(env).map_err(unresolved(DirKind::Data))?,
+            },
+            cache: match self.cache {
+                Some(cache) => cache,
+                None => cache_dir_result_with(env).map_err(unresolved(DirKind::Cache))?,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn new_matches_individual_functions() {
+        let dirs = Dirs::new().expect("all dirs resolve on supported platforms");
+        assert_eq!(Some(dirs.config), crate::config_dir());
+        assert_eq!(Some(dirs.data), crate::data_dir());
+        assert_eq!(Some(dirs.cache), crate::cache_dir());
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn ensure_creates_all_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let dirs = Dirs {
+            config: root.path().join("config"),
+            data: root.path().join("data"),
+            cache: root.path().join("data/cache"),
+        };
+        std::fs::create_dir(&dirs.config).unwrap();
+
+        dirs.ensure().unwrap();
+        assert!(dirs.config.is_dir());
+        assert!(dirs.data.is_dir());
+        assert!(dirs.cache.is_dir());
+        dirs.ensure().unwrap();
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn ensure_reports_failed_kind() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("file"), "").unwrap();
+        let dirs = Dirs {
+            config: root.path().join("config"),
+            data: root.path().join("data"),
+            cache: root.path().join("file/cache"),
+        };
+
+        let err = dirs.ensure().unwrap_err();
+        let failed = err
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<crate::EnsureDirError>();
+        assert_eq!(failed.unwrap().kind, DirKind::Cache);
+        assert!(dirs.data.is_dir());
+    }
+
+    #[test]
+    fn from_env_is_none_if_any_dir_is_unresolved() {
+        assert_eq!(Dirs::from_env(&MapEnv::new()), None);
+    }
+
+    #[test]
+    fn try_from_env_reports_first_unresolved_dir() {
+        let err = Dirs::try_from_env(&MapEnv::default()).unwrap_err();
+        assert!(matches!(
+            err,
+            DirError::Unresolved {
+                kind: DirKind::Config,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_try_from_env_names_missing_home() {
+        let err = Dirs::try_from_env(&MapEnv::default()).unwrap_err();
+        assert_eq!(
+            err,
+            DirError::Unresolved {
+                kind: DirKind::Config,
+                source: Box::new(DirError::MissingHome),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "cannot resolve the config directory: home directory is unknown"
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_try_from_env_resolves_all_dirs() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+
+        let dirs = Dirs::try_from_env(&env).unwrap();
+        assert_eq!(dirs.data, PathBuf::from("/home/testuser/.local/share"));
+        assert_eq!(dirs.cache, PathBuf::from("/home/testuser/.cache"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_from_env_resolves_xdg_dirs() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CONFIG_HOME", "/xdg/config")
+            .with("XDG_DATA_HOME", "/xdg/data")
+            .with("XDG_CACHE_HOME", "/xdg/cache");
+
+        assert_eq!(
+            Dirs::from_env(&env),
+            Some(Dirs {
+                config: PathBuf::from("/xdg/config"),
+                data: PathBuf::from("/xdg/data"),
+                cache: PathBuf::from("/xdg/cache"),
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn dirs_round_trip_through_json() {
+        let dirs = Dirs {
+            config: PathBuf::from("/home/testuser/.config"),
+            data: PathBuf::from("/home/testuser/.local/share"),
+            cache: PathBuf::from("/home/testuser/.cache"),
+        };
+        let json = serde_json::to_string(&dirs).unwrap();
+        assert_eq!(
+            json,
+            r#"{"config":"/home/testuser/.config","data":"/home/testuser/.local/share","cache":"/home/testuser/.cache"}"#
+        );
+        assert_eq!(serde_json::from_str::<Dirs>(&json).unwrap(), dirs);
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde", target_os = "linux"))]
+    fn linux_dirs_fail_to_serialize_non_utf8_paths() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dirs = Dirs {
+            config: PathBuf::from(OsStr::from_bytes(b"/home/\xff/.config")),
+            data: PathBuf::from("/home/testuser/.local/share"),
+            cache: PathBuf::from("/home/testuser/.cache"),
+        };
+        assert!(serde_json::to_string(&dirs).is_err());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_diff_reports_changed_dirs_only() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        let before = Dirs::from_env(&env).unwrap();
+        let after = Dirs::from_env(&env.with("XDG_CONFIG_HOME", "/xdg/config")).unwrap();
+
+        assert_eq!(
+            before.diff(&after),
+            [(
+                "config",
+                Path::new("/home/testuser/.config"),
+                Path::new("/xdg/config")
+            )]
+        );
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_builder_resolves_unset_dirs() {
+        let env = MapEnv::new().with("HOME", "/home/alice");
+        let dirs = Dirs::builder()
+            .config("/tmp/test/config")
+            .build_from_env(&env)
+            .unwrap();
+        assert_eq!(dirs.config, PathBuf::from("/tmp/test/config"));
+        assert_eq!(dirs.data, PathBuf::from("/home/alice/.local/share"));
+        assert_eq!(dirs.cache, PathBuf::from("/home/alice/.cache"));
+    }
+
+    #[test]
+    fn builder_reports_unset_unresolved_dir() {
+        let err = Dirs::builder()
+            .config("/tmp/test/config")
+            .build_from_env(&MapEnv::new())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DirError::Unresolved {
+                kind: DirKind::Data,
+                ..
+            }
+        ));
+
+        let dirs = Dirs::builder()
+            .config("/c")
+            .data("/d")
+            .cache("/e")
+            .build_from_env(&MapEnv::new())
+            .unwrap();
+        assert_eq!(dirs.cache, PathBuf::from("/e"));
+    }
+}
@@ -0,0 +1,545 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    DirError, DirKind, Dirs, Env, ProcessEnv, bin_dir_result_with, cache_dir_result_with,
+    config_dir_result_with, data_dir_result_with, download_dir_with,
+};
+
+/// Returns [`config_dir`](crate::config_dir), creating it first if it does not exist.
+///
+/// Missing parents are created as well. On Unix, a newly created directory gets the mode
+/// `0700`, so that its contents are only accessible to the current user. An existing
+/// directory is left untouched, also if another process creates it concurrently.
+///
+/// Returns an error of kind [`io::ErrorKind::NotFound`] if the directory cannot be resolved.
+pub fn ensure_config_dir() -> io::Result<PathBuf> {
+    ensure_config_dir_with(&ProcessEnv)
+}
+
+/// Like [`ensure_config_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn ensure_config_dir_with<E: Env + ?Sized>(env: &E) -> io::Result<PathBuf> {
+    ensure(config_dir_result_with(env))
+}
+
+/// Returns [`data_dir`](crate::data_dir), creating it first if it does not exist.
+///
+/// See [`ensure_config_dir`] for details.
+pub fn ensure_data_dir() -> io::Result<PathBuf> {
+    ensure_data_dir_with(&ProcessEnv)
+}
+
+/// Like [`ensure_data_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn ensure_data_dir_with<E: Env + ?Sized>(env: &E) -> io::Result<PathBuf> {
+    ensure(data_dir_result_with(env))
+}
+
+/// Returns [`cache_dir`](crate::cache_dir), creating it first if it does not exist.
+///
+/// See [`ensure_config_dir`] for details. On macOS, a newly created cache directory is also
+/// excluded from Time Machine backups, on a best effort basis.
+pub fn ensure_cache_dir() -> io::Result<PathBuf> {
+    ensure_cache_dir_with(&ProcessEnv)
+}
+
+/// Like [`ensure_cache_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn ensure_cache_dir_with<E: Env + ?Sized>(env: &E) -> io::Result<PathBuf> {
+    let dir = cache_dir_result_with(env).map_err(not_found)?;
+    if !dir.is_dir() {
+        create_cache(&dir)?;
+    }
+    Ok(dir)
+}
+
+/// Returns [`bin_dir`](crate::bin_dir), creating it first if it does not exist.
+///
+/// Unlike the other directories, a newly created bin directory gets the mode `0755` on Unix,
+/// as the binaries in it are meant to be run. Returns an error of kind
+/// [`io::ErrorKind::NotFound`] on Windows, which has no such directory.
+pub fn ensure_bin_dir() -> io::Result<PathBuf> {
+    ensure_bin_dir_with(&ProcessEnv)
+}
+
+/// Like [`ensure_bin_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn ensure_bin_dir_with<E: Env + ?Sized>(env: &E) -> io::Result<PathBuf> {
+    let dir = bin_dir_result_with(env).map_err(not_found)?;
+    if !dir.is_dir() {
+        create(&dir, 0o755)?;
+    }
+    Ok(dir)
+}
+
+/// Returns [`download_dir`](crate::download_dir), creating it first if it does not exist.
+///
+/// Like [`ensure_bin_dir`], a newly created directory gets the mode `0755` on Unix, as
+/// downloads are not meant to be private. Returns an error of kind
+/// [`io::ErrorKind::Unsupported`] if there is no download directory on the current platform.
+pub fn ensure_download_dir() -> io::Result<PathBuf> {
+    ensure_download_dir_with(&ProcessEnv)
+}
+
+/// Like [`ensure_download_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn ensure_download_dir_with<E: Env + ?Sized>(env: &E) -> io::Result<PathBuf> {
+    let dir = download_dir_with(env).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "no download directory on this platform",
+        )
+    })?;
+    if !dir.is_dir() {
+        create(&dir, 0o755)?;
+    }
+    Ok(dir)
+}
+
+/// Creates the missing parent directories of the file `path`, e.g. of one returned by
+/// [`config_file`](crate::config_file), so that the file can be written.
+///
+/// On Unix, newly created directories get the mode `0700`, like [`ensure_config_dir`].
+/// Existing directories are left untouched, the file itself is not created, and a path
+/// without parent, e.g. `/`, is ignored.
+///
+/// ```no_run
+/// if let Some(path) = dirs_lite::config_file("myapp/plugins/foo.toml") {
+///     dirs_lite::ensure_parent_dirs(&path)?;
+///     std::fs::write(&path, "")?;
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn ensure_parent_dirs(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => {
+            let mut builder = fs::DirBuilder::new();
+            builder.recursive(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::DirBuilderExt;
+                builder.mode(0o700);
+            }
+            builder.create(parent)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns the config, data and cache directories, creating them first if they do not exist,
+/// see [`ensure_config_dir`].
+///
+/// Stops at the first directory that fails. The returned error has the kind of the underlying
+/// one, and wraps an [`EnsureDirError`] telling which directory failed:
+///
+/// ```no_run
+/// # use dirs_lite::EnsureDirError;
+/// if let Err(e) = dirs_lite::ensure_all() {
+///     let failed = e.get_ref().and_then(|e| e.downcast_ref::<EnsureDirError>());
+///     eprintln!("{:?}", failed.map(|e| e.kind));
+/// }
+/// ```
+pub fn ensure_all() -> io::Result<Dirs> {
+    ensure_all_with(&ProcessEnv)
+}
+
+/// Like [`ensure_all`], but resolves against the given environment instead of the one of the current process.
+pub fn ensure_all_with<E: Env + ?Sized>(env: &E) -> io::Result<Dirs> {
+    Ok(Dirs {
+        config: ensure_config_dir_with(env).map_err(|e| failed(DirKind::Config, e))?,
+        data: ensure_data_dir_with(env).map_err(|e| failed(DirKind::Data, e))?,
+        cache: ensure_cache_dir_with(env).map_err(|e| failed(DirKind::Cache, e))?,
+    })
+}
+
+/// The error of [`ensure_all`] and [`Dirs::ensure`], telling which directory could not be
+/// resolved or created.
+#[derive(Debug)]
+pub struct EnsureDirError {
+    /// The kind of the directory that failed.
+    pub kind: DirKind,
+    /// The reason it failed.
+    pub source: io::Error,
+}
+
+impl fmt::Display for EnsureDirError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot create the {} directory: {}",
+            self.kind, self.source
+        )
+    }
+}
+
+impl Error for EnsureDirError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+pub(crate) fn failed(kind: DirKind, source: io::Error) -> io::Error {
+    io::Error::new(source.kind(), EnsureDirError { kind, source })
+}
+
+fn ensure(dir: Result<PathBuf, DirError>) -> io::Result<PathBuf> {
+    let dir = dir.map_err(not_found)?;
+    if !dir.is_dir() {
+        create(&dir, 0o700)?;
+    }
+    Ok(dir)
+}
+
+/// Creates the cache directory `dir` like [`create`], and excludes it from backups on macOS.
+pub(crate) fn create_cache(dir: &Path) -> io::Result<()> {
+    create(dir, 0o700)?;
+    #[cfg(target_os = "macos")]
+    {
+        // Failing to exclude the cache is no reason to fail, e.g. on file systems without
+        // extended attributes
+        let _ = crate::macos::exclude_from_backup(dir);
+    }
+    Ok(())
+}
+
+/// Creates `dir` and its missing parents, and sets its mode to `mode` on Unix.
+///
+/// The mode is set after creating `dir`, so that it does not depend on the umask of the process,
+/// and only if this call created it: a directory another process created in the meantime is
+/// left untouched. Missing parents get the default mode.
+///
+/// Fails with [`DirError::NotADirectory`] if `dir` exists but is not a directory.
+#[cfg_attr(not(unix), allow(unused_variables))]
+pub(crate) fn create(dir: &Path, mode: u32) -> io::Result<()> {
+    if dir.exists() && !dir.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotADirectory,
+            DirError::NotADirectory {
+                path: dir.to_path_buf(),
+            },
+        ));
+    }
+    let created = create_dir_all(dir)?;
+    #[cfg(unix)]
+    if created {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir, fs::Permissions::from_mode(mode))?;
+    }
+    Ok(())
+}
+
+/// Like [`fs::create_dir_all`], but tolerates other processes creating `dir` at the same time,
+/// e.g. parallel test workers sharing a home directory on their first run, and returns whether
+/// `dir` itself was created by this call.
+///
+/// An `AlreadyExists` error counts as success if `dir` is a directory by now, and the creation
+/// is retried once after a transient error.
+fn create_dir_all(dir: &Path) -> io::Result<bool> {
+    let create = || {
+        if let Some(parent) = dir.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        match fs::create_dir(dir) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && dir.is_dir() => Ok(false),
+            Err(e) => Err(e),
+        }
+    };
+    match create() {
+        Err(e) if is_transient(e.kind()) => create(),
+        result => result,
+    }
+}
+
+/// Returns whether an error of `kind` may not recur when trying again, e.g. a parent directory
+/// that another process removed and recreated in the meantime.
+fn is_transient(kind: io::ErrorKind) -> bool {
+    matches!(
+        kind,
+        io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::NotFound
+    )
+}
+
+pub(crate) fn not_found(e: DirError) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_ensure_config_dir_creates_private_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let home = tempfile::tempdir().unwrap();
+        let env = crate::MapEnv::new().with("HOME", home.path());
+
+        let config = ensure_config_dir_with(&env).unwrap();
+        assert_eq!(config, home.path().join(".config"));
+        assert!(config.is_dir());
+        let mode = fs::metadata(&config).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        let data = ensure_data_dir_with(&env).unwrap();
+        assert_eq!(data, home.path().join(".local/share"));
+        assert!(data.is_dir());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_ensure_config_dir_mode_ignores_umask() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let home = tempfile::tempdir().unwrap();
+        let existing = home.path().join("existing");
+        fs::create_dir(&existing).unwrap();
+        fs::set_permissions(&existing, fs::Permissions::from_mode(0o755)).unwrap();
+        let mode = |dir: &Path| fs::metadata(dir).unwrap().permissions().mode() & 0o777;
+
+        // SAFETY: `umask` always succeeds, tests run single-threaded with --test-threads=1
+        let umask = unsafe { libc::umask(0o000) };
+        let config = ensure_config_dir_with(&crate::MapEnv::new().with("HOME", home.path()));
+        let existing_config = ensure_config_dir_with(
+            &crate::MapEnv::new()
+                .with("HOME", home.path())
+                .with("XDG_CONFIG_HOME", &existing),
+        );
+        // SAFETY: As above
+        unsafe { libc::umask(umask) };
+
+        let config = config.unwrap();
+        assert!(config.is_dir());
+        assert_eq!(mode(&config), 0o700);
+        // A directory that already exists is left untouched
+        assert_eq!(existing_config.unwrap(), existing);
+        assert_eq!(mode(&existing), 0o755);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_ensure_config_dir_survives_concurrent_first_runs() {
+        let home = tempfile::tempdir().unwrap();
+        let env = crate::MapEnv::new()
+            .with("HOME", home.path())
+            .with("XDG_CONFIG_HOME", home.path().join("a/b/c/config"));
+
+        let barrier = std::sync::Barrier::new(8);
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..8)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        ensure_config_dir_with(&env)
+                    })
+                })
+                .collect();
+            for worker in workers {
+                assert_eq!(
+                    worker.join().unwrap().unwrap(),
+                    home.path().join("a/b/c/config")
+                );
+            }
+        });
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_ensure_config_dir_rejects_file() {
+        let root = tempfile::tempdir().unwrap();
+        let file = root.path().join("config");
+        fs::write(&file, "").unwrap();
+        let env = crate::MapEnv::new()
+            .with("HOME", root.path())
+            .with("XDG_CONFIG_HOME", &file);
+
+        assert_eq!(
+            crate::config_dir_checked_with(&env),
+            Err(DirError::NotADirectory { path: file.clone() })
+        );
+        let err = ensure_config_dir_with(&env).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotADirectory);
+        assert_eq!(
+            err.get_ref().unwrap().downcast_ref::<DirError>(),
+            Some(&DirError::NotADirectory { path: file })
+        );
+
+        let env = crate::MapEnv::new().with("HOME", root.path());
+        assert_eq!(
+            crate::config_dir_checked_with(&env),
+            Ok(root.path().join(".config"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_ensure_cache_dir_keeps_existing_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let home = tempfile::tempdir().unwrap();
+        let cache = home.path().join(".cache");
+        fs::create_dir(&cache).unwrap();
+        fs::set_permissions(&cache, fs::Permissions::from_mode(0o755)).unwrap();
+        let env = crate::MapEnv::new().with("HOME", home.path());
+
+        assert_eq!(ensure_cache_dir_with(&env).unwrap(), cache);
+        assert_eq!(ensure_cache_dir_with(&env).unwrap(), cache);
+        let mode = fs::metadata(&cache).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_ensure_bin_dir_creates_executable_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let home = tempfile::tempdir().unwrap();
+        let env = crate::MapEnv::new().with("HOME", home.path());
+
+        let bin = ensure_bin_dir_with(&env).unwrap();
+        assert_eq!(bin, home.path().join(".local/bin"));
+        assert!(bin.is_dir());
+        let mode = fs::metadata(&bin).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_ensure_download_dir_creates_configured_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let home = tempfile::tempdir().unwrap();
+        let config = home.path().join(".config");
+        fs::create_dir(&config).unwrap();
+        fs::write(
+            config.join("user-dirs.dirs"),
+            "XDG_DOWNLOAD_DIR=\"$HOME/Incoming/Downloads\"\n",
+        )
+        .unwrap();
+        let env = crate::MapEnv::new().with("HOME", home.path());
+
+        let download = ensure_download_dir_with(&env).unwrap();
+        assert_eq!(download, home.path().join("Incoming/Downloads"));
+        assert!(download.is_dir());
+        let mode = fs::metadata(&download).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        fs::write(download.join("file.bin"), "").unwrap();
+        assert_eq!(ensure_download_dir_with(&env).unwrap(), download);
+    }
+
+    #[test]
+    fn ensure_download_dir_is_unsupported_without_dir() {
+        let err = ensure_download_dir_with(&crate::MapEnv::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_ensure_bin_dir_creates_home_bin() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let home = tempfile::tempdir().unwrap();
+        let env = crate::MapEnv::new().with("HOME", home.path());
+
+        let bin = ensure_bin_dir_with(&env).unwrap();
+        assert_eq!(bin, home.path().join("bin"));
+        let mode = fs::metadata(&bin).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_ensure_bin_dir_is_not_found() {
+        let env = crate::MapEnv::new().with("USERPROFILE", "C:\\Users\\testuser");
+        let err = ensure_bin_dir_with(&env).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_ensure_cache_dir_excludes_new_dir_from_backup() {
+        let home = tempfile::tempdir().unwrap();
+        let env = crate::MapEnv::new().with("HOME", home.path());
+
+        let cache = ensure_cache_dir_with(&env).unwrap();
+        assert!(cache.is_dir());
+        assert!(crate::macos::backup_exclusion(&cache).is_some());
+
+        let config = ensure_config_dir_with(&env).unwrap();
+        assert_eq!(crate::macos::backup_exclusion(&config), None);
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn ensure_parent_dirs_creates_parents_only() {
+        let root = tempfile::tempdir().unwrap();
+        let file = root.path().join("plugins/foo/bar.toml");
+
+        ensure_parent_dirs(&file).unwrap();
+        assert!(root.path().join("plugins/foo").is_dir());
+        assert!(!file.exists());
+        ensure_parent_dirs(&file).unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(root.path().join("plugins"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o077, 0);
+        }
+    }
+
+    #[test]
+    fn ensure_parent_dirs_ignores_paths_without_parent() {
+        ensure_parent_dirs(Path::new("/")).unwrap();
+        ensure_parent_dirs(Path::new("file.toml")).unwrap();
+        ensure_parent_dirs(Path::new("")).unwrap();
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_ensure_all_creates_all_dirs() {
+        let home = tempfile::tempdir().unwrap();
+        let env = crate::MapEnv::new().with("HOME", home.path());
+
+        let dirs = ensure_all_with(&env).unwrap();
+        assert_eq!(dirs, Dirs::from_env(&env).unwrap());
+        assert!(dirs.config.is_dir());
+        assert!(dirs.data.is_dir());
+        assert!(dirs.cache.is_dir());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_ensure_all_reports_failed_kind() {
+        let home = tempfile::tempdir().unwrap();
+        fs::write(home.path().join(".local"), "").unwrap();
+        let env = crate::MapEnv::new().with("HOME", home.path());
+
+        let err = ensure_all_with(&env).unwrap_err();
+        let failed = err.get_ref().unwrap().downcast_ref::<EnsureDirError>();
+        assert_eq!(failed.unwrap().kind, DirKind::Data);
+        assert!(
+            err.to_string()
+                .starts_with("cannot create the data directory: ")
+        );
+        assert!(home.path().join(".config").is_dir());
+        assert!(!home.path().join(".cache").exists());
+    }
+
+    #[test]
+    fn ensure_reports_unresolved_dir_as_not_found() {
+        let err = ensure_config_dir_with(&crate::MapEnv::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        let err = ensure_all_with(&crate::MapEnv::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        let failed = err.get_ref().unwrap().downcast_ref::<EnsureDirError>();
+        assert_eq!(failed.unwrap().kind, DirKind::Config);
+    }
+}
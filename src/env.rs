@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// A source of environment variables the directories are resolved from.
+///
+/// [`ProcessEnv`] reads the environment of the current process, which is what the
+/// plain functions like [`config_dir`](crate::config_dir) use. Implement this trait
+/// to resolve directories against a different environment, or use [`MapEnv`].
+#[doc(alias = "EnvProvider")]
+pub trait Env {
+    /// Returns the value of the environment variable `key`, or `None` if it is not set.
+    fn var_os(&self, key: &str) -> Option<OsString>;
+
+    /// Returns the path of the Windows known folder `folder`, or `None` if it is unknown.
+    ///
+    /// This is only consulted on Windows, before falling back to the environment variables.
+    /// The default implementation knows no folders.
+    fn known_folder(&self, folder: KnownFolder) -> Option<PathBuf> {
+        let _ = folder;
+        None
+    }
+
+    /// Returns the home directory of the current user from the password database, or `None`
+    /// if it is unknown.
+    ///
+    /// This is only consulted on Unix, if `$HOME` is unset or not an absolute path. The default
+    /// implementation knows no home directory.
+    fn passwd_home(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Returns the home directory of the user `name` from the password database, or `None`
+    /// if it is unknown.
+    ///
+    /// This is consulted on Unix for the directories of other users, i.e. by
+    /// [`config_dir_for_invoking_user`](crate::config_dir_for_invoking_user) for `$SUDO_USER`,
+    /// by [`config_dir_for_user`](crate::config_dir_for_user) and its data and cache siblings,
+    /// to expand `~user` in the `$XDG_*` variables, and by [`Resolver::snapshot`](crate::Resolver::snapshot)
+    /// for the users referred to that way. The default implementation knows no users.
+    fn user_home(&self, name: &str) -> Option<PathBuf> {
+        let _ = name;
+        None
+    }
+
+    /// Returns the ID of the Windows session the current process runs in, or `None` if it is
+    /// unknown.
+    ///
+    /// This is only consulted on Windows, to keep the [`runtime_dir`](crate::runtime_dir) of
+    /// different sessions apart, e.g. of users logged in over RDP. The default implementation
+    /// knows no session.
+    fn session_id(&self) -> Option<u32> {
+        None
+    }
+}
+
+impl<E: Env + ?Sized> Env for &E {
+    fn var_os(&self, key: &str) -> Option<OsString> {
+        (**self).var_os(key)
+    }
+
+    fn known_folder(&self, folder: KnownFolder) -> Option<PathBuf> {
+        (**self).known_folder(folder)
+    }
+
+    fn passwd_home(&self) -> Option<PathBuf> {
+        (**self).passwd_home()
+    }
+
+    fn user_home(&self, name: &str) -> Option<PathBuf> {
+        (**self).user_home(name)
+    }
+
+    fn session_id(&self) -> Option<u32> {
+        (**self).session_id()
+    }
+}
+
+/// A Windows known folder, see [`Env::known_folder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KnownFolder {
+    /// `FOLDERID_RoamingAppData`, usually `%APPDATA%`.
+    RoamingAppData,
+    /// `FOLDERID_LocalAppData`, usually `%LOCALAPPDATA%`.
+    LocalAppData,
+    /// `FOLDERID_Music`, usually `%USERPROFILE%\Music`.
+    Music,
+    /// `FOLDERID_Pictures`, usually `%USERPROFILE%\Pictures`.
+    Pictures,
+    /// `FOLDERID_Videos`, usually `%USERPROFILE%\Videos`.
+    Videos,
+}
+
+/// The environment of the current process, see [`std::env::var_os`].
+///
+/// With the feature `windows-known-folders` enabled, known folders are looked up with
+/// `SHGetKnownFolderPath` on Windows. The session is always looked up with `ProcessIdToSessionId`.
+///
+/// With the feature `passwd-fallback` enabled, the home directory is looked up with
+/// `getpwuid_r` on Unix if `$HOME` is unset or not an absolute path, and other users with `getpwnam_r`.
+/// Without it, the home directory is still looked up in the password database if `$HOME` is
+/// unset, through [`std::env::home_dir`], but not if it is relative.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessEnv;
+
+impl Env for ProcessEnv {
+    fn var_os(&self, key: &str) -> Option<OsString> {
+        crate::home_override::overridden_var(key).or_else(|| std::env::var_os(key))
+    }
+
+    #[cfg(all(target_os = "windows", feature = "windows-known-folders"))]
+    fn known_folder(&self, folder: KnownFolder) -> Option<PathBuf> {
+        if crate::home_override::is_home_overridden() {
+            return None;
+        }
+        crate::windows::known_folder_path(folder)
+    }
+
+    #[cfg(all(unix, feature = "passwd-fallback"))]
+    fn passwd_home(&self) -> Option<PathBuf> {
+        if crate::home_override::is_home_overridden() {
+            return None;
+        }
+        crate::unix::passwd_home()
+    }
+
+    #[cfg(all(unix, not(feature = "passwd-fallback")))]
+    fn passwd_home(&self) -> Option<PathBuf> {
+        if crate::home_override::is_home_overridden() {
+            return None;
+        }
+        // Returns $HOME as-is if it is set, and only consults the password database otherwise.
+        // Kept on purpose: without the feature `passwd-fallback` and its `libc`, this is the only
+        // dependency-free way to reach `getpwuid_r`, and its behavior on Unix is the one 1.0
+        // shipped. The deprecation on older toolchains is about its former Windows behavior.
+        #[allow(deprecated)]
+        std::env::home_dir().filter(|home| home.is_absolute() && std::env::var_os("HOME").is_none())
+    }
+
+    #[cfg(all(unix, feature = "passwd-fallback"))]
+    fn user_home(&self, name: &str) -> Option<PathBuf> {
+        crate::unix::user_home(name)
+    }
+
+    #[cfg(target_os = "windows")]
+    fn session_id(&self) -> Option<u32> {
+        crate::windows::session_id()
+    }
+}
+
+/// An environment backed by a map, to resolve directories without touching the
+/// environment of the current process, e.g. in tests.
+///
+/// ```
+/// use dirs_lite::{MapEnv, home_dir_with};
+///
+/// let env = MapEnv::new()
+///     .with("HOME", "/home/alice")
+///     .with("USERPROFILE", "C:\\Users\\Alice");
+/// assert!(home_dir_with(&env).is_some());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MapEnv(HashMap<String, OsString>);
+
+impl MapEnv {
+    /// Creates an environment without any variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the environment with the variable `key` set to `value`.
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<OsString>) -> Self {
+        self.set(key, value);
+        self
+    }
+
+    /// Sets the variable `key` to `value`.
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<OsString>) {
+        self.0.insert(key.into(), value.into());
+    }
+
+    /// Removes the variable `key`.
+    pub fn remove(&mut self, key: &str) {
+        self.0.remove(key);
+    }
+}
+
+impl<K: Into<String>, V: Into<OsString>> FromIterator<(K, V)> for MapEnv {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        MapEnv(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl Env for MapEnv {
+    fn var_os(&self, key: &str) -> Option<OsString> {
+        self.0.get(key).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_env_reads_process_environment() {
+        assert_eq!(ProcessEnv.var_os("PATH"), std::env::var_os("PATH"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_unset_home_falls_back_to_password_database() {
+        use crate::env_guard::EnvGuard;
+
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        let env = unsafe { EnvGuard::new().remove("HOME") };
+        let home = ProcessEnv.passwd_home();
+        assert!(
+            home.as_ref().is_some_and(|home| home.is_absolute()),
+            "{home:?}"
+        );
+        assert_eq!(crate::home_dir(), home);
+
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        let _env = unsafe { env.set("HOME", "relative/home") };
+        if cfg!(not(feature = "passwd-fallback")) {
+            assert_eq!(ProcessEnv.passwd_home(), None);
+        }
+    }
+
+    #[test]
+    fn map_env_reads_only_its_own_variables() {
+        let mut env = MapEnv::new().with("HOME", "/home/testuser");
+        env.set("XDG_CONFIG_HOME", "/custom/config");
+        env.remove("HOME");
+
+        assert_eq!(env.var_os("HOME"), None);
+        assert_eq!(env.var_os("XDG_CONFIG_HOME"), Some("/custom/config".into()));
+        assert_eq!(env.var_os("PATH"), None);
+    }
+}
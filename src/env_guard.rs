@@ -0,0 +1,105 @@
+use std::env;
+use std::ffi::{OsStr, OsString};
+
+/// Sets environment variables of the current process for the scope of a test, and restores
+/// their original values, including unset and non-UTF-8 ones, when dropped.
+///
+/// ```ignore
+/// // SAFETY: Tests run single-threaded with --test-threads=1
+/// let _env = unsafe { EnvGuard::new().set("HOME", "/home/testuser").remove("XDG_STATE_HOME") };
+/// ```
+#[must_use = "the variables are restored when the guard is dropped"]
+pub(crate) struct EnvGuard {
+    originals: Vec<(String, Option<OsString>)>,
+}
+
+impl EnvGuard {
+    /// Creates a guard that has not changed any variable yet.
+    pub(crate) fn new() -> Self {
+        EnvGuard {
+            originals: Vec::new(),
+        }
+    }
+
+    /// Sets the variable `key` to `value` until the guard is dropped.
+    ///
+    /// # Safety
+    ///
+    /// No other thread may access the environment while the guard is alive, see [`env::set_var`].
+    pub(crate) unsafe fn set(mut self, key: &str, value: impl AsRef<OsStr>) -> Self {
+        self.save(key);
+        // SAFETY: Upheld by the caller.
+        unsafe { env::set_var(key, value) };
+        self
+    }
+
+    /// Removes the variable `key` until the guard is dropped.
+    ///
+    /// # Safety
+    ///
+    /// No other thread may access the environment while the guard is alive, see [`env::remove_var`].
+    pub(crate) unsafe fn remove(mut self, key: &str) -> Self {
+        self.save(key);
+        // SAFETY: Upheld by the caller.
+        unsafe { env::remove_var(key) };
+        self
+    }
+
+    /// Remembers the original value of `key`, unless it was changed by this guard before.
+    fn save(&mut self, key: &str) {
+        if !self.originals.iter().any(|(saved, _)| saved == key) {
+            self.originals.push((key.to_string(), env::var_os(key)));
+        }
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for (key, original) in self.originals.drain(..).rev() {
+            // SAFETY: The caller of `set` or `remove` guaranteed exclusive access to the
+            // environment while the guard is alive.
+            unsafe {
+                match original {
+                    Some(value) => env::set_var(&key, value),
+                    None => env::remove_var(&key),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_guard_restores_original_values() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let non_utf8 = OsStr::from_bytes(b"/tmp/\xff\xfe");
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        let outer = unsafe { EnvGuard::new().set("DIRS_LITE_GUARD_SET", non_utf8) };
+        {
+            // SAFETY: Tests run single-threaded with --test-threads=1
+            let _inner = unsafe {
+                EnvGuard::new()
+                    .remove("DIRS_LITE_GUARD_SET")
+                    .set("DIRS_LITE_GUARD_UNSET", "value")
+                    .set("DIRS_LITE_GUARD_UNSET", "other")
+            };
+            assert_eq!(env::var_os("DIRS_LITE_GUARD_SET"), None);
+            assert_eq!(
+                env::var_os("DIRS_LITE_GUARD_UNSET"),
+                Some(OsString::from("other"))
+            );
+        }
+        assert_eq!(
+            env::var_os("DIRS_LITE_GUARD_SET").as_deref(),
+            Some(non_utf8)
+        );
+        assert_eq!(env::var_os("DIRS_LITE_GUARD_UNSET"), None);
+
+        drop(outer);
+        assert_eq!(env::var_os("DIRS_LITE_GUARD_SET"), None);
+    }
+}
@@ -0,0 +1,141 @@
+use std::path::PathBuf;
+
+use crate::{Env, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with};
+
+/// Returns the directory in the environment variable `var`, or [`config_dir`](crate::config_dir)
+/// if it is unset.
+///
+/// This lets users of an application force a location, e.g. with `MYAPP_CONFIG_DIR`. Like the
+/// XDG variables, a leading `~` is expanded and the override is ignored if it is empty or not an
+/// absolute path.
+pub fn config_dir_with_override(var: &str) -> Option<PathBuf> {
+    config_dir_with_override_with(&ProcessEnv, var)
+}
+
+/// Like [`config_dir_with_override`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_with_override_with<E: Env + ?Sized>(env: &E, var: &str) -> Option<PathBuf> {
+    override_var(env, var).or_else(|| config_dir_with(env))
+}
+
+/// Returns the directory in the environment variable `var`, or [`data_dir`](crate::data_dir)
+/// if it is unset, see [`config_dir_with_override`].
+pub fn data_dir_with_override(var: &str) -> Option<PathBuf> {
+    data_dir_with_override_with(&ProcessEnv, var)
+}
+
+/// Like [`data_dir_with_override`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_with_override_with<E: Env + ?Sized>(env: &E, var: &str) -> Option<PathBuf> {
+    override_var(env, var).or_else(|| data_dir_with(env))
+}
+
+/// Returns the directory in the environment variable `var`, or [`cache_dir`](crate::cache_dir)
+/// if it is unset, see [`config_dir_with_override`].
+pub fn cache_dir_with_override(var: &str) -> Option<PathBuf> {
+    cache_dir_with_override_with(&ProcessEnv, var)
+}
+
+/// Like [`cache_dir_with_override`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_with_override_with<E: Env + ?Sized>(env: &E, var: &str) -> Option<PathBuf> {
+    override_var(env, var).or_else(|| cache_dir_with(env))
+}
+
+/// Returns the value of `var` if it is a non-empty absolute path, after expanding a leading `~`.
+pub(crate) fn override_var<E: Env + ?Sized>(env: &E, var: &str) -> Option<PathBuf> {
+    env.var_os(var)
+        .and_then(|dir| crate::expand_tilde(env, PathBuf::from(dir)))
+        .filter(|dir| dir.is_absolute())
+        .map(|dir| dir.components().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    #[cfg(unix)]
+    fn override_set_takes_precedence() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("MYAPP_CONFIG_DIR", "/srv/myapp/config/")
+            .with("MYAPP_DATA_DIR", "/srv/myapp/data")
+            .with("MYAPP_CACHE_DIR", "/srv/myapp/cache");
+        assert_eq!(
+            config_dir_with_override_with(&env, "MYAPP_CONFIG_DIR"),
+            Some(PathBuf::from("/srv/myapp/config"))
+        );
+        assert_eq!(
+            data_dir_with_override_with(&env, "MYAPP_DATA_DIR"),
+            Some(PathBuf::from("/srv/myapp/data"))
+        );
+        assert_eq!(
+            cache_dir_with_override_with(&env, "MYAPP_CACHE_DIR"),
+            Some(PathBuf::from("/srv/myapp/cache"))
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn override_set_without_home() {
+        let env = MapEnv::new().with("MYAPP_CONFIG_DIR", "/srv/myapp/config");
+        assert_eq!(
+            config_dir_with_override_with(&env, "MYAPP_CONFIG_DIR"),
+            Some(PathBuf::from("/srv/myapp/config"))
+        );
+    }
+
+    #[test]
+    fn override_empty_falls_back() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming")
+            .with("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local")
+            .with("MYAPP_DIR", "");
+        assert_eq!(
+            config_dir_with_override_with(&env, "MYAPP_DIR"),
+            config_dir_with(&env)
+        );
+        assert_eq!(
+            data_dir_with_override_with(&env, "MYAPP_DIR"),
+            data_dir_with(&env)
+        );
+        assert_eq!(
+            cache_dir_with_override_with(&env, "MYAPP_DIR"),
+            cache_dir_with(&env)
+        );
+    }
+
+    #[test]
+    fn override_relative_falls_back() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming")
+            .with("MYAPP_CONFIG_DIR", "myapp/config");
+        assert_eq!(
+            config_dir_with_override_with(&env, "MYAPP_CONFIG_DIR"),
+            config_dir_with(&env)
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_override_unset_falls_back() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(
+            config_dir_with_override_with(&env, "MYAPP_CONFIG_DIR"),
+            Some(PathBuf::from("/home/testuser/.config"))
+        );
+        assert_eq!(
+            data_dir_with_override_with(&env, "MYAPP_DATA_DIR"),
+            Some(PathBuf::from("/home/testuser/.local/share"))
+        );
+        assert_eq!(
+            cache_dir_with_override_with(&env, "MYAPP_CACHE_DIR"),
+            Some(PathBuf::from("/home/testuser/.cache"))
+        );
+        assert_eq!(
+            config_dir_with_override_with(&MapEnv::new(), "MYAPP_CONFIG_DIR"),
+            None
+        );
+    }
+}
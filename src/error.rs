@@ -0,0 +1,76 @@
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::DirKind;
+
+/// The reason a directory could not be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DirError {
+    /// The directory has no convention on the current platform.
+    UnsupportedPlatform,
+    /// The home directory of the current user is unknown.
+    MissingHome,
+    /// The directory of the current executable is unknown, see [`DirStyle::Portable`](crate::DirStyle::Portable).
+    MissingExecutableDir,
+    /// The environment variable `key` is not set.
+    MissingEnvVar { key: &'static str },
+    /// The environment variable `key` is set to an empty value.
+    EmptyEnvVar { key: &'static str },
+    /// The environment variable `key` is set to a relative path, where an absolute one is required.
+    RelativeEnvVar { key: &'static str },
+    /// The environment variable `key` contains a NUL byte, so no path derived from it can be opened.
+    NulInEnvVar { key: &'static str },
+    /// The directory resolves to `path`, which exists but is not a directory, e.g. a
+    /// `$XDG_CONFIG_HOME` set to a file by mistake.
+    NotADirectory { path: PathBuf },
+    /// The directory `path` leaves only `headroom` characters before the `MAX_PATH` limit of
+    /// Windows, too few for the paths below it.
+    PathTooLong { path: PathBuf, headroom: usize },
+    /// The directory of the given kind could not be resolved, because of `source`.
+    Unresolved {
+        kind: DirKind,
+        source: Box<DirError>,
+    },
+}
+
+impl fmt::Display for DirError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DirError::UnsupportedPlatform => f.write_str("unsupported platform"),
+            DirError::MissingHome => f.write_str("home directory is unknown"),
+            DirError::MissingExecutableDir => {
+                f.write_str("directory of the current executable is unknown")
+            }
+            DirError::MissingEnvVar { key } => write!(f, "environment variable {key} is not set"),
+            DirError::EmptyEnvVar { key } => write!(f, "environment variable {key} is empty"),
+            DirError::RelativeEnvVar { key } => {
+                write!(f, "environment variable {key} is not an absolute path")
+            }
+            DirError::NulInEnvVar { key } => {
+                write!(f, "environment variable {key} contains a NUL byte")
+            }
+            DirError::NotADirectory { path } => {
+                write!(f, "{} exists but is not a directory", path.display())
+            }
+            DirError::PathTooLong { path, headroom } => write!(
+                f,
+                "{} leaves only {headroom} characters before the Windows path length limit",
+                path.display()
+            ),
+            DirError::Unresolved { kind, source } => {
+                write!(f, "cannot resolve the {kind} directory: {source}")
+            }
+        }
+    }
+}
+
+impl Error for DirError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DirError::Unresolved { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
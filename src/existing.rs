@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::{DirKind, Env, ProcessEnv};
+
+/// Returns the config, data, cache, state and runtime directories that exist on disk, each
+/// tagged with the name of its [`DirKind`], e.g. `("config", path)`.
+///
+/// This is meant for diagnostics. Directories that cannot be resolved are skipped like
+/// missing ones, and several kinds may share a path, e.g. config and data on macOS.
+pub fn iter_existing() -> Vec<(&'static str, PathBuf)> {
+    iter_existing_with(&ProcessEnv)
+}
+
+/// Like [`iter_existing`], but resolves against the given environment instead of the one of the current process.
+pub fn iter_existing_with<E: Env + ?Sized>(env: &E) -> Vec<(&'static str, PathBuf)> {
+    resolve_kinds(env)
+        .filter(|(_, dir)| dir.exists())
+        .map(|(kind, dir)| (kind.name(), dir))
+        .collect()
+}
+
+/// Returns every base directory that can be resolved on the current platform, i.e. the config,
+/// data, cache, state and runtime directories, without duplicates.
+///
+/// This is meant for sandboxes that need to know all paths up front, e.g. to pass them to
+/// `unveil` on OpenBSD before dropping privileges. Whether the directories exist is not
+/// checked. Directories shared by several kinds, e.g. config and data on macOS, are listed
+/// once, in the order of their first kind.
+pub fn all_base_dirs() -> Vec<PathBuf> {
+    all_base_dirs_with(&ProcessEnv)
+}
+
+/// Like [`all_base_dirs`], but resolves against the given environment instead of the one of the current process.
+pub fn all_base_dirs_with<E: Env + ?Sized>(env: &E) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for (_, dir) in resolve_kinds(env) {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+/// Returns the directory of every [`DirKind`] that can be resolved on the current platform,
+/// e.g. to include the complete layout of an app in a bug report.
+///
+/// Kinds without a directory, like the runtime directory while `$XDG_RUNTIME_DIR` is unset,
+/// have no entry. Whether the directories exist is not checked.
+pub fn resolve_all() -> HashMap<DirKind, PathBuf> {
+    resolve_all_with(&ProcessEnv)
+}
+
+/// Like [`resolve_all`], but resolves against the given environment instead of the one of the current process.
+pub fn resolve_all_with<E: Env + ?Sized>(env: &E) -> HashMap<DirKind, PathBuf> {
+    resolve_kinds(env).collect()
+}
+
+/// Resolves the directories of every kind, skipping those that cannot be resolved.
+fn resolve_kinds<E: Env + ?Sized>(env: &E) -> impl Iterator<Item = (DirKind, PathBuf)> + '_ {
+    DirKind::all()
+        .into_iter()
+        .filter_map(move |kind| Some((kind, kind.resolve_with(env)?)))
+}
+
+#[cfg(all(
+    test,
+    any(target_os = "linux", target_os = "macos", target_os = "windows")
+))]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_iter_existing_skips_missing_dirs() {
+        let home = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(home.path().join(".config")).unwrap();
+        std::fs::create_dir_all(home.path().join(".local/state")).unwrap();
+        let env = MapEnv::new().with("HOME", home.path());
+
+        assert_eq!(
+            iter_existing_with(&env),
+            [
+                ("config", home.path().join(".config")),
+                ("state", home.path().join(".local/state")),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_all_base_dirs_lists_every_kind() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CACHE_HOME", "/home/testuser/.config")
+            .with("XDG_RUNTIME_DIR", "/run/user/1000");
+
+        assert_eq!(
+            all_base_dirs_with(&env),
+            [
+                PathBuf::from("/home/testuser/.config"),
+                PathBuf::from("/home/testuser/.local/share"),
+                PathBuf::from("/home/testuser/.local/state"),
+                PathBuf::from("/run/user/1000"),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_all_base_dirs_are_deduplicated() {
+        let env = MapEnv::new().with("HOME", "/Users/testuser");
+        let dirs = all_base_dirs_with(&env);
+
+        assert!(dirs.contains(&PathBuf::from(
+            "/Users/testuser/Library/Application Support"
+        )));
+        assert!(dirs.contains(&PathBuf::from("/Users/testuser/Library/Caches")));
+        for (i, dir) in dirs.iter().enumerate() {
+            assert!(!dirs[i + 1..].contains(dir), "{dir:?} is listed twice");
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_all_base_dirs_are_deduplicated() {
+        let env = MapEnv::new()
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming")
+            .with("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local");
+
+        assert_eq!(
+            all_base_dirs_with(&env),
+            [
+                PathBuf::from("C:\\Users\\testuser\\AppData\\Roaming"),
+                PathBuf::from("C:\\Users\\testuser\\AppData\\Local"),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_resolve_all_skips_unresolved_kinds() {
+        let env = MapEnv::new().with("HOME", "/home/alice");
+        let dirs = resolve_all_with(&env);
+
+        assert_eq!(dirs[&DirKind::Config], PathBuf::from("/home/alice/.config"));
+        assert_eq!(
+            dirs[&DirKind::Data],
+            PathBuf::from("/home/alice/.local/share")
+        );
+        assert_eq!(dirs[&DirKind::Cache], PathBuf::from("/home/alice/.cache"));
+        assert!(dirs.contains_key(&DirKind::State));
+        assert!(!dirs.contains_key(&DirKind::Runtime));
+
+        let env = env.with("XDG_RUNTIME_DIR", "/run/user/1000");
+        assert_eq!(
+            resolve_all_with(&env).get(&DirKind::Runtime),
+            Some(&PathBuf::from("/run/user/1000"))
+        );
+    }
+}
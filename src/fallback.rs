@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+
+use crate::{
+    CACHE_DIR, CONFIG_DIR, DATA_DIR, Env, ProcessEnv, cache_dir_with, config_dir_with,
+    data_dir_with,
+};
+
+/// Returns [`config_dir`](crate::config_dir), or `fallback` if it cannot be resolved.
+///
+/// This saves handling `None` at every call site when there is a sensible default, e.g. a
+/// directory next to the executable in single-binary deployments.
+pub fn config_dir_or(fallback: impl Into<PathBuf>) -> PathBuf {
+    config_dir_or_with(&ProcessEnv, fallback)
+}
+
+/// Like [`config_dir_or`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_or_with<E: Env + ?Sized>(env: &E, fallback: impl Into<PathBuf>) -> PathBuf {
+    config_dir_with(env).unwrap_or_else(|| fallback.into())
+}
+
+/// Returns [`data_dir`](crate::data_dir), or `fallback` if it cannot be resolved, see [`config_dir_or`].
+pub fn data_dir_or(fallback: impl Into<PathBuf>) -> PathBuf {
+    data_dir_or_with(&ProcessEnv, fallback)
+}
+
+/// Like [`data_dir_or`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_or_with<E: Env + ?Sized>(env: &E, fallback: impl Into<PathBuf>) -> PathBuf {
+    data_dir_with(env).unwrap_or_else(|| fallback.into())
+}
+
+/// Returns [`cache_dir`](crate::cache_dir), or `fallback` if it cannot be resolved, see [`config_dir_or`].
+pub fn cache_dir_or(fallback: impl Into<PathBuf>) -> PathBuf {
+    cache_dir_or_with(&ProcessEnv, fallback)
+}
+
+/// Like [`cache_dir_or`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_or_with<E: Env + ?Sized>(env: &E, fallback: impl Into<PathBuf>) -> PathBuf {
+    cache_dir_with(env).unwrap_or_else(|| fallback.into())
+}
+
+/// Returns [`config_dir`](crate::config_dir), or `.config` in the current working directory if
+/// it cannot be resolved, e.g. in a CI step without a home directory.
+///
+/// This never fails: if the working directory cannot be determined either, the relative path
+/// `.config` is returned.
+pub fn config_dir_or_cwd() -> PathBuf {
+    config_dir_or_cwd_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_or_cwd`], but resolves against the given environment instead of the one of the current process.
+///
+/// The working directory is still the one of the current process.
+pub fn config_dir_or_cwd_with<E: Env + ?Sized>(env: &E) -> PathBuf {
+    config_dir_with(env).unwrap_or_else(|| cwd_subdir(CONFIG_DIR))
+}
+
+/// Returns [`data_dir`](crate::data_dir), or `.local/share` in the current working directory if
+/// it cannot be resolved, see [`config_dir_or_cwd`].
+pub fn data_dir_or_cwd() -> PathBuf {
+    data_dir_or_cwd_with(&ProcessEnv)
+}
+
+/// Like [`data_dir_or_cwd`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_or_cwd_with<E: Env + ?Sized>(env: &E) -> PathBuf {
+    data_dir_with(env).unwrap_or_else(|| cwd_subdir(DATA_DIR))
+}
+
+/// Returns [`cache_dir`](crate::cache_dir), or `.cache` in the current working directory if
+/// it cannot be resolved, see [`config_dir_or_cwd`].
+pub fn cache_dir_or_cwd() -> PathBuf {
+    cache_dir_or_cwd_with(&ProcessEnv)
+}
+
+/// Like [`cache_dir_or_cwd`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_or_cwd_with<E: Env + ?Sized>(env: &E) -> PathBuf {
+    cache_dir_with(env).unwrap_or_else(|| cwd_subdir(CACHE_DIR))
+}
+
+/// Returns `subdir` in the current working directory, or `subdir` itself if it is unknown.
+fn cwd_subdir(subdir: &str) -> PathBuf {
+    std::env::current_dir().map_or_else(|_| PathBuf::from(subdir), |cwd| cwd.join(subdir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    fn unresolved_dirs_use_fallback() {
+        let env = MapEnv::new();
+        assert_eq!(
+            config_dir_or_with(&env, "/opt/app/config"),
+            PathBuf::from("/opt/app/config")
+        );
+        assert_eq!(
+            data_dir_or_with(&env, "/opt/app/data"),
+            PathBuf::from("/opt/app/data")
+        );
+        assert_eq!(
+            cache_dir_or_with(&env, PathBuf::from("/opt/app/cache")),
+            PathBuf::from("/opt/app/cache")
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_resolved_dirs_ignore_fallback() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(
+            config_dir_or_with(&env, "/opt/app/config"),
+            PathBuf::from("/home/testuser/.config")
+        );
+        assert_eq!(
+            data_dir_or_with(&env, "/opt/app/data"),
+            PathBuf::from("/home/testuser/.local/share")
+        );
+        assert_eq!(
+            cache_dir_or_with(&env, "/opt/app/cache"),
+            PathBuf::from("/home/testuser/.cache")
+        );
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn unresolved_dirs_fall_back_to_cwd() {
+        let env = MapEnv::new();
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(config_dir_or_cwd_with(&env), cwd.join(".config"));
+        assert_eq!(data_dir_or_cwd_with(&env), cwd.join(".local/share"));
+        assert_eq!(cache_dir_or_cwd_with(&env), cwd.join(".cache"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_resolved_dirs_ignore_cwd() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(
+            config_dir_or_cwd_with(&env),
+            PathBuf::from("/home/testuser/.config")
+        );
+    }
+}
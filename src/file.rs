@@ -0,0 +1,156 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::{
+    Env, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with, runtime_dir_with,
+    state_dir_with,
+};
+
+/// Returns the path to the file `name` in [`config_dir`](crate::config_dir), e.g.
+/// `config_file("myapp/settings.toml")`.
+///
+/// Returns `None` if the config directory cannot be resolved, or if `name` could escape it,
+/// i.e. is absolute or contains `..`. The file system is not accessed.
+pub fn config_file(name: impl AsRef<Path>) -> Option<PathBuf> {
+    config_file_with(&ProcessEnv, name)
+}
+
+/// Like [`config_file`], but resolves against the given environment instead of the one of the current process.
+pub fn config_file_with<E: Env + ?Sized>(env: &E, name: impl AsRef<Path>) -> Option<PathBuf> {
+    join_relative(config_dir_with(env)?, name.as_ref())
+}
+
+/// Returns the path to the file `name` in [`data_dir`](crate::data_dir), see [`config_file`].
+pub fn data_file(name: impl AsRef<Path>) -> Option<PathBuf> {
+    data_file_with(&ProcessEnv, name)
+}
+
+/// Like [`data_file`], but resolves against the given environment instead of the one of the current process.
+pub fn data_file_with<E: Env + ?Sized>(env: &E, name: impl AsRef<Path>) -> Option<PathBuf> {
+    join_relative(data_dir_with(env)?, name.as_ref())
+}
+
+/// Returns the path to the file `name` in [`cache_dir`](crate::cache_dir), see [`config_file`].
+pub fn cache_file(name: impl AsRef<Path>) -> Option<PathBuf> {
+    cache_file_with(&ProcessEnv, name)
+}
+
+/// Like [`cache_file`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_file_with<E: Env + ?Sized>(env: &E, name: impl AsRef<Path>) -> Option<PathBuf> {
+    join_relative(cache_dir_with(env)?, name.as_ref())
+}
+
+/// Returns the path to the file `name` in [`state_dir`](crate::state_dir), see [`config_file`].
+pub fn state_file(name: impl AsRef<Path>) -> Option<PathBuf> {
+    state_file_with(&ProcessEnv, name)
+}
+
+/// Like [`state_file`], but resolves against the given environment instead of the one of the current process.
+pub fn state_file_with<E: Env + ?Sized>(env: &E, name: impl AsRef<Path>) -> Option<PathBuf> {
+    join_relative(state_dir_with(env)?, name.as_ref())
+}
+
+/// Returns the path to the file `name` in [`runtime_dir`](crate::runtime_dir), see [`config_file`].
+///
+/// Returns `None` on platforms without a runtime directory.
+pub fn runtime_file(name: impl AsRef<Path>) -> Option<PathBuf> {
+    runtime_file_with(&ProcessEnv, name)
+}
+
+/// Like [`runtime_file`], but resolves against the given environment instead of the one of the current process.
+pub fn runtime_file_with<E: Env + ?Sized>(env: &E, name: impl AsRef<Path>) -> Option<PathBuf> {
+    join_relative(runtime_dir_with(env)?, name.as_ref())
+}
+
+/// Returns `base` with `name` appended, unless `name` is absolute or contains `..`.
+fn join_relative(base: PathBuf, name: &Path) -> Option<PathBuf> {
+    let escapes = name.components().any(|component| {
+        matches!(
+            component,
+            Component::Prefix(_) | Component::RootDir | Component::ParentDir
+        )
+    });
+    (!escapes).then(|| base.join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_file_joins_name_to_dir() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(
+            config_file_with(&env, "settings.toml"),
+            Some(PathBuf::from("/home/testuser/.config/settings.toml"))
+        );
+        assert_eq!(
+            data_file_with(&env, "a/b.toml"),
+            Some(PathBuf::from("/home/testuser/.local/share/a/b.toml"))
+        );
+        assert_eq!(
+            cache_file_with(&env, Path::new("myapp/index")),
+            Some(PathBuf::from("/home/testuser/.cache/myapp/index"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_state_and_runtime_file_join_name_to_dir() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_RUNTIME_DIR", "/run/user/1000");
+        assert_eq!(
+            state_file_with(&env, "myapp/history"),
+            Some(PathBuf::from("/home/testuser/.local/state/myapp/history"))
+        );
+        assert_eq!(
+            runtime_file_with(&env, "myapp.sock"),
+            Some(PathBuf::from("/run/user/1000/myapp.sock"))
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
+    fn macos_runtime_file_is_none() {
+        let env = MapEnv::new().with("HOME", "/Users/testuser");
+        assert_eq!(runtime_file_with(&env, "myapp.sock"), None);
+    }
+
+    #[test]
+    fn file_is_none_without_dir() {
+        let env = MapEnv::new();
+        assert_eq!(config_file_with(&env, "settings.toml"), None);
+        assert_eq!(data_file_with(&env, "a/b.toml"), None);
+        assert_eq!(cache_file_with(&env, "index"), None);
+        assert_eq!(state_file_with(&env, "history"), None);
+        assert_eq!(runtime_file_with(&env, "myapp.sock"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn file_rejects_escaping_names() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(config_file_with(&env, "/etc/passwd"), None);
+        assert_eq!(config_file_with(&env, "../.bashrc"), None);
+        assert_eq!(config_file_with(&env, "myapp/../../.bashrc"), None);
+    }
+
+    #[test]
+    fn join_relative_rejects_escaping_names() {
+        let base = PathBuf::from("base");
+        assert_eq!(
+            join_relative(base.clone(), Path::new("./a/b.toml")),
+            Some(base.join("./a/b.toml"))
+        );
+        assert_eq!(join_relative(base.clone(), Path::new("..")), None);
+        if cfg!(target_os = "windows") {
+            assert_eq!(
+                join_relative(base.clone(), Path::new("C:settings.toml")),
+                None
+            );
+            assert_eq!(join_relative(base, Path::new("\\settings.toml")), None);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::{PoisonError, RwLock};
+
+/// The home directory set by [`set_home_override`].
+static HOME_OVERRIDE: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Makes the functions resolving against the environment of the current process, e.g.
+/// [`config_dir`](crate::config_dir), resolve relative to `home` in the whole process, or
+/// restores the default behavior with `None`.
+///
+/// This is meant for the tests of applications, which can then redirect their directories
+/// without mutating the environment of the process. It is off by default. While it is set,
+/// [`ProcessEnv`](crate::ProcessEnv) reports `home` as `$HOME` and `%USERPROFILE%`, and as
+/// `%APPDATA%` and `%LOCALAPPDATA%` with `AppData\Roaming` and `AppData\Local` appended on
+/// Windows, instead of consulting the password database or the Windows known folders.
+///
+/// The `$XDG_*` variables are excluded: they are still read from the environment and take
+/// precedence, e.g. a set `$XDG_CONFIG_HOME` is returned by `config_dir()` as before. Unset
+/// them, or resolve against a [`MapEnv`](crate::MapEnv) with the `_with` functions, to redirect
+/// every directory.
+///
+/// ```
+/// dirs_lite::set_home_override(Some("/tmp/test-home".into()));
+/// // config_dir() is now e.g. `/tmp/test-home/.config` on Linux
+/// dirs_lite::set_home_override(None);
+/// ```
+///
+/// The override applies to all threads, so tests setting different homes have to run one
+/// after the other. Setting or clearing it forgets the directories memoized by the `_cached`
+/// functions, see [`invalidate_cache`](crate::invalidate_cache).
+pub fn set_home_override(home: Option<PathBuf>) {
+    *HOME_OVERRIDE
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = home;
+    crate::invalidate_cache();
+}
+
+/// Returns whether a home directory is set with [`set_home_override`].
+#[cfg(any(all(target_os = "windows", feature = "windows-known-folders"), unix))]
+pub(crate) fn is_home_overridden() -> bool {
+    HOME_OVERRIDE
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_some()
+}
+
+/// Returns the value of the variable `key` under the home directory set with
+/// [`set_home_override`], or `None` if no home directory is set or `key` does not name it.
+pub(crate) fn overridden_var(key: &str) -> Option<OsString> {
+    let home = HOME_OVERRIDE.read().unwrap_or_else(PoisonError::into_inner);
+    let home = home.as_ref()?;
+    match key {
+        "HOME" | "USERPROFILE" => Some(home.clone().into_os_string()),
+        "APPDATA" if cfg!(target_os = "windows") => {
+            Some(home.join("AppData").join("Roaming").into_os_string())
+        }
+        "LOCALAPPDATA" if cfg!(target_os = "windows") => {
+            Some(home.join("AppData").join("Local").into_os_string())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(all(test, not(target_family = "wasm")))]
+mod tests {
+    use super::*;
+    use crate::{cache_dir, config_dir, data_dir, home_dir};
+
+    fn test_home() -> PathBuf {
+        PathBuf::from(if cfg!(windows) {
+            "C:\\test\\home"
+        } else {
+            "/test/home"
+        })
+    }
+
+    #[test]
+    fn home_override_redirects_process_dirs() {
+        let home = test_home();
+        let original = (config_dir(), data_dir(), cache_dir());
+
+        set_home_override(Some(home.clone()));
+        let overridden = (home_dir(), config_dir(), data_dir(), cache_dir());
+        set_home_override(None);
+
+        assert_eq!(overridden.0.as_ref(), Some(&home));
+        // A base directory set in the environment still takes precedence
+        let vars = ["XDG_CONFIG_HOME", "XDG_DATA_HOME", "XDG_CACHE_HOME"];
+        for (dir, var) in [overridden.1, overridden.2, overridden.3]
+            .into_iter()
+            .zip(vars)
+        {
+            if std::env::var_os(var).is_some() {
+                continue;
+            }
+            let dir = dir.expect("dirs resolve under the overridden home");
+            assert!(dir.starts_with(&home), "{dir:?}");
+            assert_ne!(dir, home);
+        }
+        assert_eq!((config_dir(), data_dir(), cache_dir()), original);
+    }
+
+    #[test]
+    fn home_override_applies_to_every_thread() {
+        let original = home_dir();
+
+        set_home_override(Some(test_home()));
+        let other_thread = std::thread::spawn(home_dir).join().unwrap();
+        set_home_override(None);
+
+        assert_eq!(other_thread, Some(test_home()));
+        assert_eq!(home_dir(), original);
+    }
+}
@@ -0,0 +1,544 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::{Env, ProcessEnv, non_empty_var, xdg_var};
+
+/// A platform family with its own directory convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Platform {
+    /// Linux and the BSDs, following the XDG Base Directory Specification.
+    Linux,
+    /// macOS, following the Apple File System Programming Guide.
+    MacOs,
+    /// Windows, following the Known Folder conventions.
+    Windows,
+    /// Any other platform, where no directory is resolved.
+    Other,
+}
+
+/// Returns the platform whose directory convention is in effect for the current build target.
+///
+/// The BSDs and WASI follow the XDG convention and are reported as [`Platform::Linux`].
+pub const fn current_platform() -> Platform {
+    if crate::is_xdg_platform() {
+        Platform::Linux
+    } else if cfg!(target_os = "macos") {
+        Platform::MacOs
+    } else if cfg!(target_os = "windows") {
+        Platform::Windows
+    } else {
+        Platform::Other
+    }
+}
+
+impl Platform {
+    /// Returns the config directory following the convention of this platform, e.g. to preview
+    /// the paths of other platforms.
+    ///
+    /// Unlike [`config_dir`](crate::config_dir), only the variables of the convention are read,
+    /// `$HOME` on Linux and macOS and `%APPDATA%` or `%LOCALAPPDATA%` on Windows, and known
+    /// folders are not queried. Like there, a `$HOME` or `$XDG_*` variable that is not an
+    /// absolute path, judged by the current platform, counts as unset. On macOS, the feature
+    /// `favor-xdg-style` is respected. Returns `None` for [`Platform::Other`].
+    pub fn config_dir(&self) -> Option<PathBuf> {
+        self.config_dir_with(&ProcessEnv)
+    }
+
+    /// Like [`Platform::config_dir`], but resolves against the given environment instead of the one of the current process.
+    pub fn config_dir_with<E: Env + ?Sized>(&self, env: &E) -> Option<PathBuf> {
+        match self {
+            Platform::Linux => xdg(env, "XDG_CONFIG_HOME").or_else(|| home(env, ".config")),
+            Platform::MacOs if xdg_style() => home(env, ".config"),
+            Platform::MacOs => home(env, "Library/Application Support"),
+            Platform::Windows => non_empty_var(env, "APPDATA").ok(),
+            Platform::Other => None,
+        }
+    }
+
+    /// Returns the data directory following the convention of this platform, see [`Platform::config_dir`].
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        self.data_dir_with(&ProcessEnv)
+    }
+
+    /// Like [`Platform::data_dir`], but resolves against the given environment instead of the one of the current process.
+    pub fn data_dir_with<E: Env + ?Sized>(&self, env: &E) -> Option<PathBuf> {
+        match self {
+            Platform::Linux => xdg(env, "XDG_DATA_HOME").or_else(|| home(env, ".local/share")),
+            Platform::MacOs if xdg_style() => home(env, ".local/share"),
+            Platform::MacOs if cfg!(feature = "macos-split") => {
+                home(env, "Library/Application Support/Data")
+            }
+            Platform::MacOs => home(env, "Library/Application Support"),
+            Platform::Windows => non_empty_var(env, "LOCALAPPDATA").ok(),
+            Platform::Other => None,
+        }
+    }
+
+    /// Returns the cache directory following the convention of this platform, see [`Platform::config_dir`].
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        self.cache_dir_with(&ProcessEnv)
+    }
+
+    /// Like [`Platform::cache_dir`], but resolves against the given environment instead of the one of the current process.
+    pub fn cache_dir_with<E: Env + ?Sized>(&self, env: &E) -> Option<PathBuf> {
+        match self {
+            Platform::Linux => xdg(env, "XDG_CACHE_HOME").or_else(|| home(env, ".cache")),
+            Platform::MacOs if xdg_style() => home(env, ".cache"),
+            Platform::MacOs => home(env, "Library/Caches"),
+            Platform::Windows => non_empty_var(env, "LOCALAPPDATA").ok(),
+            Platform::Other => None,
+        }
+    }
+}
+
+/// Returns whether the XDG style is selected on macOS.
+fn xdg_style() -> bool {
+    DirStyle::default() == DirStyle::Xdg
+}
+
+/// Returns the XDG variable `key` if it is an absolute path, see [`xdg_var`].
+fn xdg<E: Env + ?Sized>(env: &E, key: &'static str) -> Option<PathBuf> {
+    xdg_var(env, key).ok()
+}
+
+/// Returns `$HOME` with `subdir` appended, if it is an absolute path.
+///
+/// Unlike [`home_dir`](crate::home_dir), the home directory of the current platform, e.g.
+/// `%USERPROFILE%` on Windows, and the password database are not consulted.
+fn home<E: Env + ?Sized>(env: &E, subdir: &str) -> Option<PathBuf> {
+    non_empty_var(env, "HOME")
+        .ok()
+        .filter(|home| home.is_absolute())
+        .map(|home| home.join(subdir))
+}
+
+/// The directory convention followed on macOS, see [`config_dir_styled`](crate::config_dir_styled).
+///
+/// Other platforms have a single convention and ignore the style, except for [`DirStyle::Portable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DirStyle {
+    /// The native convention, e.g. `$HOME/Library/Application Support`.
+    Native,
+    /// The XDG convention, e.g. `$HOME/.config`.
+    Xdg,
+    /// Directories next to the current executable on every platform, e.g. `<exe_dir>/config`,
+    /// for self-contained distributions like on a USB stick.
+    Portable,
+}
+
+impl Default for DirStyle {
+    /// Returns the style selected by the feature `favor-xdg-style`.
+    fn default() -> Self {
+        crate::macos_style()
+    }
+}
+
+/// A kind of directory this crate resolves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DirKind {
+    /// See [`config_dir`](crate::config_dir).
+    Config,
+    /// See [`data_dir`](crate::data_dir).
+    Data,
+    /// See [`cache_dir`](crate::cache_dir).
+    Cache,
+    /// See [`state_dir`](crate::state_dir).
+    State,
+    /// See [`runtime_dir`](crate::runtime_dir).
+    Runtime,
+}
+
+/// The error returned when parsing an unknown [`DirKind`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDirKindError(String);
+
+impl fmt::Display for ParseDirKindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown directory kind `{}`", self.0)
+    }
+}
+
+impl std::error::Error for ParseDirKindError {}
+
+impl FromStr for DirKind {
+    type Err = ParseDirKindError;
+
+    /// Parses the lowercase name of a kind, as returned by its [`Display`](fmt::Display) implementation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "config" => Ok(DirKind::Config),
+            "data" => Ok(DirKind::Data),
+            "cache" => Ok(DirKind::Cache),
+            "state" => Ok(DirKind::State),
+            "runtime" => Ok(DirKind::Runtime),
+            _ => Err(ParseDirKindError(s.to_string())),
+        }
+    }
+}
+
+impl DirKind {
+    /// Returns every kind, in the order config, data, cache, state and runtime.
+    ///
+    /// ```
+    /// use dirs_lite::DirKind;
+    ///
+    /// for kind in DirKind::all() {
+    ///     println!("{kind}: {:?}", kind.resolve());
+    /// }
+    /// ```
+    pub const fn all() -> [DirKind; 5] {
+        [
+            DirKind::Config,
+            DirKind::Data,
+            DirKind::Cache,
+            DirKind::State,
+            DirKind::Runtime,
+        ]
+    }
+
+    /// Returns whether the contents of directories of this kind can be deleted without losing
+    /// user data, as they are regenerated on demand. This is only true for [`DirKind::Cache`].
+    ///
+    /// The runtime directory is short-lived too, but holds sockets and locks of running
+    /// processes, so it is not purgeable.
+    pub const fn is_purgeable(self) -> bool {
+        matches!(self, DirKind::Cache)
+    }
+
+    /// Returns whether the directory of this kind follows the user across machines in a
+    /// roaming profile. This is only true for [`DirKind::Config`] on Windows, `%APPDATA%`.
+    ///
+    /// Other platforms have no roaming profiles. The data directory does not roam on Windows
+    /// either, see [`roaming_data_dir`](crate::roaming_data_dir).
+    pub const fn is_roaming(self) -> bool {
+        cfg!(target_os = "windows") && matches!(self, DirKind::Config)
+    }
+
+    /// Resolves the directory of this kind, e.g. [`config_dir`](crate::config_dir) for
+    /// [`DirKind::Config`].
+    pub fn resolve(self) -> Option<PathBuf> {
+        self.resolve_with(&ProcessEnv)
+    }
+
+    /// Like [`DirKind::resolve`], but resolves against the given environment instead of the one of the current process.
+    pub fn resolve_with<E: Env + ?Sized>(self, env: &E) -> Option<PathBuf> {
+        match self {
+            DirKind::Config => crate::config_dir_with(env),
+            DirKind::Data => crate::data_dir_with(env),
+            DirKind::Cache => crate::cache_dir_with(env),
+            DirKind::State => crate::state_dir_with(env),
+            DirKind::Runtime => crate::runtime_dir_with(env),
+        }
+    }
+
+    /// Returns the lowercase name of the kind, as used by its [`Display`](fmt::Display)
+    /// implementation.
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            DirKind::Config => "config",
+            DirKind::Data => "data",
+            DirKind::Cache => "cache",
+            DirKind::State => "state",
+            DirKind::Runtime => "runtime",
+        }
+    }
+}
+
+impl fmt::Display for DirKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Returns the documented layout for each platform and directory kind.
+///
+/// The entries mirror the tables in the docs of [`config_dir`](crate::config_dir),
+/// [`data_dir`](crate::data_dir), [`cache_dir`](crate::cache_dir),
+/// [`state_dir`](crate::state_dir) and [`runtime_dir`](crate::runtime_dir), e.g.
+/// `(Platform::Linux, DirKind::Config, "$XDG_CONFIG_HOME or $HOME/.config")`.
+///
+/// Combinations without a directory, like the runtime directory on macOS, have no entry.
+///
+/// NOTE: the macOS and runtime entries reflect whether the feature `favor-xdg-style` is enabled,
+/// the macOS data entry also whether `macos-split` is.
+pub fn layout_table() -> Vec<(Platform, DirKind, &'static str)> {
+    let xdg_style = xdg_style();
+    let mut table = vec![
+        (
+            Platform::Linux,
+            DirKind::Config,
+            "$XDG_CONFIG_HOME or $HOME/.config",
+        ),
+        (
+            Platform::Linux,
+            DirKind::Data,
+            "$XDG_DATA_HOME or $HOME/.local/share",
+        ),
+        (
+            Platform::Linux,
+            DirKind::Cache,
+            "$XDG_CACHE_HOME or $HOME/.cache",
+        ),
+        (
+            Platform::MacOs,
+            DirKind::Config,
+            if xdg_style {
+                "$HOME/.config"
+            } else {
+                "$HOME/Library/Application Support"
+            },
+        ),
+        (
+            Platform::MacOs,
+            DirKind::Data,
+            if xdg_style {
+                "$HOME/.local/share"
+            } else if cfg!(feature = "macos-split") {
+                "$HOME/Library/Application Support/Data"
+            } else {
+                "$HOME/Library/Application Support"
+            },
+        ),
+        (
+            Platform::MacOs,
+            DirKind::Cache,
+            if xdg_style {
+                "$HOME/.cache"
+            } else {
+                "$HOME/Library/Caches"
+            },
+        ),
+        (Platform::Windows, DirKind::Config, "%APPDATA%"),
+        (Platform::Windows, DirKind::Data, "%LOCALAPPDATA%"),
+        (Platform::Windows, DirKind::Cache, "%LOCALAPPDATA%"),
+        (
+            Platform::Linux,
+            DirKind::State,
+            "$XDG_STATE_HOME or $HOME/.local/state",
+        ),
+        (
+            Platform::MacOs,
+            DirKind::State,
+            if xdg_style {
+                "$HOME/.local/state"
+            } else {
+                "$HOME/Library/Application Support"
+            },
+        ),
+        (Platform::Windows, DirKind::State, "%LOCALAPPDATA%"),
+        (Platform::Linux, DirKind::Runtime, "$XDG_RUNTIME_DIR"),
+    ];
+    if xdg_style {
+        table.push((Platform::MacOs, DirKind::Runtime, "$HOME/.cache"));
+        table.push((Platform::Windows, DirKind::Runtime, "%LOCALAPPDATA%"));
+    } else {
+        table.push((Platform::Windows, DirKind::Runtime, "%TEMP%\\session-<id>"));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_table_covers_core_kinds_on_all_platforms() {
+        let table = layout_table();
+        for platform in [Platform::Linux, Platform::MacOs, Platform::Windows] {
+            for kind in [DirKind::Config, DirKind::Data, DirKind::Cache] {
+                assert_eq!(
+                    table
+                        .iter()
+                        .filter(|(p, k, _)| *p == platform && *k == kind)
+                        .count(),
+                    1,
+                    "expected exactly one layout for {platform:?} {kind:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn dir_kind_parses_its_display_name() {
+        for kind in [
+            DirKind::Config,
+            DirKind::Data,
+            DirKind::Cache,
+            DirKind::State,
+            DirKind::Runtime,
+        ] {
+            assert_eq!(kind.to_string().parse(), Ok(kind));
+        }
+        assert!("Config".parse::<DirKind>().is_err());
+    }
+
+    #[test]
+    fn current_platform_matches_build_target() {
+        let expected = if cfg!(any(
+            target_os = "linux",
+            target_os = "freebsd",
+            target_os = "openbsd",
+            target_os = "netbsd",
+            target_os = "dragonfly",
+            target_os = "wasi"
+        )) {
+            Platform::Linux
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else if cfg!(target_os = "windows") {
+            Platform::Windows
+        } else {
+            Platform::Other
+        };
+        assert_eq!(current_platform(), expected);
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    fn current_platform_resolves_like_dir_functions() {
+        let env = crate::MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("USERPROFILE", "C:\\Users\\testuser")
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming")
+            .with("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local");
+        let platform = current_platform();
+        assert_eq!(platform.config_dir_with(&env), crate::config_dir_with(&env));
+        assert_eq!(platform.data_dir_with(&env), crate::data_dir_with(&env));
+        assert_eq!(platform.cache_dir_with(&env), crate::cache_dir_with(&env));
+    }
+
+    #[test]
+    // POSIX paths are not absolute on Windows
+    #[cfg(not(target_os = "windows"))]
+    fn linux_platform_resolves_xdg_dirs() {
+        let env = crate::MapEnv::new()
+            .with("HOME", "/home/alice")
+            .with("XDG_CACHE_HOME", "/tmp/cache");
+        assert_eq!(
+            Platform::Linux.config_dir_with(&env),
+            Some(PathBuf::from("/home/alice").join(".config"))
+        );
+        assert_eq!(
+            Platform::Linux.data_dir_with(&env),
+            Some(PathBuf::from("/home/alice").join(".local/share"))
+        );
+        assert_eq!(
+            Platform::Linux.cache_dir_with(&env),
+            Some(PathBuf::from("/tmp/cache"))
+        );
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn linux_platform_ignores_relative_vars() {
+        let env = crate::MapEnv::new()
+            .with("HOME", "/home/alice")
+            .with("XDG_CONFIG_HOME", "relative/config");
+        assert_eq!(
+            Platform::Linux.config_dir_with(&env),
+            Some(PathBuf::from("/home/alice/.config"))
+        );
+
+        let env = crate::MapEnv::new().with("HOME", "relative/home");
+        assert_eq!(Platform::Linux.cache_dir_with(&env), None);
+        assert_eq!(Platform::MacOs.cache_dir_with(&env), None);
+    }
+
+    #[test]
+    #[cfg(not(any(
+        target_os = "windows",
+        feature = "favor-xdg-style",
+        feature = "macos-split"
+    )))]
+    fn macos_platform_resolves_library_dirs() {
+        let env = crate::MapEnv::new().with("HOME", "/Users/Alice");
+        let home = PathBuf::from("/Users/Alice");
+        assert_eq!(
+            Platform::MacOs.config_dir_with(&env),
+            Some(home.join("Library/Application Support"))
+        );
+        assert_eq!(
+            Platform::MacOs.data_dir_with(&env),
+            Some(home.join("Library/Application Support"))
+        );
+        assert_eq!(
+            Platform::MacOs.cache_dir_with(&env),
+            Some(home.join("Library/Caches"))
+        );
+    }
+
+    #[test]
+    fn windows_platform_resolves_appdata() {
+        let env = crate::MapEnv::new()
+            .with("APPDATA", "C:\\Users\\Alice\\AppData\\Roaming")
+            .with("LOCALAPPDATA", "C:\\Users\\Alice\\AppData\\Local");
+        assert_eq!(
+            Platform::Windows.config_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\Alice\\AppData\\Roaming"))
+        );
+        assert_eq!(
+            Platform::Windows.data_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\Alice\\AppData\\Local"))
+        );
+        assert_eq!(
+            Platform::Windows.cache_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\Alice\\AppData\\Local"))
+        );
+        assert_eq!(
+            Platform::Windows.config_dir_with(&crate::MapEnv::new()),
+            None
+        );
+    }
+
+    #[test]
+    fn other_platform_resolves_no_dirs() {
+        let env = crate::MapEnv::new().with("HOME", "/home/alice");
+        assert_eq!(Platform::Other.config_dir_with(&env), None);
+        assert_eq!(Platform::Other.data_dir_with(&env), None);
+        assert_eq!(Platform::Other.cache_dir_with(&env), None);
+    }
+
+    #[test]
+    fn layout_table_mirrors_linux_docs() {
+        assert!(layout_table().contains(&(
+            Platform::Linux,
+            DirKind::Config,
+            "$XDG_CONFIG_HOME or $HOME/.config"
+        )));
+    }
+
+    #[test]
+    fn dir_kind_properties() {
+        assert!(DirKind::Cache.is_purgeable());
+        assert!(!DirKind::Config.is_purgeable());
+        assert!(!DirKind::Runtime.is_purgeable());
+        assert_eq!(DirKind::Config.is_roaming(), cfg!(target_os = "windows"));
+        assert!(!DirKind::Data.is_roaming());
+        assert!(!DirKind::Cache.is_roaming());
+    }
+
+    #[test]
+    fn dir_kind_resolve_matches_free_functions() {
+        let env = crate::MapEnv::new()
+            .with("HOME", "/home/alice")
+            .with("USERPROFILE", "C:\\Users\\Alice")
+            .with("LOCALAPPDATA", "C:\\Users\\Alice\\AppData\\Local")
+            .with("APPDATA", "C:\\Users\\Alice\\AppData\\Roaming")
+            .with("XDG_RUNTIME_DIR", "/run/user/1000");
+        assert_eq!(DirKind::all().len(), 5);
+        assert_eq!(
+            DirKind::all().map(|kind| kind.resolve_with(&env)),
+            [
+                crate::config_dir_with(&env),
+                crate::data_dir_with(&env),
+                crate::cache_dir_with(&env),
+                crate::state_dir_with(&env),
+                crate::runtime_dir_with(&env),
+            ]
+        );
+        assert_eq!(DirKind::Config.resolve(), crate::config_dir());
+    }
+}
@@ -0,0 +1,79 @@
+//! Deprecated behavior of earlier releases, kept as a migration path.
+//!
+//! Enable the feature `legacy` to use it. Every function in here will be removed in the
+//! next major release.
+
+use std::path::PathBuf;
+
+use crate::{DirLayout, DirSource, DirStyle, Env, ProcessEnv, config_dir_styled_into};
+
+/// Returns the path of the user's config directory as earlier releases did.
+///
+/// On Linux and the BSDs, `.config` was appended to `$XDG_CONFIG_HOME` as well, e.g. `XDG_CONFIG_HOME=/custom/config`
+/// resolved to `/custom/config/.config` instead of `/custom/config`. On all other platforms, and
+/// if `$XDG_CONFIG_HOME` is unset or empty, this is the same as [`crate::config_dir`].
+#[deprecated(
+    note = "use `dirs_lite::config_dir` and move existing files out of `$XDG_CONFIG_HOME/.config`"
+)]
+pub fn config_dir() -> Option<PathBuf> {
+    #[allow(deprecated)]
+    config_dir_with(&ProcessEnv)
+}
+
+/// Like [`config_dir`], but resolves against the given environment instead of the one of the current process.
+#[deprecated(
+    note = "use `dirs_lite::config_dir_with` and move existing files out of `$XDG_CONFIG_HOME/.config`"
+)]
+pub fn config_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    let mut config = PathBuf::new();
+    let source =
+        config_dir_styled_into(env, DirStyle::default(), &DirLayout::default(), &mut config)
+            .ok()?;
+    // Only a $XDG_CONFIG_HOME that was accepted, after expanding a leading `~`, got the suffix
+    if source == DirSource::Xdg {
+        config.push(crate::CONFIG_DIR);
+    }
+    Some(config)
+}
+
+#[cfg(all(test, not(target_os = "windows")))]
+#[allow(deprecated)]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_legacy_config_dir_appends_to_xdg_config_home() {
+        let env = MapEnv::new().with("XDG_CONFIG_HOME", "/custom/config");
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from("/custom/config/.config"))
+        );
+        assert_eq!(
+            crate::config_dir_with(&env),
+            Some(PathBuf::from("/custom/config"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_legacy_config_dir_compares_the_expanded_xdg_config_home() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CONFIG_HOME", "~/cfg");
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/cfg/.config"))
+        );
+
+        let env = env.with("XDG_CONFIG_HOME", "relative/config");
+        assert_eq!(config_dir_with(&env), crate::config_dir_with(&env));
+    }
+
+    #[test]
+    fn legacy_config_dir_matches_home_fallback() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(config_dir_with(&env), crate::config_dir_with(&env));
+    }
+}
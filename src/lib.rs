@@ -1,9 +1,690 @@
-use std::env;
-use std::path::PathBuf;
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, Path, PathBuf};
+
+/// Emits a `tracing` debug event if the feature `tracing` is enabled.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+mod app;
+mod archive;
+mod available;
+#[cfg(feature = "cache-lock")]
+mod cache_lock;
+mod cache_usage;
+mod cached;
+mod candidates;
+mod canonical;
+mod chain;
+#[cfg(test)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+mod child;
+#[cfg(feature = "dirs-compat")]
+pub mod compat;
+mod decision_trace;
+mod default_dirs;
+mod dir_ext;
+mod dir_layout;
+mod dirs;
+mod ensure;
+mod env;
+#[cfg(test)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+mod env_guard;
+mod env_override;
+mod error;
+mod existing;
+mod fallback;
+mod file;
+mod home_override;
+mod layout;
+#[cfg(feature = "legacy")]
+pub mod legacy;
+#[cfg(target_os = "macos")]
+mod macos;
+mod max_path;
+mod migrate;
+mod os_string;
+mod path;
+pub mod prelude;
+mod resolver;
+mod reuse;
+mod rooted;
+mod scheme;
+mod search_dirs;
+mod shell;
+mod spec;
+pub mod static_dirs;
+mod subpath;
+mod sudo;
+mod sysroot;
+mod temp;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod triple;
+#[cfg(all(
+    unix,
+    any(
+        feature = "passwd-fallback",
+        feature = "checked-runtime-dir",
+        feature = "cache-lock"
+    )
+))]
+mod unix;
+mod uri;
+mod user_dirs;
+#[cfg(feature = "camino")]
+mod utf8;
+mod vendor;
+mod verify;
+mod warning;
+#[cfg(target_os = "windows")]
+mod windows;
+mod wrapped;
+mod writable;
+
+pub use app::AppDirs;
+pub use archive::{Clock, SystemClock, archive_path};
+pub use available::{config_dir_available, config_dir_available_with};
+#[cfg(feature = "cache-lock")]
+pub use cache_lock::CacheGuard;
+pub use cache_usage::{cache_dir_size, cache_dir_size_with};
+pub use cached::{
+    cache_dir_cached, cache_dir_shared, config_dir_cached, config_dir_shared, data_dir_cached,
+    data_dir_shared, invalidate_cache,
+};
+pub use candidates::{Candidate, config_dir_candidates, config_dir_candidates_with};
+pub use canonical::{
+    cache_dir_canonical, cache_dir_canonical_with, config_dir_canonical, config_dir_canonical_with,
+    data_dir_canonical, data_dir_canonical_with,
+};
+pub use chain::{FallbackChain, Source};
+pub use decision_trace::{
+    TraceStep, cache_dir_trace, cache_dir_trace_with, config_dir_trace, config_dir_trace_with,
+    data_dir_trace, data_dir_trace_with,
+};
+pub use dir_ext::DirExt;
+pub use dir_layout::DirLayout;
+pub use dirs::{Dirs, DirsBuilder};
+pub use ensure::{
+    EnsureDirError, ensure_all, ensure_all_with, ensure_bin_dir, ensure_bin_dir_with,
+    ensure_cache_dir, ensure_cache_dir_with, ensure_config_dir, ensure_config_dir_with,
+    ensure_data_dir, ensure_data_dir_with, ensure_download_dir, ensure_download_dir_with,
+    ensure_parent_dirs,
+};
+pub use env::{Env, KnownFolder, MapEnv, ProcessEnv};
+pub use env_override::{
+    cache_dir_with_override, cache_dir_with_override_with, config_dir_with_override,
+    config_dir_with_override_with, data_dir_with_override, data_dir_with_override_with,
+};
+pub use error::DirError;
+pub use existing::{
+    all_base_dirs, all_base_dirs_with, iter_existing, iter_existing_with, resolve_all,
+    resolve_all_with,
+};
+pub use fallback::{
+    cache_dir_or, cache_dir_or_cwd, cache_dir_or_cwd_with, cache_dir_or_with, config_dir_or,
+    config_dir_or_cwd, config_dir_or_cwd_with, config_dir_or_with, data_dir_or, data_dir_or_cwd,
+    data_dir_or_cwd_with, data_dir_or_with,
+};
+pub use file::{
+    cache_file, cache_file_with, config_file, config_file_with, data_file, data_file_with,
+    runtime_file, runtime_file_with, state_file, state_file_with,
+};
+pub use home_override::set_home_override;
+pub use layout::{DirKind, DirStyle, ParseDirKindError, Platform, current_platform, layout_table};
+pub use max_path::{max_path_headroom, max_path_headroom_with};
+pub use migrate::{migrate_macos_to_xdg, migrate_macos_to_xdg_with};
+pub use os_string::{
+    cache_dir_os, cache_dir_os_with, config_dir_os, config_dir_os_with, data_dir_os,
+    data_dir_os_with,
+};
+pub use path::{expand_origin, same_dir};
+pub use resolver::Resolver;
+pub use reuse::{
+    cache_dir_into, cache_dir_into_with, config_dir_into, config_dir_into_with, data_dir_into,
+    data_dir_into_with,
+};
+pub use rooted::{cache_dir_rooted, config_dir_rooted, config_dir_under, data_dir_rooted};
+pub use scheme::{
+    Scheme, cache_dir_with_scheme, cache_dir_with_scheme_with, config_dir_with_scheme,
+    config_dir_with_scheme_with, data_dir_with_scheme, data_dir_with_scheme_with,
+};
+pub use search_dirs::{
+    ConfigDirs, DataDirs, config_dirs, config_dirs_with, data_dirs, data_dirs_with,
+};
+pub use shell::{Shell, export_script, export_script_with};
+pub use spec::{DirRule, PlatformSpec, resolve_with_spec};
+pub use subpath::{config_subpath, config_subpath_with};
+pub use sudo::{
+    cache_dir_for_user, cache_dir_for_user_with, config_dir_for_invoking_user,
+    config_dir_for_invoking_user_with, config_dir_for_user, config_dir_for_user_with,
+    data_dir_for_user, data_dir_for_user_with,
+};
+pub use sysroot::{Sysroot, with_sysroot};
+pub use temp::{app_temp_dir, app_temp_dir_with, temp_dir, temp_dir_with};
+pub use triple::{resolve_triple, resolve_triple_with};
+pub use uri::{
+    cache_dir_uri, cache_dir_uri_with, config_dir_uri, config_dir_uri_with, data_dir_uri,
+    data_dir_uri_with,
+};
+pub use user_dirs::{
+    desktop_dir, desktop_dir_with, documents_dir, documents_dir_with, download_dir,
+    download_dir_with, music_dir, music_dir_with, pictures_dir, pictures_dir_with, public_dir,
+    public_dir_with, templates_dir, templates_dir_with, videos_dir, videos_dir_with,
+};
+#[cfg(feature = "camino")]
+pub use utf8::{
+    cache_dir_utf8, cache_dir_utf8_with, config_dir_utf8, config_dir_utf8_with, data_dir_utf8,
+    data_dir_utf8_with,
+};
+pub use vendor::{vendor_dir, vendor_dir_with};
+pub use verify::{LayoutWarning, verify_layout, verify_layout_with};
+pub use warning::{
+    Warning, clear_deprecated_vars, clear_warning_handler, deprecate_var, set_warning_handler,
+};
+#[cfg(all(target_os = "windows", feature = "winapi-known-folders"))]
+pub use windows::config_dir_for_windows_user;
+pub use wrapped::{
+    Dir, cache_dir_wrapped, cache_dir_wrapped_with, config_dir_wrapped, config_dir_wrapped_with,
+    data_dir_wrapped, data_dir_wrapped_with,
+};
+pub use writable::{
+    cache_dir_writable, cache_dir_writable_with, config_dir_writable, config_dir_writable_with,
+    data_dir_writable, data_dir_writable_with,
+};
 
 const CONFIG_DIR: &str = ".config";
 const DATA_DIR: &str = ".local/share";
 const CACHE_DIR: &str = ".cache";
+const STATE_DIR: &str = ".local/state";
+const BIN_DIR: &str = ".local/bin";
+
+const PREFIX_ENV: &str = "DIRS_LITE_PREFIX";
+
+/// The default of `$XDG_CONFIG_DIRS`, `/etc/xdg` unless overridden at build time, see `build.rs`.
+const DEFAULT_XDG_CONFIG_DIRS: &str = env!("DIRS_LITE_DEFAULT_XDG_CONFIG_DIRS");
+
+/// Returns whether the current platform follows the XDG Base Directory Specification.
+///
+/// This includes WASI, where the host may pass `$HOME` and the XDG variables to the module.
+const fn is_xdg_platform() -> bool {
+    cfg!(any(
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "openbsd",
+        target_os = "netbsd",
+        target_os = "dragonfly",
+        target_os = "wasi"
+    ))
+}
+
+/// Returns whether the current platform has a convention for the config, data and cache
+/// directories, so that they can be resolved at all.
+#[cfg_attr(not(feature = "strict-platform"), allow(dead_code))]
+const fn is_supported_platform() -> bool {
+    is_xdg_platform()
+        || cfg!(any(
+            target_os = "macos",
+            target_os = "windows",
+            target_os = "haiku"
+        ))
+}
+
+#[cfg(feature = "strict-platform")]
+const _: () = assert!(
+    is_supported_platform(),
+    "dirs-lite has no directory convention for this target, every directory would resolve to `None`; \
+     disable the feature `strict-platform` to build anyway"
+);
+
+/// Returns `$DIRS_LITE_PREFIX/<subdir>` if the feature `global-prefix-env` is enabled
+/// and the variable is set to a non-empty value.
+fn global_prefix_dir<E: Env + ?Sized>(env: &E, subdir: &str) -> Option<PathBuf> {
+    if !cfg!(feature = "global-prefix-env") {
+        return None;
+    }
+    let prefix = env.var_os(PREFIX_ENV);
+    trace!(key = PREFIX_ENV, value = ?prefix, "read environment variable");
+    let dir = prefix.filter(|s| !s.is_empty()).map(|prefix| {
+        let mut dir = PathBuf::from(prefix);
+        dir.push(subdir);
+        dir
+    });
+    decision_trace::record(|| TraceStep::env_var(env, PREFIX_ENV, dir.is_some()));
+    dir
+}
+
+/// Returns `<exe_dir>/<subdir>`, where `<exe_dir>` is the directory of the current executable.
+fn portable_dir(subdir: &str) -> Result<PathBuf, DirError> {
+    let exe = std::env::current_exe().map_err(|_| DirError::MissingExecutableDir)?;
+    let mut dir = exe
+        .parent()
+        .ok_or(DirError::MissingExecutableDir)?
+        .to_path_buf();
+    dir.push(subdir);
+    Ok(dir)
+}
+
+/// Returns `dir` after reporting it as the directory of the given kind, if the feature `tracing` is enabled.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+fn traced(
+    kind: DirKind,
+    style: DirStyle,
+    dir: Result<PathBuf, DirError>,
+) -> Result<PathBuf, DirError> {
+    trace!(%kind, ?style, platform = ?current_platform(), ?dir, "resolved directory");
+    dir
+}
+
+/// Like [`traced`], but for a directory that was resolved into `buf`.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+fn traced_into<T>(
+    kind: DirKind,
+    style: DirStyle,
+    resolved: Result<T, DirError>,
+    buf: &Path,
+) -> Result<T, DirError> {
+    let dir = resolved.as_ref().map(|_| buf);
+    trace!(%kind, ?style, platform = ?current_platform(), ?dir, "resolved directory");
+    resolved
+}
+
+/// Replaces the contents of `buf` with `dir`.
+///
+/// `dir` is copied if `buf` has the capacity for it, so that the buffer passed to one of the
+/// `*_into` functions is reused, and moved into `buf` otherwise.
+fn put_dir(buf: &mut PathBuf, dir: PathBuf) {
+    if buf.capacity() < dir.as_os_str().len() {
+        *buf = dir;
+    } else {
+        let buf = buf.as_mut_os_string();
+        buf.clear();
+        buf.push(dir);
+    }
+}
+
+/// Returns the XDG variable `key` of the host, e.g. `$HOST_XDG_CONFIG_HOME`, if the feature
+/// `container-host` is enabled on Linux.
+///
+/// `toolbox` and `distrobox` expose the directories of the host that way inside their
+/// containers, so that configuration can be shared with it.
+fn host_xdg_var<E: Env + ?Sized>(env: &E, key: &'static str) -> Result<PathBuf, DirError> {
+    if !cfg!(all(feature = "container-host", target_os = "linux")) {
+        return Err(DirError::MissingEnvVar { key });
+    }
+    xdg_var(env, key)
+}
+
+/// Returns `$<key>/<subdir>` if the feature `respect-snap` is enabled and the process runs
+/// as a Snap on Linux, i.e. `$SNAP` and `$<key>` are set to non-empty values.
+fn snap_dir<E: Env + ?Sized>(env: &E, key: &'static str, subdir: &str) -> Option<PathBuf> {
+    if !cfg!(all(feature = "respect-snap", target_os = "linux")) {
+        return None;
+    }
+    non_empty_var(env, "SNAP").ok()?;
+    non_empty_var(env, key).ok().map(|mut dir| {
+        dir.push(subdir);
+        dir
+    })
+}
+
+/// Returns `<dir>/.<name>/<subdir>` if the feature `appimage` is enabled and the process runs
+/// as an AppImage on Linux, where `$APPIMAGE` is the absolute path `<dir>/<name>.AppImage`.
+///
+/// This keeps the files of a portable AppImage next to it, e.g. on a USB stick.
+fn appimage_dir<E: Env + ?Sized>(env: &E, subdir: &str) -> Option<PathBuf> {
+    if !cfg!(all(feature = "appimage", target_os = "linux")) {
+        return None;
+    }
+    let appimage = non_empty_var(env, "APPIMAGE").ok()?;
+    if !appimage.is_absolute() {
+        return None;
+    }
+    let mut name = std::ffi::OsString::from(".");
+    name.push(appimage.file_stem()?);
+    let mut dir = appimage.parent()?.join(name);
+    dir.push(subdir);
+    Some(dir)
+}
+
+/// Returns the value of the environment variable `key` as a path, if it is set and not empty.
+fn non_empty_var<E: Env + ?Sized>(env: &E, key: &'static str) -> Result<PathBuf, DirError> {
+    let dir = read_var(env, key);
+    decision_trace::record(|| TraceStep::env_var(env, key, dir.is_ok()));
+    dir
+}
+
+/// Like [`non_empty_var`], but without recording a [`TraceStep`].
+fn read_var<E: Env + ?Sized>(env: &E, key: &'static str) -> Result<PathBuf, DirError> {
+    let value = env.var_os(key);
+    trace!(key, ?value, "read environment variable");
+    match value {
+        None => Err(DirError::MissingEnvVar { key }),
+        Some(value) if value.is_empty() => Err(DirError::EmptyEnvVar { key }),
+        Some(value) if value.as_encoded_bytes().contains(&0) => Err(DirError::NulInEnvVar { key }),
+        Some(value) => Ok(PathBuf::from(value)),
+    }
+}
+
+/// Returns the Windows known folder `folder`, or the value of the environment variable `key` if it is unknown.
+///
+/// If `key` is unset or empty as well, as on some locked-down systems, the default location of
+/// the folder in `%USERPROFILE%` is derived, e.g. `%USERPROFILE%\AppData\Roaming`.
+fn known_folder<E: Env + ?Sized>(
+    env: &E,
+    folder: KnownFolder,
+    key: &'static str,
+) -> Result<PathBuf, DirError> {
+    if let Some(dir) = queried_known_folder(env, folder) {
+        return Ok(dir);
+    }
+    windows_var(env, key).or_else(|e| profile_known_folder(env, folder).ok_or(e))
+}
+
+/// Returns the Windows known folder `folder`, if it is known.
+fn queried_known_folder<E: Env + ?Sized>(env: &E, folder: KnownFolder) -> Option<PathBuf> {
+    let dir = env.known_folder(folder);
+    trace!(?folder, ?dir, "queried known folder");
+    decision_trace::record(|| TraceStep::QueriedKnownFolder {
+        folder,
+        path: dir.clone(),
+    });
+    dir
+}
+
+/// Returns the value of the Windows environment variable `key` as a path, with references to
+/// other variables expanded.
+fn windows_var<E: Env + ?Sized>(env: &E, key: &'static str) -> Result<PathBuf, DirError> {
+    non_empty_var(env, key).map(|dir| windows_separators(expand_windows_vars(env, dir)))
+}
+
+/// Returns the default location of the Windows known folder `folder` in `%USERPROFILE%`, if
+/// it has one and `%USERPROFILE%` is set.
+fn profile_known_folder<E: Env + ?Sized>(env: &E, folder: KnownFolder) -> Option<PathBuf> {
+    let subdir = match folder {
+        KnownFolder::RoamingAppData => "AppData\\Roaming",
+        KnownFolder::LocalAppData => "AppData\\Local",
+        _ => return None,
+    };
+    let profile = non_empty_var(env, "USERPROFILE").ok()?;
+    let profile = windows_separators(expand_windows_vars(env, profile));
+    decision_trace::record(|| TraceStep::FellBackToHome {
+        home: profile.clone(),
+    });
+    Some(profile.join(subdir))
+}
+
+/// Replaces the forward slashes in `dir` with backslashes, e.g. in `C:/Users/alice/AppData/Roaming`
+/// as set by some cross-platform toolchains, so that paths joined to it are separated consistently.
+///
+/// A verbatim prefix `\\?\` is kept as-is. Paths that are not valid Unicode are returned untouched.
+fn windows_separators(dir: PathBuf) -> PathBuf {
+    match dir.to_str() {
+        Some(value) if value.contains('/') => PathBuf::from(value.replace('/', "\\")),
+        _ => dir,
+    }
+}
+
+/// Expands the `%NAME%` references in `dir` against `env`, as Windows does for `REG_EXPAND_SZ` values.
+///
+/// References to unset variables are left as-is, as are references that would expand into
+/// themselves. The expansion works on the raw value, so the parts of a path that are not valid
+/// Unicode, e.g. an unpaired surrogate in a wide string on Windows, are kept as they are.
+fn expand_windows_vars<E: Env + ?Sized>(env: &E, dir: PathBuf) -> PathBuf {
+    fn expand<E: Env + ?Sized>(env: &E, value: &OsStr, expanding: &mut Vec<String>) -> OsString {
+        let mut expanded = OsString::with_capacity(value.len());
+        let mut rest = value.as_encoded_bytes();
+        while let Some(start) = rest.iter().position(|&byte| byte == b'%') {
+            expanded.push(os_str(&rest[..start]));
+            let after = &rest[start + 1..];
+            let Some(end) = after.iter().position(|&byte| byte == b'%') else {
+                rest = &rest[start..];
+                break;
+            };
+            let name = &after[..end];
+            let resolved = std::str::from_utf8(name)
+                .ok()
+                .filter(|name| !name.is_empty() && !expanding.iter().any(|n| n == name))
+                .and_then(|name| Some((name, env.var_os(name)?)));
+            match resolved {
+                Some((name, value)) => {
+                    expanding.push(name.to_string());
+                    expanded.push(expand(env, &value, expanding));
+                    expanding.pop();
+                }
+                None => {
+                    expanded.push("%");
+                    expanded.push(os_str(name));
+                    expanded.push("%");
+                }
+            }
+            rest = &after[end + 1..];
+        }
+        expanded.push(os_str(rest));
+        expanded
+    }
+
+    /// Returns a part of the encoded bytes of an `OsStr` that was split off at `%`s.
+    fn os_str(bytes: &[u8]) -> &OsStr {
+        // SAFETY: The bytes come from `OsStr::as_encoded_bytes` and are only split right before
+        // or after an ASCII `%`, which is a valid non-empty UTF-8 substring
+        unsafe { OsStr::from_encoded_bytes_unchecked(bytes) }
+    }
+
+    if dir.as_os_str().as_encoded_bytes().contains(&b'%') {
+        PathBuf::from(expand(env, dir.as_os_str(), &mut Vec::new()))
+    } else {
+        dir
+    }
+}
+
+/// Expands a leading `~` or `~user` in `dir` to the respective home directory, as a shell would.
+///
+/// Returns `None` if the home directory is unknown, `~user` is only resolved through
+/// [`Env::user_home`]. Paths without a leading `~` are returned unchanged.
+fn expand_tilde<E: Env + ?Sized>(env: &E, dir: PathBuf) -> Option<PathBuf> {
+    let mut components = dir.components();
+    let user = match components.next() {
+        Some(std::path::Component::Normal(first)) => match first.to_str() {
+            Some(first) if first.starts_with('~') => &first[1..],
+            _ => return Some(dir),
+        },
+        _ => return Some(dir),
+    };
+    let home = if user.is_empty() {
+        home_dir_with(env)?
+    } else {
+        env.user_home(user)?
+    };
+    Some(home.join(components.as_path()))
+}
+
+/// Like [`non_empty_var`], but only accepts absolute paths, as required by the XDG Base Directory Specification.
+///
+/// A leading `~` is expanded first, see [`expand_tilde`]. Trailing and repeated separators are
+/// removed. Warns if the XDG variable `key` points to the home directory itself.
+fn xdg_var<E: Env + ?Sized>(env: &E, key: &'static str) -> Result<PathBuf, DirError> {
+    let mut dir = PathBuf::new();
+    xdg_var_into(env, key, &mut dir).map(|()| dir)
+}
+
+/// Like [`xdg_var`], but writes the value into `buf`, which is left unchanged on failure.
+fn xdg_var_into<E: Env + ?Sized>(
+    env: &E,
+    key: &'static str,
+    buf: &mut PathBuf,
+) -> Result<(), DirError> {
+    // The home directory is only looked up to expand and check the value, not as a fallback
+    let resolved = decision_trace::paused(|| checked_xdg_var_into(env, key, buf));
+    decision_trace::record(|| TraceStep::env_var(env, key, resolved.is_ok()));
+    resolved
+}
+
+/// Like [`xdg_var_into`], but without recording a [`TraceStep`].
+fn checked_xdg_var_into<E: Env + ?Sized>(
+    env: &E,
+    key: &'static str,
+    buf: &mut PathBuf,
+) -> Result<(), DirError> {
+    let dir = expand_tilde(env, read_var(env, key)?).ok_or(DirError::RelativeEnvVar { key })?;
+    if !dir.is_absolute() {
+        return Err(DirError::RelativeEnvVar { key });
+    }
+    // Pushing the components one by one drops trailing and repeated separators
+    buf.as_mut_os_string().clear();
+    buf.extend(dir.components());
+    if home_dir_result_with(env).is_ok_and(|home| home == *buf) {
+        warning::emit(Warning::BaseDirIsHome {
+            key,
+            path: buf.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Splits the XDG search path `key` on `:`, skipping empty and relative entries.
+///
+/// Returns `default` if the variable is unset or no entry remains.
+fn xdg_dirs<E: Env + ?Sized>(env: &E, key: &str, default: &[&str]) -> Vec<PathBuf> {
+    let value = env.var_os(key);
+    trace!(key, ?value, "read environment variable");
+    let dirs: Vec<PathBuf> = value
+        .map(|dirs| {
+            std::env::split_paths(&dirs)
+                .filter(|dir| dir.is_absolute())
+                .map(|dir| dir.components().collect())
+                .collect()
+        })
+        .unwrap_or_default();
+    if dirs.is_empty() {
+        default.iter().map(PathBuf::from).collect()
+    } else {
+        dirs
+    }
+}
+
+/// Returns the home directory, recording it as the fallback of the directory being resolved.
+fn fallback_home<E: Env + ?Sized>(env: &E) -> Result<PathBuf, DirError> {
+    let home = home_dir_result_with(env)?;
+    decision_trace::record(|| TraceStep::FellBackToHome { home: home.clone() });
+    Ok(home)
+}
+
+/// Returns the home directory with `subdir` appended.
+fn home_subdir<E: Env + ?Sized>(env: &E, subdir: &str) -> Result<PathBuf, DirError> {
+    let mut dir = PathBuf::new();
+    home_subdir_into(env, subdir, &mut dir).map(|()| dir)
+}
+
+/// Like [`home_subdir`], but writes the path into `buf`, which is left unchanged on failure.
+fn home_subdir_into<E: Env + ?Sized>(
+    env: &E,
+    subdir: &str,
+    buf: &mut PathBuf,
+) -> Result<(), DirError> {
+    put_dir(buf, fallback_home(env)?);
+    buf.push(subdir);
+    Ok(())
+}
+
+/// Returns `path` as an absolute Windows path, translating the POSIX-style drive paths of MSYS2
+/// and Git Bash, `/c/Users/Alice`, and Cygwin, `/cygdrive/c/Users/Alice`, to `C:\Users\Alice`.
+///
+/// Returns `None` for any other path that is not absolute on Windows.
+fn windows_path_from_posix(path: &std::ffi::OsStr) -> Option<PathBuf> {
+    if let Some(posix) = path.to_str().filter(|path| path.starts_with('/')) {
+        let posix = posix.strip_prefix("/cygdrive").unwrap_or(posix);
+        let mut parts = posix[1..].splitn(2, '/');
+        let drive = parts
+            .next()
+            .filter(|drive| drive.len() == 1 && drive.chars().all(|c| c.is_ascii_alphabetic()))?;
+        let rest = parts.next().unwrap_or_default().replace('/', "\\");
+        return Some(PathBuf::from(format!(
+            "{}:\\{rest}",
+            drive.to_ascii_uppercase()
+        )));
+    }
+    let path = PathBuf::from(path);
+    (cfg!(target_os = "windows") && path.is_absolute()).then_some(path)
+}
+
+/// Returns the path to the user's home directory.
+///
+/// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+///
+/// |Platform | Value                                      | Example        |
+/// | ------- | ------------------------------------------ | -------------- |
+/// | Linux   | `$HOME`                                    | /home/alice    |
+/// | macOS   | `$HOME`                                    | /Users/Alice   |
+/// | Windows | `%USERPROFILE%` or `%HOMEDRIVE%%HOMEPATH%` | C:\Users\Alice |
+///
+/// NOTE: if the feature `passwd-fallback` is enabled and `$HOME` is unset or not an absolute path, the home directory from the password database is returned on Unix.
+///
+/// NOTE: if the feature `unix-home-on-windows` is enabled, `$HOME` is preferred on Windows as set by MSYS2, Cygwin and Git Bash, with POSIX-style paths like `/c/Users/Alice` translated to `C:\Users\Alice`.
+pub fn home_dir() -> Option<PathBuf> {
+    home_dir_with(&ProcessEnv)
+}
+
+/// Like [`home_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn home_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    home_dir_result_with(env).ok()
+}
+
+/// Like [`home_dir`], but returns the reason if the directory cannot be resolved.
+pub fn home_dir_result() -> Result<PathBuf, DirError> {
+    home_dir_result_with(&ProcessEnv)
+}
+
+/// Like [`home_dir_result`], but resolves against the given environment instead of the one of the current process.
+pub fn home_dir_result_with<E: Env + ?Sized>(env: &E) -> Result<PathBuf, DirError> {
+    if cfg!(target_os = "windows") {
+        // Windows: Use $HOME if unix-home-on-windows is enabled
+        if cfg!(feature = "unix-home-on-windows") {
+            let home = env
+                .var_os("HOME")
+                .and_then(|home| windows_path_from_posix(&home));
+            decision_trace::record(|| TraceStep::env_var(env, "HOME", home.is_some()));
+            if let Some(home) = home {
+                return Ok(home);
+            }
+        }
+        // Windows: Use %USERPROFILE% or %HOMEDRIVE%%HOMEPATH%
+        non_empty_var(env, "USERPROFILE")
+            .or_else(|_| {
+                let mut home = non_empty_var(env, "HOMEDRIVE")?.into_os_string();
+                home.push(non_empty_var(env, "HOMEPATH")?);
+                Ok(PathBuf::from(home))
+            })
+            .map_err(|_: DirError| DirError::MissingHome)
+    } else if cfg!(all(target_family = "wasm", not(target_os = "wasi"))) {
+        // WebAssembly: No home directory, and so no directory derived from it
+        Err(DirError::UnsupportedPlatform)
+    } else {
+        // Unix-like and WASI: Use $HOME, or the password database if it is unset or not an
+        // absolute path, like the XDG variables, as every directory derived from it would be
+        // relative. This covers an empty or whitespace-only $HOME, one set to a relative path
+        // by a broken container entrypoint, and a tilde a login script failed to expand, e.g.
+        // `~alice`. A $HOME with an embedded NUL byte can never be opened, so it counts as
+        // unset too
+        let home = env.var_os("HOME");
+        trace!(key = "HOME", value = ?home, "read environment variable");
+        let home = home
+            .filter(|home| Path::new(home).is_absolute() && !home.as_encoded_bytes().contains(&0));
+        decision_trace::record(|| TraceStep::env_var(env, "HOME", home.is_some()));
+        match home {
+            Some(home) => Ok(PathBuf::from(home)),
+            None => {
+                let home = env.passwd_home();
+                decision_trace::record(|| TraceStep::UsedPasswdHome { home: home.clone() });
+                home.ok_or(DirError::MissingHome)
+            }
+        }
+    }
+}
 
 /// Returns the path to the user's config directory.
 ///
@@ -14,622 +695,3806 @@ const CACHE_DIR: &str = ".cache";
 /// | Linux   | `$XDG_CONFIG_HOME` or `$HOME`/.config | /home/alice/.config                      |
 /// | macOS   | `$HOME`/Library/Application Support   | /Users/Alice/Library/Application Support |
 /// | Windows | `%APPDATA%`\Roaming                 | C:\Users\Alice\AppData\Roaming           |
+/// | Haiku   | `$HOME`/config/settings               | /boot/home/config/settings               |
 ///
 /// NOTE: if the feature `favor-xdg-style` is enabled, `$HOME/.config` is favorized.
+///
+/// NOTE: if the feature `global-prefix-env` is enabled and `$DIRS_LITE_PREFIX` is set, `$DIRS_LITE_PREFIX/config` is returned on all platforms.
+///
+/// NOTE: if the feature `respect-snap` is enabled and the process runs as a Snap on Linux, `$SNAP_USER_DATA/.config` is returned.
+///
+/// NOTE: if the feature `appimage` is enabled and the process runs as an AppImage on Linux, `.<name>/config` next to `$APPIMAGE` is returned.
+///
+/// NOTE: if the feature `container-host` is enabled, `$HOST_XDG_CONFIG_HOME` is preferred on Linux, as set by `toolbox` and `distrobox` for the directory of the host.
 pub fn config_dir() -> Option<PathBuf> {
-    if cfg!(target_os = "linux") {
-        // Linux: Use $HOME/.config
-        env::var_os("XDG_CONFIG_HOME")
-            .filter(|s| !s.is_empty())
-            .map(PathBuf::from)
-            .or_else(std::env::home_dir)
-            .map(|mut base| {
-                base.push(CONFIG_DIR);
-                base
-            })
+    config_dir_with(&ProcessEnv)
+}
+
+/// Like [`config_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    config_dir_result_with(env).ok()
+}
+
+/// Like [`config_dir`], but returns the reason if the directory cannot be resolved.
+pub fn config_dir_result() -> Result<PathBuf, DirError> {
+    config_dir_result_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_result`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_result_with<E: Env + ?Sized>(env: &E) -> Result<PathBuf, DirError> {
+    config_dir_result_styled(env, DirStyle::default(), &DirLayout::default())
+}
+
+/// Like [`config_dir_result`], but also fails if the directory exists and is not a directory.
+///
+/// This catches e.g. a `$XDG_CONFIG_HOME` set to a file by mistake, which would otherwise only
+/// fail later and less clearly when creating files in it. A missing directory is fine.
+pub fn config_dir_checked() -> Result<PathBuf, DirError> {
+    config_dir_checked_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_checked`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_checked_with<E: Env + ?Sized>(env: &E) -> Result<PathBuf, DirError> {
+    let dir = config_dir_result_with(env)?;
+    if dir.exists() && !dir.is_dir() {
+        return Err(DirError::NotADirectory { path: dir });
+    }
+    Ok(dir)
+}
+
+/// Like [`config_dir`], but follows the given style on macOS instead of the one selected by the feature `favor-xdg-style`.
+///
+/// This lets each caller choose, as features are unified across all dependents of this crate.
+/// [`DirStyle::Portable`] returns `<exe_dir>/config` next to the current executable on every platform.
+pub fn config_dir_styled(style: DirStyle) -> Option<PathBuf> {
+    config_dir_styled_with(&ProcessEnv, style)
+}
+
+/// Like [`config_dir_styled`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_styled_with<E: Env + ?Sized>(env: &E, style: DirStyle) -> Option<PathBuf> {
+    config_dir_result_styled(env, style, &DirLayout::default()).ok()
+}
+
+fn config_dir_result_styled<E: Env + ?Sized>(
+    env: &E,
+    style: DirStyle,
+    layout: &DirLayout,
+) -> Result<PathBuf, DirError> {
+    let mut dir = PathBuf::new();
+    config_dir_styled_into(env, style, layout, &mut dir).map(|_| dir)
+}
+
+/// Like [`config_dir_result_styled`], but resolves into `buf`, see [`config_dir_into`], and
+/// returns the source the directory was resolved from.
+pub(crate) fn config_dir_styled_into<E: Env + ?Sized>(
+    env: &E,
+    style: DirStyle,
+    layout: &DirLayout,
+    buf: &mut PathBuf,
+) -> Result<DirSource, DirError> {
+    warning::check_deprecated_vars(env);
+    let resolved = resolve_base_dir(env, &BaseDir::CONFIG, style, layout, buf);
+    traced_into(DirKind::Config, style, resolved, buf)
+}
+
+/// Returns the style selected on macOS by the feature `favor-xdg-style`.
+///
+/// This is the only place the feature is read, so that the macOS branches below can be tested
+/// with both styles regardless of the compiled features.
+const fn macos_style() -> DirStyle {
+    if cfg!(feature = "favor-xdg-style") {
+        DirStyle::Xdg
+    } else {
+        DirStyle::Native
+    }
+}
+
+/// Returns the macOS config dir in `style`, `$HOME/Library/Application Support` or
+/// `$HOME/.config`.
+fn config_dir_macos<E: Env + ?Sized>(
+    env: &E,
+    style: DirStyle,
+    layout: &DirLayout,
+) -> Result<PathBuf, DirError> {
+    fallback_home(env).map(|mut home| {
+        if style == DirStyle::Xdg {
+            home.push(&*layout.config);
+            return home;
+        }
+        home.push("Library");
+        home.push("Application Support");
+        home
+    })
+}
+
+/// Returns the macOS data dir in `style`, `$HOME/Library/Application Support`, with `Data`
+/// appended if the feature `macos-split` is enabled, or `$HOME/.local/share`.
+fn data_dir_macos<E: Env + ?Sized>(
+    env: &E,
+    style: DirStyle,
+    layout: &DirLayout,
+) -> Result<PathBuf, DirError> {
+    fallback_home(env).map(|mut home| {
+        if style == DirStyle::Xdg {
+            home.push(&*layout.data);
+            return home;
+        }
+        home.push("Library");
+        home.push("Application Support");
+        if cfg!(feature = "macos-split") {
+            home.push("Data");
+        }
+        home
+    })
+}
+
+/// Returns the macOS cache dir in `style`, `$HOME/Library/Caches` or `$HOME/.cache`.
+fn cache_dir_macos<E: Env + ?Sized>(
+    env: &E,
+    style: DirStyle,
+    layout: &DirLayout,
+) -> Result<PathBuf, DirError> {
+    fallback_home(env).map(|mut home| {
+        if style == DirStyle::Xdg {
+            home.push(&*layout.cache);
+            return home;
+        }
+        home.push("Library");
+        home.push("Caches");
+        home
+    })
+}
+
+/// One of the base directories resolved from the [`DirSource`]s of [`base_dir_sources`].
+pub(crate) struct BaseDir {
+    /// The kind of the directory.
+    kind: DirKind,
+    /// The subdirectory of `$DIRS_LITE_PREFIX`, the executable dir and an AppImage dir.
+    name: &'static str,
+    /// The Snap variable the directory is placed in.
+    snap_key: &'static str,
+    /// The XDG variable of the host, see [`host_xdg_var`].
+    host_key: &'static str,
+    /// The XDG variable.
+    xdg_key: &'static str,
+    /// The Windows known folder.
+    folder: KnownFolder,
+    /// The variable the Windows known folder is read from if it is unknown.
+    folder_key: &'static str,
+    /// The subdirectory of the home directory on Haiku.
+    haiku_subdir: &'static str,
+}
+
+impl BaseDir {
+    pub(crate) const CONFIG: BaseDir = BaseDir {
+        kind: DirKind::Config,
+        name: "config",
+        snap_key: "SNAP_USER_DATA",
+        host_key: "HOST_XDG_CONFIG_HOME",
+        xdg_key: "XDG_CONFIG_HOME",
+        folder: KnownFolder::RoamingAppData,
+        folder_key: "APPDATA",
+        haiku_subdir: "config/settings",
+    };
+
+    pub(crate) const DATA: BaseDir = BaseDir {
+        kind: DirKind::Data,
+        name: "data",
+        snap_key: "SNAP_USER_DATA",
+        host_key: "HOST_XDG_DATA_HOME",
+        xdg_key: "XDG_DATA_HOME",
+        folder: KnownFolder::LocalAppData,
+        folder_key: "LOCALAPPDATA",
+        haiku_subdir: "config/non-packaged/data",
+    };
+
+    /// The data directory, but roaming with the user profile on Windows, see [`roaming_data_dir`].
+    pub(crate) const ROAMING_DATA: BaseDir = BaseDir {
+        folder: KnownFolder::RoamingAppData,
+        folder_key: "APPDATA",
+        ..BaseDir::DATA
+    };
+
+    pub(crate) const CACHE: BaseDir = BaseDir {
+        kind: DirKind::Cache,
+        name: "cache",
+        snap_key: "SNAP_USER_COMMON",
+        host_key: "HOST_XDG_CACHE_HOME",
+        xdg_key: "XDG_CACHE_HOME",
+        folder: KnownFolder::LocalAppData,
+        folder_key: "LOCALAPPDATA",
+        haiku_subdir: "config/cache",
+    };
+
+    /// Returns the subdirectory of the home directory in the XDG convention, e.g. `.config`.
+    fn xdg_subdir<'a>(&self, layout: &'a DirLayout) -> &'a str {
+        match self.kind {
+            DirKind::Data => &layout.data,
+            DirKind::Cache => &layout.cache,
+            _ => &layout.config,
+        }
+    }
+
+    /// Returns the directory on macOS in `style`.
+    fn macos_dir<E: Env + ?Sized>(
+        &self,
+        env: &E,
+        style: DirStyle,
+        layout: &DirLayout,
+    ) -> Result<PathBuf, DirError> {
+        match self.kind {
+            DirKind::Data => data_dir_macos(env, style, layout),
+            DirKind::Cache => cache_dir_macos(env, style, layout),
+            _ => config_dir_macos(env, style, layout),
+        }
+    }
+}
+
+/// A location a [`BaseDir`] is looked up in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DirSource {
+    /// `$DIRS_LITE_PREFIX/<name>`, see [`global_prefix_dir`].
+    GlobalPrefix,
+    /// `<exe_dir>/<name>` in the portable style, see [`portable_dir`].
+    Portable,
+    /// Below the data dir of a Snap, see [`snap_dir`].
+    Snap,
+    /// Next to an AppImage, see [`appimage_dir`].
+    AppImage,
+    /// The XDG variable of the host, see [`host_xdg_var`].
+    HostXdg,
+    /// The XDG variable, e.g. `$XDG_CONFIG_HOME`.
+    Xdg,
+    /// The XDG default below the home directory, e.g. `$HOME/.config`.
+    XdgHome,
+    /// The macOS convention in the selected style, e.g. `$HOME/Library/Application Support`.
+    Macos,
+    /// The Windows known folder, e.g. `FOLDERID_RoamingAppData`.
+    KnownFolder,
+    /// The variable of the Windows known folder, e.g. `%APPDATA%`.
+    WindowsVar,
+    /// The default location of the Windows known folder, e.g. `%USERPROFILE%\AppData\Roaming`.
+    WindowsProfile,
+    /// The Haiku convention, e.g. `$HOME/config/settings`.
+    Haiku,
+}
+
+impl DirSource {
+    /// Returns whether the feature this source depends on is enabled for the current platform.
+    pub(crate) fn is_enabled(self) -> bool {
+        let linux = cfg!(target_os = "linux");
+        match self {
+            DirSource::GlobalPrefix => cfg!(feature = "global-prefix-env"),
+            DirSource::Snap => linux && cfg!(feature = "respect-snap"),
+            DirSource::AppImage => linux && cfg!(feature = "appimage"),
+            DirSource::HostXdg => linux && cfg!(feature = "container-host"),
+            _ => true,
+        }
+    }
+
+    /// Resolves `base` from this source into `buf`, which is left unchanged on failure.
+    ///
+    /// Returns `None` if the source does not apply, e.g. the process does not run as a Snap,
+    /// so that the error of an earlier source is reported instead.
+    pub(crate) fn resolve<E: Env + ?Sized>(
+        self,
+        env: &E,
+        base: &BaseDir,
+        style: DirStyle,
+        layout: &DirLayout,
+        buf: &mut PathBuf,
+    ) -> Option<Result<(), DirError>> {
+        let dir = match self {
+            DirSource::GlobalPrefix => Ok(global_prefix_dir(env, base.name)?),
+            DirSource::Portable => portable_dir(base.name),
+            DirSource::Snap => Ok(snap_dir(env, base.snap_key, base.xdg_subdir(layout))?),
+            DirSource::AppImage => Ok(appimage_dir(env, base.name)?),
+            DirSource::HostXdg => host_xdg_var(env, base.host_key),
+            DirSource::Xdg => return Some(xdg_var_into(env, base.xdg_key, buf)),
+            DirSource::XdgHome => {
+                return Some(home_subdir_into(env, base.xdg_subdir(layout), buf));
+            }
+            DirSource::Macos => base.macos_dir(env, style, layout),
+            DirSource::KnownFolder => Ok(queried_known_folder(env, base.folder)?),
+            DirSource::WindowsVar => windows_var(env, base.folder_key),
+            DirSource::WindowsProfile => Ok(profile_known_folder(env, base.folder)?),
+            DirSource::Haiku => return Some(home_subdir_into(env, base.haiku_subdir, buf)),
+        };
+        Some(dir.map(|dir| put_dir(buf, dir)))
+    }
+}
+
+/// Returns the sources the config, data and cache dirs are looked up in, in order.
+pub(crate) fn base_dir_sources(style: DirStyle) -> &'static [DirSource] {
+    if style == DirStyle::Portable {
+        // The portable style keeps everything next to the executable
+        &[DirSource::GlobalPrefix, DirSource::Portable]
+    } else if is_xdg_platform() {
+        // Linux and BSDs: Use the Snap or AppImage dir, the XDG variable, e.g.
+        //  $XDG_CONFIG_HOME, or its default, e.g. $HOME/.config
+        &[
+            DirSource::GlobalPrefix,
+            DirSource::Snap,
+            DirSource::AppImage,
+            DirSource::HostXdg,
+            DirSource::Xdg,
+            DirSource::XdgHome,
+        ]
     } else if cfg!(target_os = "macos") {
         // macOS: Use $HOME/Library/Application Support
-        //  or $HOME/.config if favor-xdg-style is enabled
-        std::env::home_dir().map(|mut home| {
-            if cfg!(feature = "favor-xdg-style") {
-                home.push(CONFIG_DIR);
-                return home;
-            }
-            home.push("Library");
-            home.push("Application Support");
-            home
-        })
+        //  or $HOME/.config if the XDG style is selected
+        &[DirSource::GlobalPrefix, DirSource::Macos]
     } else if cfg!(target_os = "windows") {
-        // Windows: Use %APPDATA%
-        env::var_os("APPDATA")
-            .filter(|s| !s.is_empty())
-            .map(PathBuf::from)
+        // Windows: Use the known folder, e.g. RoamingAppData, its variable, e.g. %APPDATA%,
+        //  or its default location in %USERPROFILE%
+        &[
+            DirSource::GlobalPrefix,
+            DirSource::KnownFolder,
+            DirSource::WindowsVar,
+            DirSource::WindowsProfile,
+        ]
+    } else if cfg!(target_os = "haiku") {
+        // Haiku: Use a dir in $HOME/config, e.g. $HOME/config/settings
+        &[DirSource::GlobalPrefix, DirSource::Haiku]
     } else {
         // Unsupported platform
-        None
+        &[DirSource::GlobalPrefix]
     }
 }
 
-/// Returns the path to the user's data directory.
+/// Resolves `base` into `buf` from the first of its [`base_dir_sources`] that yields a path,
+/// and returns that source.
 ///
-/// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+/// `buf` is left unchanged or partially written on failure.
+fn resolve_base_dir<E: Env + ?Sized>(
+    env: &E,
+    base: &BaseDir,
+    style: DirStyle,
+    layout: &DirLayout,
+    buf: &mut PathBuf,
+) -> Result<DirSource, DirError> {
+    let mut resolved = Err(DirError::UnsupportedPlatform);
+    for &source in base_dir_sources(style) {
+        match source.resolve(env, base, style, layout, buf) {
+            Some(Ok(())) => return Ok(source),
+            Some(Err(e)) => resolved = Err(e),
+            None => {}
+        }
+    }
+    resolved
+}
+
+/// Returns the path to the user's roaming config directory, which follows the user across machines on Windows.
+///
+/// This is the same as [`config_dir`], see [`config_local_dir`] for the machine-local counterpart.
 ///
 /// |Platform | Value                                 | Example                                  |
 /// | ------- | ------------------------------------- | ---------------------------------------- |
-/// | Linux   | `$XDG_DATA_HOME` or `$HOME`/.local/share | /home/alice/.local/share              |
+/// | Linux   | `$XDG_CONFIG_HOME` or `$HOME`/.config | /home/alice/.config                      |
+/// | macOS   | `$HOME`/Library/Application Support   | /Users/Alice/Library/Application Support |
+/// | Windows | `%APPDATA%`                           | C:\Users\Alice\AppData\Roaming           |
+pub fn config_roaming_dir() -> Option<PathBuf> {
+    config_roaming_dir_with(&ProcessEnv)
+}
+
+/// Like [`config_roaming_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn config_roaming_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    config_dir_with(env)
+}
+
+/// Returns the path to the user's machine-local config directory.
+///
+/// On Windows this is `%LOCALAPPDATA%`, which is not synchronized in roaming profiles. Other
+/// platforms make no such distinction and return [`config_dir`].
+///
+/// |Platform | Value                                 | Example                                  |
+/// | ------- | ------------------------------------- | ---------------------------------------- |
+/// | Linux   | `$XDG_CONFIG_HOME` or `$HOME`/.config | /home/alice/.config                      |
 /// | macOS   | `$HOME`/Library/Application Support   | /Users/Alice/Library/Application Support |
 /// | Windows | `%LOCALAPPDATA%`                      | C:\Users\Alice\AppData\Local             |
 ///
-/// NOTE: if the feature `favor-xdg-style` is enabled, `$HOME/.local/share` is favorized on macOS.
-pub fn data_dir() -> Option<PathBuf> {
-    if cfg!(target_os = "linux") {
-        // Linux: Use $XDG_DATA_HOME or $HOME/.local/share
-        env::var_os("XDG_DATA_HOME")
-            .filter(|s| !s.is_empty())
-            .map(PathBuf::from)
-            .or_else(|| {
-                std::env::home_dir().map(|mut home| {
-                    home.push(DATA_DIR);
-                    home
-                })
-            })
-    } else if cfg!(target_os = "macos") {
-        // macOS: Use $HOME/Library/Application Support
-        //  or $HOME/.local/share if favor-xdg-style is enabled
-        std::env::home_dir().map(|mut home| {
-            if cfg!(feature = "favor-xdg-style") {
-                home.push(DATA_DIR);
-                return home;
-            }
+/// NOTE: if the feature `global-prefix-env` is enabled and `$DIRS_LITE_PREFIX` is set, `$DIRS_LITE_PREFIX/config` is returned on all platforms.
+pub fn config_local_dir() -> Option<PathBuf> {
+    config_local_dir_with(&ProcessEnv)
+}
+
+/// Like [`config_local_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn config_local_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    if cfg!(target_os = "windows") && global_prefix_dir(env, "config").is_none() {
+        // Windows: Use the LocalAppData known folder or %LOCALAPPDATA%
+        return known_folder(env, KnownFolder::LocalAppData, "LOCALAPPDATA").ok();
+    }
+    config_dir_with(env)
+}
+
+/// Returns the path to the user's preference directory, e.g. for `.plist` files on macOS.
+///
+/// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+///
+/// |Platform | Value                                 | Example                                  |
+/// | ------- | ------------------------------------- | ---------------------------------------- |
+/// | Linux   | [`config_dir`]                        | /home/alice/.config                      |
+/// | macOS   | `$HOME`/Library/Preferences           | /Users/Alice/Library/Preferences         |
+/// | Windows | [`config_dir`]                        | C:\Users\Alice\AppData\Roaming           |
+pub fn preference_dir() -> Option<PathBuf> {
+    preference_dir_with(&ProcessEnv)
+}
+
+/// Like [`preference_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn preference_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    if cfg!(target_os = "macos") {
+        // macOS: Use $HOME/Library/Preferences
+        home_dir_with(env).map(|mut home| {
             home.push("Library");
-            home.push("Application Support");
+            home.push("Preferences");
             home
         })
-    } else if cfg!(target_os = "windows") {
-        // Windows: Use %LOCALAPPDATA%
-        env::var_os("LOCALAPPDATA")
-            .filter(|s| !s.is_empty())
-            .map(PathBuf::from)
     } else {
-        // Unsupported platform
-        None
+        // Other platforms: Use the config dir
+        config_dir_with(env)
     }
 }
 
-/// Returns the path to the user's cache directory.
+/// Returns the path to the user's data directory.
 ///
 /// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
 ///
 /// |Platform | Value                                 | Example                                  |
 /// | ------- | ------------------------------------- | ---------------------------------------- |
-/// | Linux   | `$XDG_CACHE_HOME` or `$HOME`/.cache   | /home/alice/.cache                       |
-/// | macOS   | `$HOME`/Library/Caches                | /Users/Alice/Library/Caches              |
+/// | Linux   | `$XDG_DATA_HOME` or `$HOME`/.local/share | /home/alice/.local/share              |
+/// | macOS   | `$HOME`/Library/Application Support   | /Users/Alice/Library/Application Support |
 /// | Windows | `%LOCALAPPDATA%`                      | C:\Users\Alice\AppData\Local             |
+/// | Haiku   | `$HOME`/config/non-packaged/data      | /boot/home/config/non-packaged/data      |
 ///
-/// NOTE: if the feature `favor-xdg-style` is enabled, `$HOME/.cache` is favorized on macOS.
-pub fn cache_dir() -> Option<PathBuf> {
-    if cfg!(target_os = "linux") {
-        // Linux: Use $XDG_CACHE_HOME or $HOME/.cache
-        env::var_os("XDG_CACHE_HOME")
-            .filter(|s| !s.is_empty())
-            .map(PathBuf::from)
-            .or_else(|| {
-                std::env::home_dir().map(|mut home| {
-                    home.push(CACHE_DIR);
-                    home
-                })
-            })
-    } else if cfg!(target_os = "macos") {
-        // macOS: Use $HOME/Library/Caches
-        //  or $HOME/.cache if favor-xdg-style is enabled
-        std::env::home_dir().map(|mut home| {
-            if cfg!(feature = "favor-xdg-style") {
-                home.push(CACHE_DIR);
-                return home;
-            }
-            home.push("Library");
-            home.push("Caches");
-            home
-        })
-    } else if cfg!(target_os = "windows") {
-        // Windows: Use %LOCALAPPDATA%
-        env::var_os("LOCALAPPDATA")
-            .filter(|s| !s.is_empty())
-            .map(PathBuf::from)
-    } else {
-        // Unsupported platform
-        None
+/// On Windows, the data directory does not roam with the user profile, as application data is
+/// often large or specific to the machine. Use [`roaming_data_dir`] for small data that should
+/// follow the user across machines.
+///
+/// NOTE: if the feature `favor-xdg-style` is enabled, `$HOME/.local/share` is favorized on macOS.
+///
+/// NOTE: if the feature `macos-split` is enabled, `$HOME/Library/Application Support/Data` is returned
+/// on macOS instead, so that it differs from [`config_dir`]. This diverges from the Apple conventions.
+///
+/// NOTE: if the feature `global-prefix-env` is enabled and `$DIRS_LITE_PREFIX` is set, `$DIRS_LITE_PREFIX/data` is returned on all platforms.
+///
+/// NOTE: if the feature `respect-snap` is enabled and the process runs as a Snap on Linux, `$SNAP_USER_DATA/.local/share` is returned.
+///
+/// NOTE: if the feature `appimage` is enabled and the process runs as an AppImage on Linux, `.<name>/data` next to `$APPIMAGE` is returned.
+///
+/// NOTE: if the feature `container-host` is enabled, `$HOST_XDG_DATA_HOME` is preferred on Linux, as set by `toolbox` and `distrobox` for the directory of the host.
+pub fn data_dir() -> Option<PathBuf> {
+    data_dir_with(&ProcessEnv)
+}
+
+/// Like [`data_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    data_dir_result_with(env).ok()
+}
+
+/// Like [`data_dir`], but returns the reason if the directory cannot be resolved.
+pub fn data_dir_result() -> Result<PathBuf, DirError> {
+    data_dir_result_with(&ProcessEnv)
+}
+
+/// Like [`data_dir_result`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_result_with<E: Env + ?Sized>(env: &E) -> Result<PathBuf, DirError> {
+    data_dir_result_styled(env, DirStyle::default(), &DirLayout::default())
+}
+
+/// Like [`data_dir_result`], but also fails if the directory exists and is not a directory, see
+/// [`config_dir_checked`], or if it is too long on Windows.
+///
+/// On Windows, this fails with [`DirError::PathTooLong`] if fewer than 100 characters remain
+/// before `MAX_PATH`, see [`max_path_headroom`], as paths below the directory would then easily
+/// fail to open without long path support.
+pub fn data_dir_checked() -> Result<PathBuf, DirError> {
+    data_dir_checked_with(&ProcessEnv)
+}
+
+/// Like [`data_dir_checked`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_checked_with<E: Env + ?Sized>(env: &E) -> Result<PathBuf, DirError> {
+    let dir = data_dir_result_with(env)?;
+    if dir.exists() && !dir.is_dir() {
+        return Err(DirError::NotADirectory { path: dir });
+    }
+    let headroom = max_path::headroom(&dir);
+    if cfg!(target_os = "windows") && headroom < max_path::MIN_HEADROOM {
+        return Err(DirError::PathTooLong {
+            path: dir,
+            headroom,
+        });
+    }
+    Ok(dir)
+}
+
+/// Like [`data_dir`], but follows the given style on macOS, see [`config_dir_styled`].
+pub fn data_dir_styled(style: DirStyle) -> Option<PathBuf> {
+    data_dir_styled_with(&ProcessEnv, style)
+}
+
+/// Like [`data_dir_styled`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_styled_with<E: Env + ?Sized>(env: &E, style: DirStyle) -> Option<PathBuf> {
+    data_dir_result_styled(env, style, &DirLayout::default()).ok()
+}
+
+fn data_dir_result_styled<E: Env + ?Sized>(
+    env: &E,
+    style: DirStyle,
+    layout: &DirLayout,
+) -> Result<PathBuf, DirError> {
+    let mut dir = PathBuf::new();
+    data_dir_styled_into(env, style, layout, &mut dir).map(|_| dir)
+}
+
+/// Like [`data_dir_result_styled`], but resolves into `buf`, see [`config_dir_into`], and
+/// returns the source the directory was resolved from.
+pub(crate) fn data_dir_styled_into<E: Env + ?Sized>(
+    env: &E,
+    style: DirStyle,
+    layout: &DirLayout,
+    buf: &mut PathBuf,
+) -> Result<DirSource, DirError> {
+    let resolved = resolve_base_dir(env, &BaseDir::DATA, style, layout, buf);
+    traced_into(DirKind::Data, style, resolved, buf)
+}
+
+/// Returns the path to the user's machine-local data directory.
+///
+/// This is the same as [`data_dir`], which does not roam on Windows either, see
+/// [`roaming_data_dir`] for the roaming counterpart.
+pub fn data_local_dir() -> Option<PathBuf> {
+    data_local_dir_with(&ProcessEnv)
+}
+
+/// Like [`data_local_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn data_local_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    data_dir_with(env)
+}
+
+/// Returns the path to the user's roaming data directory, which follows the user across machines on Windows.
+///
+/// On Windows this is `%APPDATA%`, for small data like settings that should be synchronized in
+/// roaming profiles. Other platforms make no such distinction and return [`data_dir`].
+///
+/// |Platform | Value                                 | Example                                  |
+/// | ------- | ------------------------------------- | ---------------------------------------- |
+/// | Linux   | `$XDG_DATA_HOME` or `$HOME`/.local/share | /home/alice/.local/share              |
+/// | macOS   | `$HOME`/Library/Application Support   | /Users/Alice/Library/Application Support |
+/// | Windows | `%APPDATA%`                           | C:\Users\Alice\AppData\Roaming           |
+///
+/// NOTE: if the feature `global-prefix-env` is enabled and `$DIRS_LITE_PREFIX` is set, `$DIRS_LITE_PREFIX/data` is returned on all platforms.
+pub fn roaming_data_dir() -> Option<PathBuf> {
+    roaming_data_dir_with(&ProcessEnv)
+}
+
+/// Like [`roaming_data_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn roaming_data_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    // Windows: Use the RoamingAppData known folder or %APPDATA%, others resolve like data_dir
+    let (style, mut dir) = (DirStyle::default(), PathBuf::new());
+    let resolved = resolve_base_dir(
+        env,
+        &BaseDir::ROAMING_DATA,
+        style,
+        &DirLayout::default(),
+        &mut dir,
+    );
+    traced_into(DirKind::Data, style, resolved, &dir)
+        .ok()
+        .map(|_| dir)
+}
+
+/// Returns the path to the user's cache directory.
+///
+/// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+///
+/// |Platform | Value                                 | Example                                  |
+/// | ------- | ------------------------------------- | ---------------------------------------- |
+/// | Linux   | `$XDG_CACHE_HOME` or `$HOME`/.cache   | /home/alice/.cache                       |
+/// | macOS   | `$HOME`/Library/Caches                | /Users/Alice/Library/Caches              |
+/// | Windows | `%LOCALAPPDATA%`                      | C:\Users\Alice\AppData\Local             |
+/// | Haiku   | `$HOME`/config/cache                  | /boot/home/config/cache                  |
+///
+/// NOTE: if the feature `favor-xdg-style` is enabled, `$HOME/.cache` is favorized on macOS.
+///
+/// NOTE: if the feature `global-prefix-env` is enabled and `$DIRS_LITE_PREFIX` is set, `$DIRS_LITE_PREFIX/cache` is returned on all platforms.
+///
+/// NOTE: if the feature `respect-snap` is enabled and the process runs as a Snap on Linux, `$SNAP_USER_COMMON/.cache` is returned.
+///
+/// NOTE: if the feature `appimage` is enabled and the process runs as an AppImage on Linux, `.<name>/cache` next to `$APPIMAGE` is returned.
+///
+/// NOTE: if the feature `container-host` is enabled, `$HOST_XDG_CACHE_HOME` is preferred on Linux, as set by `toolbox` and `distrobox` for the directory of the host.
+pub fn cache_dir() -> Option<PathBuf> {
+    cache_dir_with(&ProcessEnv)
+}
+
+/// Like [`cache_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    cache_dir_result_with(env).ok()
+}
+
+/// Like [`cache_dir`], but returns the reason if the directory cannot be resolved.
+pub fn cache_dir_result() -> Result<PathBuf, DirError> {
+    cache_dir_result_with(&ProcessEnv)
+}
+
+/// Like [`cache_dir_result`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_result_with<E: Env + ?Sized>(env: &E) -> Result<PathBuf, DirError> {
+    cache_dir_result_styled(env, DirStyle::default(), &DirLayout::default())
+}
+
+/// Like [`cache_dir`], but follows the given style on macOS, see [`config_dir_styled`].
+pub fn cache_dir_styled(style: DirStyle) -> Option<PathBuf> {
+    cache_dir_styled_with(&ProcessEnv, style)
+}
+
+/// Like [`cache_dir_styled`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_styled_with<E: Env + ?Sized>(env: &E, style: DirStyle) -> Option<PathBuf> {
+    cache_dir_result_styled(env, style, &DirLayout::default()).ok()
+}
+
+fn cache_dir_result_styled<E: Env + ?Sized>(
+    env: &E,
+    style: DirStyle,
+    layout: &DirLayout,
+) -> Result<PathBuf, DirError> {
+    let mut dir = PathBuf::new();
+    cache_dir_styled_into(env, style, layout, &mut dir).map(|_| dir)
+}
+
+/// Like [`cache_dir_result_styled`], but resolves into `buf`, see [`config_dir_into`], and
+/// returns the source the directory was resolved from.
+pub(crate) fn cache_dir_styled_into<E: Env + ?Sized>(
+    env: &E,
+    style: DirStyle,
+    layout: &DirLayout,
+    buf: &mut PathBuf,
+) -> Result<DirSource, DirError> {
+    let resolved = resolve_base_dir(env, &BaseDir::CACHE, style, layout, buf);
+    traced_into(DirKind::Cache, style, resolved, buf)
+}
+
+/// Returns the path to the user's state directory, meant for logs, history and other data that should persist but is not worth backing up.
+///
+/// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+///
+/// |Platform | Value                                 | Example                                  |
+/// | ------- | ------------------------------------- | ---------------------------------------- |
+/// | Linux   | `$XDG_STATE_HOME` or `$HOME`/.local/state | /home/alice/.local/state             |
+/// | macOS   | `$HOME`/Library/Application Support   | /Users/Alice/Library/Application Support |
+/// | Windows | `%LOCALAPPDATA%`                      | C:\Users\Alice\AppData\Local             |
+///
+/// NOTE: if the feature `favor-xdg-style` is enabled, `$HOME/.local/state` is favorized on macOS.
+pub fn state_dir() -> Option<PathBuf> {
+    state_dir_with(&ProcessEnv)
+}
+
+/// Like [`state_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn state_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    state_dir_result_with(env).ok()
+}
+
+/// Like [`state_dir`], but returns the reason if the directory cannot be resolved.
+pub fn state_dir_result() -> Result<PathBuf, DirError> {
+    state_dir_result_with(&ProcessEnv)
+}
+
+/// Like [`state_dir_result`], but resolves against the given environment instead of the one of the current process.
+pub fn state_dir_result_with<E: Env + ?Sized>(env: &E) -> Result<PathBuf, DirError> {
+    state_dir_result_styled(env, DirStyle::default())
+}
+
+/// Like [`state_dir`], but follows the given style on macOS, see [`config_dir_styled`].
+pub fn state_dir_styled(style: DirStyle) -> Option<PathBuf> {
+    state_dir_styled_with(&ProcessEnv, style)
+}
+
+/// Like [`state_dir_styled`], but resolves against the given environment instead of the one of the current process.
+pub fn state_dir_styled_with<E: Env + ?Sized>(env: &E, style: DirStyle) -> Option<PathBuf> {
+    state_dir_result_styled(env, style).ok()
+}
+
+fn state_dir_result_styled<E: Env + ?Sized>(env: &E, style: DirStyle) -> Result<PathBuf, DirError> {
+    traced(DirKind::State, style, resolve_state_dir(env, style))
+}
+
+fn resolve_state_dir<E: Env + ?Sized>(env: &E, style: DirStyle) -> Result<PathBuf, DirError> {
+    if style == DirStyle::Portable {
+        return portable_dir("state");
+    }
+
+    if is_xdg_platform() {
+        // Linux and BSDs: Use $XDG_STATE_HOME or $HOME/.local/state
+        xdg_var(env, "XDG_STATE_HOME").or_else(|_| home_subdir(env, STATE_DIR))
+    } else if cfg!(target_os = "macos") {
+        // macOS: Use $HOME/Library/Application Support
+        //  or $HOME/.local/state if the XDG style is selected
+        home_dir_result_with(env).map(|mut home| {
+            if style == DirStyle::Xdg {
+                home.push(STATE_DIR);
+                return home;
+            }
+            home.push("Library");
+            home.push("Application Support");
+            home
+        })
+    } else if cfg!(target_os = "windows") {
+        // Windows: Use the LocalAppData known folder or %LOCALAPPDATA%
+        known_folder(env, KnownFolder::LocalAppData, "LOCALAPPDATA")
+    } else {
+        // Unsupported platform
+        Err(DirError::UnsupportedPlatform)
+    }
+}
+
+/// Returns the path to the user's log directory.
+///
+/// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+///
+/// |Platform | Value                                 | Example                                  |
+/// | ------- | ------------------------------------- | ---------------------------------------- |
+/// | Linux   | `$XDG_STATE_HOME` or `$HOME`/.local/state | /home/alice/.local/state             |
+/// | macOS   | `$HOME`/Library/Logs                  | /Users/Alice/Library/Logs                |
+/// | Windows | `%LOCALAPPDATA%`                      | C:\Users\Alice\AppData\Local             |
+///
+/// NOTE: if the feature `favor-xdg-style` is enabled, `$HOME/.local/state` is favorized on macOS.
+pub fn log_dir() -> Option<PathBuf> {
+    log_dir_with(&ProcessEnv)
+}
+
+/// Like [`log_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn log_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    if cfg!(target_os = "macos") && DirStyle::default() == DirStyle::Native {
+        // macOS: Use $HOME/Library/Logs
+        home_dir_with(env).map(|mut home| {
+            home.push("Library");
+            home.push("Logs");
+            home
+        })
+    } else {
+        // Other platforms: Use the state dir
+        state_dir_with(env)
+    }
+}
+
+/// Returns the path to the user's font directory, where user-installed fonts are placed.
+///
+/// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+///
+/// |Platform | Value                                 | Example                                  |
+/// | ------- | ------------------------------------- | ---------------------------------------- |
+/// | Linux   | [`data_dir`]/fonts or `$HOME`/.fonts  | /home/alice/.local/share/fonts           |
+/// | macOS   | `$HOME`/Library/Fonts                 | /Users/Alice/Library/Fonts               |
+/// | Windows | –                                     | –                                        |
+///
+/// On Linux, the legacy `$HOME/.fonts` is only returned if it exists and [`data_dir`]/fonts does not.
+pub fn font_dir() -> Option<PathBuf> {
+    font_dir_with(&ProcessEnv)
+}
+
+/// Like [`font_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn font_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    if is_xdg_platform() {
+        // Linux and BSDs: Use $XDG_DATA_HOME/fonts or the legacy $HOME/.fonts if only it exists
+        let fonts = data_dir_with(env)?.join("fonts");
+        if !fonts.is_dir() {
+            let legacy = home_subdir(env, ".fonts").ok().filter(|dir| dir.is_dir());
+            if legacy.is_some() {
+                return legacy;
+            }
+        }
+        Some(fonts)
+    } else if cfg!(target_os = "macos") {
+        // macOS: Use $HOME/Library/Fonts
+        home_dir_with(env).map(|mut home| {
+            home.push("Library");
+            home.push("Fonts");
+            home
+        })
+    } else {
+        // Windows and others: No per-user font dir
+        None
+    }
+}
+
+/// Returns the path to the user's runtime directory, meant for sockets, pid files and the like.
+///
+/// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+///
+/// |Platform | Value                                 | Example                                     |
+/// | ------- | ------------------------------------- | ------------------------------------------- |
+/// | Linux   | `$XDG_RUNTIME_DIR`                    | /run/user/1001                              |
+/// | macOS   | –                                     | –                                           |
+/// | Windows | `%TEMP%`\session-`<id>`               | C:\Users\Alice\AppData\Local\Temp\session-1 |
+///
+/// There is no fallback on Linux, as the XDG Base Directory Specification asks applications to
+/// degrade gracefully if `$XDG_RUNTIME_DIR` is not set.
+///
+/// On Windows, `<id>` is the ID of the current session from `ProcessIdToSessionId`, so that users
+/// logged in over RDP at the same time do not share sockets and locks. If it is unknown,
+/// `%TEMP%` itself is returned. Unlike `$XDG_RUNTIME_DIR`, the directory is neither created nor
+/// removed at logoff by Windows.
+///
+/// NOTE: if the feature `favor-xdg-style` is enabled, [`cache_dir`] is returned on macOS and Windows.
+pub fn runtime_dir() -> Option<PathBuf> {
+    runtime_dir_with(&ProcessEnv)
+}
+
+/// Like [`runtime_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn runtime_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    runtime_dir_result_with(env).ok()
+}
+
+/// Like [`runtime_dir`], but returns the reason if the directory cannot be resolved.
+pub fn runtime_dir_result() -> Result<PathBuf, DirError> {
+    runtime_dir_result_with(&ProcessEnv)
+}
+
+/// Like [`runtime_dir_result`], but resolves against the given environment instead of the one of the current process.
+pub fn runtime_dir_result_with<E: Env + ?Sized>(env: &E) -> Result<PathBuf, DirError> {
+    if is_xdg_platform() {
+        // Linux and BSDs: Use $XDG_RUNTIME_DIR, there is no fallback
+        xdg_var(env, "XDG_RUNTIME_DIR")
+    } else if cfg!(any(target_os = "macos", target_os = "windows"))
+        && macos_style() == DirStyle::Xdg
+    {
+        // macOS and Windows: Use the cache dir if favor-xdg-style is enabled
+        cache_dir_result_with(env)
+    } else if cfg!(target_os = "windows") {
+        // Windows: Use a folder of the current session in %TEMP%, or %TEMP% if it is unknown
+        let mut dir = temp_dir_with(env);
+        if let Some(id) = env.session_id() {
+            dir.push(format!("session-{id}"));
+        }
+        Ok(dir)
+    } else {
+        // macOS and unsupported platforms: No runtime dir
+        Err(DirError::UnsupportedPlatform)
+    }
+}
+
+/// Like [`runtime_dir`], but only returns a directory that is safe to use on Unix.
+///
+/// The XDG Base Directory Specification requires `$XDG_RUNTIME_DIR` to be owned by the user
+/// with the access mode `0700`. If the directory does not exist or fails these checks, a
+/// [`Warning::InsecureRuntimeDir`] is emitted and `None` is returned, so that the caller can fall
+/// back to a private directory of its own, e.g. below [`temp_dir`]. There are no checks on
+/// other platforms.
+///
+/// NOTE: requires the feature `checked-runtime-dir`.
+#[cfg(feature = "checked-runtime-dir")]
+pub fn runtime_dir_checked() -> Option<PathBuf> {
+    runtime_dir_checked_with(&ProcessEnv)
+}
+
+/// Like [`runtime_dir_checked`], but resolves against the given environment instead of the one of the current process.
+#[cfg(feature = "checked-runtime-dir")]
+pub fn runtime_dir_checked_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    let dir = runtime_dir_with(env)?;
+    #[cfg(unix)]
+    if !unix::is_private_dir(&dir) {
+        warning::emit(Warning::InsecureRuntimeDir { path: dir });
+        return None;
+    }
+    Some(dir)
+}
+
+/// Returns the path to the document portal, through which sandboxed applications, e.g. Flatpaks,
+/// access the files the user picked in a file chooser.
+///
+/// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+///
+/// |Platform | Value                                 | Example                                  |
+/// | ------- | ------------------------------------- | ---------------------------------------- |
+/// | Linux   | `$XDG_RUNTIME_DIR`/doc                | /run/user/1001/doc                       |
+/// | macOS   | –                                     | –                                        |
+/// | Windows | –                                     | –                                        |
+///
+/// The portal is a FUSE mount of `xdg-document-portal`, which may not be running; the returned
+/// path is not checked for existence.
+pub fn documents_portal_dir() -> Option<PathBuf> {
+    documents_portal_dir_with(&ProcessEnv)
+}
+
+/// Like [`documents_portal_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn documents_portal_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    if cfg!(target_os = "linux") {
+        // Linux: Use $XDG_RUNTIME_DIR/doc
+        xdg_var(env, "XDG_RUNTIME_DIR")
+            .ok()
+            .map(|dir| dir.join("doc"))
+    } else {
+        // Other platforms: No document portal
+        None
+    }
+}
+
+/// Returns the path to the directory for `.desktop` files of applications to start at login.
+///
+/// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+///
+/// |Platform | Value                                 | Example                                  |
+/// | ------- | ------------------------------------- | ---------------------------------------- |
+/// | Linux   | `$XDG_CONFIG_HOME`/autostart or `$HOME`/.config/autostart | /home/alice/.config/autostart |
+/// | macOS   | –                                     | –                                        |
+/// | Windows | –                                     | –                                        |
+///
+/// This follows the XDG Desktop Application Autostart Specification, which the desktop session
+/// reads, so redirections of [`config_dir`] like a Snap or `$DIRS_LITE_PREFIX` do not apply. macOS and Windows start
+/// applications at login through other mechanisms, e.g. launch agents and the registry.
+pub fn autostart_dir() -> Option<PathBuf> {
+    autostart_dir_with(&ProcessEnv)
+}
+
+/// Like [`autostart_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn autostart_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    if is_xdg_platform() {
+        // Linux and BSDs: Use the autostart folder in the XDG config dir the session reads
+        xdg_var(env, "XDG_CONFIG_HOME")
+            .or_else(|_| home_subdir(env, CONFIG_DIR))
+            .ok()
+            .map(|dir| dir.join("autostart"))
+    } else {
+        // macOS, Windows and unsupported platforms: No autostart dir
+        None
+    }
+}
+
+/// Returns the path to the user's executable directory, where user-installed binaries are placed.
+///
+/// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+///
+/// |Platform | Value                                 | Example                                  |
+/// | ------- | ------------------------------------- | ---------------------------------------- |
+/// | Linux   | `$XDG_BIN_HOME` or `$HOME`/.local/bin | /home/alice/.local/bin                   |
+/// | macOS   | –                                     | –                                        |
+/// | Windows | –                                     | –                                        |
+pub fn executable_dir() -> Option<PathBuf> {
+    executable_dir_with(&ProcessEnv)
+}
+
+/// Like [`executable_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn executable_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    if is_xdg_platform() {
+        // Linux and BSDs: Use $XDG_BIN_HOME or $HOME/.local/bin
+        xdg_var(env, "XDG_BIN_HOME")
+            .or_else(|_| home_subdir(env, BIN_DIR))
+            .ok()
+    } else {
+        // Other platforms: No user executable dir
+        None
+    }
+}
+
+/// Returns whether [`executable_dir`] is an entry of `$PATH`, e.g. to warn users that the
+/// binaries installed into it cannot be run by name.
+///
+/// Both the directory and the entries are canonicalized if they exist, so that symbolic links
+/// and trailing separators do not matter. Returns `None` on platforms without a user executable
+/// directory.
+pub fn executable_dir_on_path() -> Option<bool> {
+    executable_dir_on_path_with(&ProcessEnv)
+}
+
+/// Like [`executable_dir_on_path`], but resolves against the given environment instead of the one of the current process.
+pub fn executable_dir_on_path_with<E: Env + ?Sized>(env: &E) -> Option<bool> {
+    let canonical = |dir: PathBuf| std::fs::canonicalize(&dir).unwrap_or(dir);
+    let dir = canonical(executable_dir_with(env)?);
+    let path = env.var_os("PATH").unwrap_or_default();
+    Some(std::env::split_paths(&path).any(|entry| canonical(entry) == dir))
+}
+
+/// Returns the path to the user's bin directory, where CLI installers place binaries and shims
+/// that should be on the user's `PATH`.
+///
+/// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+///
+/// |Platform | Value                                 | Example                                  |
+/// | ------- | ------------------------------------- | ---------------------------------------- |
+/// | Linux   | [`executable_dir`]                    | /home/alice/.local/bin                   |
+/// | macOS   | `$HOME`/bin                           | /Users/Alice/bin                         |
+/// | Windows | –                                     | –                                        |
+pub fn bin_dir() -> Option<PathBuf> {
+    bin_dir_with(&ProcessEnv)
+}
+
+/// Like [`bin_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn bin_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    bin_dir_result_with(env).ok()
+}
+
+fn bin_dir_result_with<E: Env + ?Sized>(env: &E) -> Result<PathBuf, DirError> {
+    if is_xdg_platform() {
+        // Linux and BSDs: Use $XDG_BIN_HOME or $HOME/.local/bin
+        xdg_var(env, "XDG_BIN_HOME").or_else(|_| home_subdir(env, BIN_DIR))
+    } else if cfg!(target_os = "macos") {
+        // macOS: Use $HOME/bin
+        home_subdir(env, "bin")
+    } else {
+        // Other platforms: No user bin dir
+        Err(DirError::UnsupportedPlatform)
+    }
+}
+
+/// Returns whether [`config_dir`] resolves to an existing path, see [`Path::exists`](std::path::Path::exists).
+pub fn config_dir_exists() -> bool {
+    config_dir_exists_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_exists`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_exists_with<E: Env + ?Sized>(env: &E) -> bool {
+    config_dir_result_with(env).is_ok_and(|dir| dir.exists())
+}
+
+/// Returns whether [`data_dir`] resolves to an existing path, see [`Path::exists`](std::path::Path::exists).
+pub fn data_dir_exists() -> bool {
+    data_dir_exists_with(&ProcessEnv)
+}
+
+/// Like [`data_dir_exists`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_exists_with<E: Env + ?Sized>(env: &E) -> bool {
+    data_dir_result_with(env).is_ok_and(|dir| dir.exists())
+}
+
+/// Returns whether [`cache_dir`] resolves to an existing path, see [`Path::exists`](std::path::Path::exists).
+pub fn cache_dir_exists() -> bool {
+    cache_dir_exists_with(&ProcessEnv)
+}
+
+/// Like [`cache_dir_exists`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_exists_with<E: Env + ?Sized>(env: &E) -> bool {
+    cache_dir_result_with(env).is_ok_and(|dir| dir.exists())
+}
+
+/// Returns whether the current process runs inside a sandbox like Flatpak.
+///
+/// This checks for the markers the sandboxes set, `$FLATPAK_ID` for Flatpak and `$container`
+/// for e.g. systemd-nspawn, Podman and Toolbox. Within a sandbox, the XDG variables usually
+/// point to per-app locations, e.g. `$HOME/.var/app/<app-id>/config` for Flatpak.
+pub fn is_sandboxed() -> bool {
+    is_sandboxed_with(&ProcessEnv)
+}
+
+/// Like [`is_sandboxed`], but resolves against the given environment instead of the one of the current process.
+pub fn is_sandboxed_with<E: Env + ?Sized>(env: &E) -> bool {
+    ["FLATPAK_ID", "container"]
+        .iter()
+        .any(|key| env.var_os(key).is_some_and(|value| !value.is_empty()))
+}
+
+/// Returns whether the current process runs without a graphical session, e.g. on a CI runner
+/// or over SSH, so that callers can skip GUI-related warnings.
+///
+/// On Linux and the BSDs, this is the case if neither `$DISPLAY` nor `$WAYLAND_DISPLAY` is set
+/// and `$XDG_SESSION_TYPE` is neither `x11` nor `wayland`. Always `false` on other platforms.
+///
+/// The resolvers do not depend on a graphical session: without one, config, data, cache and
+/// state still fall back to `$HOME`, e.g. `$HOME/.config`, if their `$XDG_*` variables are unset.
+/// Only [`runtime_dir`] has no fallback, as the XDG Base Directory Specification asks for.
+pub fn is_headless() -> bool {
+    is_headless_with(&ProcessEnv)
+}
+
+/// Like [`is_headless`], but resolves against the given environment instead of the one of the current process.
+pub fn is_headless_with<E: Env + ?Sized>(env: &E) -> bool {
+    if !is_xdg_platform() {
+        return false;
+    }
+    let is_set = |key| env.var_os(key).is_some_and(|value| !value.is_empty());
+    let graphical_session = env
+        .var_os("XDG_SESSION_TYPE")
+        .is_some_and(|session| session == "x11" || session == "wayland");
+    !is_set("DISPLAY") && !is_set("WAYLAND_DISPLAY") && !graphical_session
+}
+
+/// Returns whether the current process runs inside the macOS App Sandbox.
+///
+/// Sandboxed apps get `$HOME` pointed to their container, `$HOME/Library/Containers/<bundle-id>/Data`,
+/// so [`config_dir`] and friends resolve inside the container, e.g.
+/// `/Users/Alice/Library/Containers/com.example.myapp/Data/Library/Application Support`. This
+/// checks for `$APP_SANDBOX_CONTAINER_ID`, or a `$HOME` of that shape. Always `false` on other platforms.
+pub fn is_macos_sandboxed() -> bool {
+    is_macos_sandboxed_with(&ProcessEnv)
+}
+
+/// Like [`is_macos_sandboxed`], but resolves against the given environment instead of the one of the current process.
+pub fn is_macos_sandboxed_with<E: Env + ?Sized>(env: &E) -> bool {
+    if !cfg!(target_os = "macos") {
+        return false;
+    }
+    if env
+        .var_os("APP_SANDBOX_CONTAINER_ID")
+        .is_some_and(|value| !value.is_empty())
+    {
+        return true;
+    }
+    let Ok(home) = home_dir_result_with(env) else {
+        return false;
+    };
+    // $HOME ends with Library/Containers/<bundle-id>/Data
+    let mut components = home.components().rev();
+    matches!(
+        (
+            components.next(),
+            components.next(),
+            components.next(),
+            components.next()
+        ),
+        (
+            Some(Component::Normal(data)),
+            Some(Component::Normal(_)),
+            Some(Component::Normal(containers)),
+            Some(Component::Normal(library)),
+        ) if data == "Data" && containers == "Containers" && library == "Library"
+    )
+}
+
+/// Returns the path to the App Group container `group_id`, which an app shares with its
+/// extensions and other apps of the same team on macOS.
+///
+/// |Platform | Value                                    | Example                                              |
+/// | ------- | ---------------------------------------- | ---------------------------------------------------- |
+/// | macOS   | `$HOME`/Library/Group Containers/`group_id` | /Users/Alice/Library/Group Containers/group.com.example |
+///
+/// Returns `None` on other platforms, and if `group_id` is empty or not a single path
+/// component, e.g. contains a `/`.
+///
+/// NOTE: the path is derived from `$HOME`, which points into the app's own container in the App
+/// Sandbox, see [`is_macos_sandboxed`]. Sandboxed apps should ask Foundation's
+/// `containerURLForSecurityApplicationGroupIdentifier` instead.
+pub fn group_container_dir(group_id: &str) -> Option<PathBuf> {
+    group_container_dir_with(&ProcessEnv, group_id)
+}
+
+/// Like [`group_container_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn group_container_dir_with<E: Env + ?Sized>(env: &E, group_id: &str) -> Option<PathBuf> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    let mut components = Path::new(group_id).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(id)), None) if id == group_id
+    ) {
+        return None;
+    }
+    // macOS: Use $HOME/Library/Group Containers/<group_id>
+    home_dir_with(env).map(|mut home| {
+        home.push("Library");
+        home.push("Group Containers");
+        home.push(group_id);
+        home
+    })
+}
+
+/// Returns the system-wide config directories, in order of preference.
+///
+/// The returned value depends on the operating system:
+///
+/// |Platform | Value                                 | Example                                  |
+/// | ------- | ------------------------------------- | ---------------------------------------- |
+/// | Linux   | `$XDG_CONFIG_DIRS` or /etc/xdg        | [/etc/xdg]                               |
+/// | macOS   | /Library/Application Support          | [/Library/Application Support]           |
+/// | Windows | `%PROGRAMDATA%`                       | [C:\ProgramData]                         |
+///
+/// On Linux, `$XDG_CONFIG_DIRS` is split on `:` and empty or relative entries are skipped. If no
+/// entry remains, the default of the XDG Base Directory Specification, /etc/xdg, is returned.
+/// Packagers can change that default by setting `DIRS_LITE_DEFAULT_XDG_CONFIG_DIRS` at build
+/// time, e.g. to `/etc/xdg:/usr/share/xdg`.
+///
+/// Other platforms return an empty `Vec`.
+pub fn system_config_dirs() -> Vec<PathBuf> {
+    system_config_dirs_with(&ProcessEnv)
+}
+
+/// Like [`system_config_dirs`], but resolves against the given environment instead of the one of the current process.
+pub fn system_config_dirs_with<E: Env + ?Sized>(env: &E) -> Vec<PathBuf> {
+    if is_xdg_platform() {
+        // Linux and BSDs: Use $XDG_CONFIG_DIRS or the default, /etc/xdg
+        // build.rs rejects an invalid default, so this never falls back to an empty list
+        let default = default_dirs::parse_default_dirs(DEFAULT_XDG_CONFIG_DIRS).unwrap_or_default();
+        xdg_dirs(env, "XDG_CONFIG_DIRS", &default)
+    } else if cfg!(target_os = "macos") {
+        // macOS: Use /Library/Application Support
+        vec![PathBuf::from("/Library/Application Support")]
+    } else if cfg!(target_os = "windows") {
+        // Windows: Use %PROGRAMDATA%
+        non_empty_var(env, "PROGRAMDATA").into_iter().collect()
+    } else {
+        // Unsupported platform
+        Vec::new()
+    }
+}
+
+/// Returns the system-wide data directories, in order of preference.
+///
+/// The returned value depends on the operating system:
+///
+/// |Platform | Value                                           | Example                      |
+/// | ------- | ----------------------------------------------- | ---------------------------- |
+/// | Linux   | `$XDG_DATA_DIRS` or /usr/local/share:/usr/share | [/usr/local/share, /usr/share] |
+/// | macOS   | –                                               | []                           |
+/// | Windows | –                                               | []                           |
+///
+/// On Linux, `$XDG_DATA_DIRS` is split on `:` and empty or relative entries are skipped. If no
+/// entry remains, the default of the XDG Base Directory Specification is returned.
+pub fn system_data_dirs() -> Vec<PathBuf> {
+    system_data_dirs_with(&ProcessEnv)
+}
+
+/// Like [`system_data_dirs`], but resolves against the given environment instead of the one of the current process.
+pub fn system_data_dirs_with<E: Env + ?Sized>(env: &E) -> Vec<PathBuf> {
+    if is_xdg_platform() {
+        // Linux and BSDs: Use $XDG_DATA_DIRS or /usr/local/share:/usr/share
+        xdg_dirs(env, "XDG_DATA_DIRS", &["/usr/local/share", "/usr/share"])
+    } else {
+        // Other platforms: No system-wide data dirs
+        Vec::new()
+    }
+}
+
+/// Returns the first existing path of `relative` in one of the [`config_dirs`].
+///
+/// The directories are searched in order of preference, the user's config directory first,
+/// e.g. `find_config_file("myapp/config.toml")` on Linux checks `$HOME/.config/myapp/config.toml`
+/// before `/etc/xdg/myapp/config.toml`.
+pub fn find_config_file(relative: impl AsRef<Path>) -> Option<PathBuf> {
+    find_config_file_with(&ProcessEnv, relative)
+}
+
+/// Like [`find_config_file`], but resolves against the given environment instead of the one of the current process.
+pub fn find_config_file_with<E: Env + ?Sized>(
+    env: &E,
+    relative: impl AsRef<Path>,
+) -> Option<PathBuf> {
+    let relative = relative.as_ref();
+    config_dirs_with(env)
+        .map(|dir| dir.join(relative))
+        .find(|path| path.exists())
+}
+
+/// Returns the first existing path of `relative` in one of the [`data_dirs`].
+///
+/// Like [`find_config_file`], the user's data directory is searched first, e.g.
+/// `find_data_file("myapp/icons")` on Linux checks `$HOME/.local/share/myapp/icons` before
+/// `/usr/local/share/myapp/icons` and `/usr/share/myapp/icons`.
+pub fn find_data_file(relative: impl AsRef<Path>) -> Option<PathBuf> {
+    find_data_file_with(&ProcessEnv, relative)
+}
+
+/// Like [`find_data_file`], but resolves against the given environment instead of the one of the current process.
+pub fn find_data_file_with<E: Env + ?Sized>(
+    env: &E,
+    relative: impl AsRef<Path>,
+) -> Option<PathBuf> {
+    let relative = relative.as_ref();
+    data_dirs_with(env)
+        .map(|dir| dir.join(relative))
+        .find(|path| path.exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(target_os = "linux")]
+    use crate::child::run_in_child;
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "macos",
+        all(target_os = "windows", not(feature = "windows-known-folders"))
+    ))]
+    use crate::env_guard::EnvGuard;
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "windows",
+        feature = "global-prefix-env"
+    ))]
+    use std::env;
+
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "windows",
+        feature = "global-prefix-env"
+    ))]
+    unsafe fn set_var(key: &str, value: &str) {
+        unsafe { env::set_var(key, value) };
+    }
+
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "windows",
+        feature = "global-prefix-env"
+    ))]
+    unsafe fn remove_var(key: &str) {
+        unsafe { env::remove_var(key) };
+    }
+
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "windows",
+        feature = "global-prefix-env"
+    ))]
+    fn restore_var(key: &str, original: Option<String>) {
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe {
+            match original {
+                Some(val) => set_var(key, &val),
+                None => remove_var(key),
+            }
+        }
+    }
+
+    #[cfg(any(
+        target_os = "linux",
+        all(target_os = "macos", not(feature = "favor-xdg-style"))
+    ))]
+    fn restore_var_os(key: &str, original: Option<std::ffi::OsString>) {
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe {
+            match original {
+                Some(val) => env::set_var(key, val),
+                None => env::remove_var(key),
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn home_dir_uses_home() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(home_dir_with(&env), Some(PathBuf::from("/home/testuser")));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn home_dir_returns_none_when_home_unset() {
+        let env = MapEnv::default();
+        assert_eq!(home_dir_with(&env), None);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn home_dir_returns_none_when_home_blank() {
+        for home in ["", " ", "\t\n"] {
+            let env = MapEnv::new().with("HOME", home);
+            assert_eq!(home_dir_with(&env), None);
+            assert_eq!(config_dir_with(&env), None);
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_home_dir_rejects_unexpanded_tilde() {
+        struct PasswdEnv(MapEnv);
+
+        impl Env for PasswdEnv {
+            fn var_os(&self, key: &str) -> Option<std::ffi::OsString> {
+                self.0.var_os(key)
+            }
+
+            fn passwd_home(&self) -> Option<PathBuf> {
+                Some(PathBuf::from("/home/daemon"))
+            }
+        }
+
+        let env = MapEnv::new().with("HOME", "~bad");
+        assert_eq!(home_dir_with(&env), None);
+        assert_eq!(config_dir_with(&env), None);
+        assert_eq!(
+            home_dir_with(&PasswdEnv(env)),
+            Some(PathBuf::from("/home/daemon"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_home_dir_rejects_relative_home() {
+        let env = MapEnv::new().with("HOME", "subdir");
+        assert_eq!(home_dir_with(&env), None);
+        assert_eq!(config_dir_with(&env), None);
+
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.config"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_rejects_nul_byte_in_home_and_xdg() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        // The process environment cannot hold a NUL byte, so only an `Env` can supply one
+        let env = MapEnv::new().with("HOME", OsStr::from_bytes(b"/home/test\0user"));
+        assert_eq!(home_dir_with(&env), None);
+        assert_eq!(config_dir_with(&env), None);
+
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CONFIG_HOME", OsStr::from_bytes(b"/custom\0/config"));
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.config"))
+        );
+        assert_eq!(
+            non_empty_var(&env, "XDG_CONFIG_HOME"),
+            Err(DirError::NulInEnvVar {
+                key: "XDG_CONFIG_HOME"
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", not(feature = "passwd-fallback")))]
+    fn linux_config_dir_returns_none_when_home_relative() {
+        assert_eq!(run_in_child(&[("HOME", "subdir")], "config_dir"), None);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", not(feature = "passwd-fallback")))]
+    fn linux_config_dir_returns_none_when_home_empty() {
+        assert_eq!(run_in_child(&[("HOME", "")], "config_dir"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn home_dir_falls_back_to_passwd_home() {
+        struct PasswdEnv(MapEnv);
+
+        impl Env for PasswdEnv {
+            fn var_os(&self, key: &str) -> Option<std::ffi::OsString> {
+                self.0.var_os(key)
+            }
+
+            fn passwd_home(&self) -> Option<PathBuf> {
+                Some(PathBuf::from("/home/daemon"))
+            }
+        }
+
+        let expected = Some(PathBuf::from("/home/daemon"));
+        assert_eq!(home_dir_with(&PasswdEnv(MapEnv::new())), expected);
+        assert_eq!(
+            home_dir_with(&PasswdEnv(MapEnv::new().with("HOME", ""))),
+            expected
+        );
+        assert_eq!(
+            home_dir_with(&PasswdEnv(MapEnv::new().with("HOME", "/home/testuser"))),
+            Some(PathBuf::from("/home/testuser"))
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", feature = "passwd-fallback"))]
+    fn linux_home_dir_uses_passwd_database_without_home() {
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        let _env = unsafe { EnvGuard::new().remove("HOME") };
+
+        let home = home_dir().expect("current user has a passwd entry");
+        assert!(home.is_absolute());
+        assert!(config_dir().is_some());
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_appdata_falls_back_to_userprofile() {
+        let env = MapEnv::new()
+            .with("USERPROFILE", "C:\\Users\\testuser")
+            .with("APPDATA", "");
+
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Roaming"))
+        );
+        assert_eq!(
+            roaming_data_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Roaming"))
+        );
+        assert_eq!(
+            data_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Local"))
+        );
+        assert_eq!(
+            cache_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Local"))
+        );
+        assert_eq!(
+            config_dir_result_with(&MapEnv::new()),
+            Err(DirError::MissingEnvVar { key: "APPDATA" })
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_home_dir_uses_userprofile() {
+        let env = MapEnv::new()
+            .with("USERPROFILE", "C:\\Users\\testuser")
+            .with("HOMEDRIVE", "D:")
+            .with("HOMEPATH", "\\Users\\other");
+        assert_eq!(
+            home_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\testuser"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_home_dir_honors_posix_home_only_with_feature() {
+        let env = MapEnv::new()
+            .with("HOME", "/c/Users/alice")
+            .with("USERPROFILE", "C:\\Users\\bob");
+        let expected = if cfg!(feature = "unix-home-on-windows") {
+            "C:\\Users\\alice"
+        } else {
+            "C:\\Users\\bob"
+        };
+        assert_eq!(home_dir_with(&env), Some(PathBuf::from(expected)));
+    }
+
+    #[test]
+    #[cfg(all(target_os = "windows", feature = "unix-home-on-windows"))]
+    fn windows_home_dir_skips_relative_home() {
+        let env = MapEnv::new()
+            .with("HOME", "alice")
+            .with("USERPROFILE", "C:\\Users\\bob");
+        assert_eq!(home_dir_with(&env), Some(PathBuf::from("C:\\Users\\bob")));
+
+        let env = env.with("HOME", "D:\\home\\alice");
+        assert_eq!(home_dir_with(&env), Some(PathBuf::from("D:\\home\\alice")));
+    }
+
+    #[test]
+    fn windows_path_from_posix_translates_drive_paths() {
+        for (posix, windows) in [
+            ("/c/Users/alice", "C:\\Users\\alice"),
+            ("/cygdrive/d/home/alice", "D:\\home\\alice"),
+            ("/c", "C:\\"),
+        ] {
+            assert_eq!(
+                windows_path_from_posix(posix.as_ref()),
+                Some(PathBuf::from(windows))
+            );
+        }
+        for invalid in ["/home/alice", "/", "relative/home", ""] {
+            assert_eq!(windows_path_from_posix(invalid.as_ref()), None);
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_home_dir_falls_back_to_homedrive_homepath() {
+        let env = MapEnv::new()
+            .with("USERPROFILE", "")
+            .with("HOMEDRIVE", "D:")
+            .with("HOMEPATH", "\\Users\\testuser");
+        assert_eq!(
+            home_dir_with(&env),
+            Some(PathBuf::from("D:\\Users\\testuser"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_home_dir_requires_both_homedrive_and_homepath() {
+        let env = MapEnv::new().with("HOMEDRIVE", "D:");
+        assert_eq!(home_dir_with(&env), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_result_reports_missing_home() {
+        let env = MapEnv::new();
+        assert_eq!(home_dir_result_with(&env), Err(DirError::MissingHome));
+        assert_eq!(config_dir_result_with(&env), Err(DirError::MissingHome));
+        assert_eq!(data_dir_result_with(&env), Err(DirError::MissingHome));
+        assert_eq!(cache_dir_result_with(&env), Err(DirError::MissingHome));
+        assert_eq!(state_dir_result_with(&env), Err(DirError::MissingHome));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_result_reports_missing_and_empty_runtime_dir() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(
+            runtime_dir_result_with(&env),
+            Err(DirError::MissingEnvVar {
+                key: "XDG_RUNTIME_DIR"
+            })
+        );
+
+        let env = env.with("XDG_RUNTIME_DIR", "");
+        assert_eq!(
+            runtime_dir_result_with(&env),
+            Err(DirError::EmptyEnvVar {
+                key: "XDG_RUNTIME_DIR"
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(all(
+        any(target_os = "macos", target_os = "windows"),
+        not(feature = "favor-xdg-style")
+    ))]
+    fn runtime_dir_result_reports_unsupported_platform() {
+        assert_eq!(
+            runtime_dir_result_with(&MapEnv::new()),
+            Err(DirError::UnsupportedPlatform)
+        );
+    }
+
+    #[test]
+    fn result_matches_option() {
+        assert_eq!(config_dir_result().ok(), config_dir());
+        assert_eq!(data_dir_result().ok(), data_dir());
+        assert_eq!(cache_dir_result().ok(), cache_dir());
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn config_dir_returns_some() {
+        let result = config_dir();
+        assert!(
+            result.is_some(),
+            "config_dir should return Some on supported platforms"
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_uses_xdg_config_home_when_set() {
+        let env = MapEnv::new().with("XDG_CONFIG_HOME", "/custom/config");
+
+        let result = config_dir_with(&env);
+        assert_eq!(result, Some(PathBuf::from("/custom/config")));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_falls_back_to_home_when_xdg_unset() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+
+        let result = config_dir_with(&env);
+        assert_eq!(result, Some(PathBuf::from("/home/testuser/.config")));
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_styled_dirs_follow_style_at_runtime() {
+        let env = MapEnv::new().with("HOME", "/Users/testuser");
+
+        assert_eq!(
+            config_dir_styled_with(&env, DirStyle::Native),
+            Some(PathBuf::from("/Users/testuser/Library/Application Support"))
+        );
+        assert_eq!(
+            config_dir_styled_with(&env, DirStyle::Xdg),
+            Some(PathBuf::from("/Users/testuser/.config"))
+        );
+        assert_eq!(
+            cache_dir_styled_with(&env, DirStyle::Native),
+            Some(PathBuf::from("/Users/testuser/Library/Caches"))
+        );
+        assert_eq!(
+            cache_dir_styled_with(&env, DirStyle::Xdg),
+            Some(PathBuf::from("/Users/testuser/.cache"))
+        );
+        assert_eq!(
+            config_dir_styled_with(&env, DirStyle::default()),
+            config_dir_with(&env)
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_styled_dirs_ignore_style() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        for style in [DirStyle::Native, DirStyle::Xdg] {
+            assert_eq!(config_dir_styled_with(&env, style), config_dir_with(&env));
+            assert_eq!(data_dir_styled_with(&env, style), data_dir_with(&env));
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn portable_dirs_are_next_to_current_exe() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        let exe_dir = std::env::current_exe()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .to_path_buf();
+
+        for (dir, name) in [
+            (config_dir_styled_with(&env, DirStyle::Portable), "config"),
+            (data_dir_styled_with(&env, DirStyle::Portable), "data"),
+            (cache_dir_styled_with(&env, DirStyle::Portable), "cache"),
+            (state_dir_styled_with(&env, DirStyle::Portable), "state"),
+        ] {
+            let dir = dir.unwrap();
+            assert_eq!(dir.parent(), Some(exe_dir.as_path()));
+            assert_eq!(dir.file_name(), Some(std::ffi::OsStr::new(name)));
+        }
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
+    fn macos_detects_app_sandbox_container() {
+        let home = "/Users/testuser/Library/Containers/com.example.myapp/Data";
+        let env = MapEnv::new().with("HOME", home);
+        assert!(is_macos_sandboxed_with(&env));
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from(home).join("Library/Application Support"))
+        );
+
+        let env = MapEnv::new()
+            .with("HOME", "/Users/testuser")
+            .with("APP_SANDBOX_CONTAINER_ID", "com.example.myapp");
+        assert!(is_macos_sandboxed_with(&env));
+
+        let env = MapEnv::new().with("HOME", "/Users/testuser");
+        assert!(!is_macos_sandboxed_with(&env));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn is_macos_sandboxed_is_false_elsewhere() {
+        let env = MapEnv::new()
+            .with(
+                "HOME",
+                "/Users/testuser/Library/Containers/com.example.myapp/Data",
+            )
+            .with("APP_SANDBOX_CONTAINER_ID", "com.example.myapp");
+        assert!(!is_macos_sandboxed_with(&env));
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_group_container_dir() {
+        let env = MapEnv::new().with("HOME", "/Users/testuser");
+        assert_eq!(
+            group_container_dir_with(&env, "group.com.example.shared"),
+            Some(PathBuf::from(
+                "/Users/testuser/Library/Group Containers/group.com.example.shared"
+            ))
+        );
+        for invalid in ["", "group/escape", "..", ".", "/group"] {
+            assert_eq!(group_container_dir_with(&env, invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn group_container_dir_is_none_elsewhere() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(group_container_dir_with(&env, "group.com.example"), None);
+    }
+
+    // The macOS branches are plain functions, so both styles are tested on every platform and
+    // with either setting of the feature `favor-xdg-style`
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn macos_branches_follow_native_style() {
+        let env = MapEnv::new()
+            .with("HOME", "/Users/testuser")
+            .with("USERPROFILE", "/Users/testuser");
+        let home = home_dir_with(&env).unwrap();
+        let layout = DirLayout::default();
+        let support = home.join("Library").join("Application Support");
+
+        assert_eq!(
+            config_dir_macos(&env, DirStyle::Native, &layout),
+            Ok(support.clone())
+        );
+        let data = if cfg!(feature = "macos-split") {
+            support.join("Data")
+        } else {
+            support
+        };
+        assert_eq!(data_dir_macos(&env, DirStyle::Native, &layout), Ok(data));
+        assert_eq!(
+            cache_dir_macos(&env, DirStyle::Native, &layout),
+            Ok(home.join("Library").join("Caches"))
+        );
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn macos_branches_follow_xdg_style() {
+        let env = MapEnv::new()
+            .with("HOME", "/Users/testuser")
+            .with("USERPROFILE", "/Users/testuser");
+        let home = home_dir_with(&env).unwrap();
+        let layout = DirLayout::default();
+
+        assert_eq!(
+            config_dir_macos(&env, DirStyle::Xdg, &layout),
+            Ok(home.join(".config"))
+        );
+        assert_eq!(
+            data_dir_macos(&env, DirStyle::Xdg, &layout),
+            Ok(home.join(".local/share"))
+        );
+        assert_eq!(
+            cache_dir_macos(&env, DirStyle::Xdg, &layout),
+            Ok(home.join(".cache"))
+        );
+    }
+
+    #[test]
+    fn macos_style_follows_feature() {
+        let expected = if cfg!(feature = "favor-xdg-style") {
+            DirStyle::Xdg
+        } else {
+            DirStyle::Native
+        };
+        assert_eq!(macos_style(), expected);
+        assert_eq!(DirStyle::default(), expected);
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_preference_dir_uses_library_preferences() {
+        let env = MapEnv::new().with("HOME", "/Users/testuser");
+        assert_eq!(
+            preference_dir_with(&env),
+            Some(PathBuf::from("/Users/testuser/Library/Preferences"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_preference_dir_handles_non_utf8_home() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let non_utf8_home = OsStr::from_bytes(b"/Users/\xff\xfe");
+        let env = MapEnv::new().with("HOME", non_utf8_home);
+
+        let mut expected = PathBuf::from(non_utf8_home);
+        expected.push("Library");
+        expected.push("Preferences");
+        assert_eq!(preference_dir_with(&env), Some(expected));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn preference_dir_matches_config_dir() {
+        assert_eq!(preference_dir(), config_dir());
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
+    fn macos_config_dir_uses_library_application_support() {
+        let original = env::var("HOME").ok();
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { set_var("HOME", "/Users/testuser") };
+
+        let result = config_dir();
+        assert_eq!(
+            result,
+            Some(PathBuf::from("/Users/testuser/Library/Application Support"))
+        );
+
+        restore_var("HOME", original);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", feature = "favor-xdg-style"))]
+    fn macos_config_dir_uses_xdg_style() {
+        let original = env::var("HOME").ok();
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { set_var("HOME", "/Users/testuser") };
+
+        let result = config_dir();
+        assert_eq!(result, Some(PathBuf::from("/Users/testuser/.config")));
+
+        restore_var("HOME", original);
+    }
+
+    #[test]
+    #[cfg(target_os = "haiku")]
+    fn haiku_config_dir_uses_config_settings() {
+        let env = MapEnv::new().with("HOME", "/boot/home");
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from("/boot/home/config/settings"))
+        );
+        assert_eq!(config_dir_with(&MapEnv::new()), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "haiku")]
+    fn haiku_data_dir_uses_non_packaged_data() {
+        let env = MapEnv::new().with("HOME", "/boot/home");
+        assert_eq!(
+            data_dir_with(&env),
+            Some(PathBuf::from("/boot/home/config/non-packaged/data"))
+        );
+        assert_eq!(data_dir_with(&MapEnv::new()), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "haiku")]
+    fn haiku_cache_dir_uses_config_cache() {
+        let env = MapEnv::new().with("HOME", "/boot/home");
+        assert_eq!(
+            cache_dir_with(&env),
+            Some(PathBuf::from("/boot/home/config/cache"))
+        );
+        assert_eq!(cache_dir_with(&MapEnv::new()), None);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "windows", not(feature = "windows-known-folders")))]
+    fn windows_uses_appdata() {
+        let original = env::var("APPDATA").ok();
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { set_var("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming") };
+
+        let result = config_dir();
+        assert_eq!(
+            result,
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Roaming"))
+        );
+
+        restore_var("APPDATA", original);
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_expands_var_references() {
+        let env = MapEnv::new()
+            .with("USERPROFILE", "C:\\Users\\testuser")
+            .with("APPDATA", "%USERPROFILE%\\AppData\\Roaming")
+            .with("LOCALAPPDATA", "%USERPROFILE%\\AppData\\Local");
+
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Roaming"))
+        );
+        assert_eq!(
+            data_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Local"))
+        );
+        assert_eq!(
+            cache_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Local"))
+        );
+    }
+
+    #[test]
+    fn windows_separators_replaces_forward_slashes() {
+        assert_eq!(
+            windows_separators(PathBuf::from("C:/Users/alice/AppData/Roaming")).as_os_str(),
+            "C:\\Users\\alice\\AppData\\Roaming"
+        );
+        assert_eq!(
+            windows_separators(PathBuf::from("C:\\Users/alice\\AppData/Roaming")).as_os_str(),
+            "C:\\Users\\alice\\AppData\\Roaming"
+        );
+        assert_eq!(
+            windows_separators(PathBuf::from("\\\\?\\C:\\Users/alice")).as_os_str(),
+            "\\\\?\\C:\\Users\\alice"
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "windows", not(feature = "windows-known-folders")))]
+    fn windows_normalizes_forward_slashes_in_appdata() {
+        let env = MapEnv::new()
+            .with("APPDATA", "C:/Users/alice/AppData/Roaming")
+            .with("LOCALAPPDATA", "C:\\Users/alice\\AppData/Local");
+        assert_eq!(
+            config_dir_with(&env).unwrap().join("myapp").as_os_str(),
+            "C:\\Users\\alice\\AppData\\Roaming\\myapp"
+        );
+        assert_eq!(
+            data_dir_with(&env).unwrap().as_os_str(),
+            "C:\\Users\\alice\\AppData\\Local"
+        );
+    }
+
+    #[test]
+    fn expand_windows_vars_resolves_nested_references() {
+        let env = MapEnv::new()
+            .with("SystemDrive", "C:")
+            .with("USERPROFILE", "%SystemDrive%\\Users\\testuser");
+        assert_eq!(
+            expand_windows_vars(&env, PathBuf::from("%USERPROFILE%\\AppData")),
+            PathBuf::from("C:\\Users\\testuser\\AppData")
+        );
+    }
+
+    #[test]
+    fn expand_windows_vars_keeps_unresolved_references() {
+        let env = MapEnv::new().with("EMPTY", "");
+        for value in ["%UNSET%\\AppData", "100%", "%%", "%EMPTY%x"] {
+            let expected = value.replace("%EMPTY%", "");
+            assert_eq!(
+                expand_windows_vars(&env, PathBuf::from(value)),
+                PathBuf::from(expected)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn expand_windows_vars_expands_non_unicode_values() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let env = MapEnv::new()
+            .with("USERPROFILE", OsStr::from_bytes(b"C:\\Users\\\xff"))
+            .with("\u{e9}", "accent");
+        assert_eq!(
+            expand_windows_vars(
+                &env,
+                PathBuf::from(OsStr::from_bytes(b"%USERPROFILE%\\\xfe"))
+            ),
+            PathBuf::from(OsStr::from_bytes(b"C:\\Users\\\xff\\\xfe"))
+        );
+        assert_eq!(
+            expand_windows_vars(
+                &env,
+                PathBuf::from(OsStr::from_bytes(b"%\xff%\\%\xc3\xa9%"))
+            ),
+            PathBuf::from(OsStr::from_bytes(b"%\xff%\\accent"))
+        );
+    }
+
+    #[test]
+    fn expand_windows_vars_guards_against_cycles() {
+        let env = MapEnv::new()
+            .with("A", "%B%\\a")
+            .with("B", "%A%\\b")
+            .with("SELF", "x%SELF%");
+        assert_eq!(
+            expand_windows_vars(&env, PathBuf::from("%A%")),
+            PathBuf::from("%A%\\b\\a")
+        );
+        assert_eq!(
+            expand_windows_vars(&env, PathBuf::from("%SELF%")),
+            PathBuf::from("x%SELF%")
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_config_roaming_and_local_dirs() {
+        let env = MapEnv::new()
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming")
+            .with("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local");
+
+        assert_eq!(
+            config_roaming_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Roaming"))
+        );
+        assert_eq!(
+            config_local_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Local"))
+        );
+        assert_eq!(config_dir_with(&env), config_roaming_dir_with(&env));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn config_roaming_and_local_dirs_match_config_dir() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(config_roaming_dir_with(&env), config_dir_with(&env));
+        assert_eq!(config_local_dir_with(&env), config_dir_with(&env));
+    }
+
+    #[test]
+    fn config_dir_path_is_absolute() {
+        let result = config_dir();
+        if let Some(path) = result {
+            assert!(
+                path.is_absolute(),
+                "config_dir should return an absolute path"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn data_dir_returns_some() {
+        let result = data_dir();
+        assert!(
+            result.is_some(),
+            "data_dir should return Some on supported platforms"
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_data_dir_uses_xdg_data_home_when_set() {
+        let env = MapEnv::new().with("XDG_DATA_HOME", "/custom/data");
+
+        let result = data_dir_with(&env);
+        assert_eq!(result, Some(PathBuf::from("/custom/data")));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_data_dir_falls_back_to_home_when_xdg_unset() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+
+        let result = data_dir_with(&env);
+        assert_eq!(result, Some(PathBuf::from("/home/testuser/.local/share")));
+    }
+
+    #[test]
+    #[cfg(all(
+        target_os = "macos",
+        not(feature = "favor-xdg-style"),
+        not(feature = "macos-split")
+    ))]
+    fn macos_data_dir_uses_library_application_support() {
+        let original = env::var("HOME").ok();
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { set_var("HOME", "/Users/testuser") };
+
+        let result = data_dir();
+        assert_eq!(
+            result,
+            Some(PathBuf::from("/Users/testuser/Library/Application Support"))
+        );
+
+        restore_var("HOME", original);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", feature = "favor-xdg-style"))]
+    fn macos_data_dir_uses_xdg_style() {
+        let original = env::var("HOME").ok();
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { set_var("HOME", "/Users/testuser") };
+
+        let result = data_dir();
+        assert_eq!(result, Some(PathBuf::from("/Users/testuser/.local/share")));
+
+        restore_var("HOME", original);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "windows", not(feature = "windows-known-folders")))]
+    fn windows_data_dir_uses_localappdata() {
+        let original = env::var("LOCALAPPDATA").ok();
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { set_var("LOCALAPPDATA", "C:\\Users\\runneradmin\\AppData\\Local") };
+
+        let result = data_dir();
+        assert_eq!(
+            result,
+            Some(PathBuf::from("C:\\Users\\runneradmin\\AppData\\Local"))
+        );
+
+        restore_var("LOCALAPPDATA", original);
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_data_dir_and_roaming_data_dir_differ() {
+        let env = MapEnv::new()
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming")
+            .with("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local");
+
+        assert_eq!(
+            data_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Local"))
+        );
+        assert_eq!(
+            roaming_data_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Roaming"))
+        );
+    }
+
+    #[test]
+    fn data_local_dir_matches_data_dir() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming")
+            .with("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local");
+        assert_eq!(data_local_dir_with(&env), data_dir_with(&env));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn roaming_data_dir_matches_data_dir() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(roaming_data_dir_with(&env), data_dir_with(&env));
+    }
+
+    #[test]
+    fn data_dir_path_is_absolute() {
+        let result = data_dir();
+        if let Some(path) = result {
+            assert!(
+                path.is_absolute(),
+                "data_dir should return an absolute path"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn cache_dir_returns_some() {
+        let result = cache_dir();
+        assert!(
+            result.is_some(),
+            "cache_dir should return Some on supported platforms"
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_cache_dir_uses_xdg_cache_home_when_set() {
+        let env = MapEnv::new().with("XDG_CACHE_HOME", "/custom/cache");
+
+        let result = cache_dir_with(&env);
+        assert_eq!(result, Some(PathBuf::from("/custom/cache")));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_cache_dir_falls_back_to_home_when_xdg_unset() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+
+        let result = cache_dir_with(&env);
+        assert_eq!(result, Some(PathBuf::from("/home/testuser/.cache")));
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
+    fn macos_cache_dir_uses_library_caches() {
+        let original = env::var("HOME").ok();
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { set_var("HOME", "/Users/testuser") };
+
+        let result = cache_dir();
+        assert_eq!(
+            result,
+            Some(PathBuf::from("/Users/testuser/Library/Caches"))
+        );
+
+        restore_var("HOME", original);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", feature = "favor-xdg-style"))]
+    fn macos_cache_dir_uses_xdg_style() {
+        let original = env::var("HOME").ok();
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { set_var("HOME", "/Users/testuser") };
+
+        let result = cache_dir();
+        assert_eq!(result, Some(PathBuf::from("/Users/testuser/.cache")));
+
+        restore_var("HOME", original);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "windows", not(feature = "windows-known-folders")))]
+    fn windows_cache_dir_uses_localappdata() {
+        let original = env::var("LOCALAPPDATA").ok();
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { set_var("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local") };
+
+        let result = cache_dir();
+        assert_eq!(
+            result,
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Local"))
+        );
+
+        restore_var("LOCALAPPDATA", original);
+    }
+
+    #[test]
+    fn cache_dir_path_is_absolute() {
+        let result = cache_dir();
+        if let Some(path) = result {
+            assert!(
+                path.is_absolute(),
+                "cache_dir should return an absolute path"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_config_dir_handles_non_utf8_xdg() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let non_utf8 = OsStr::from_bytes(b"/tmp/\xff\xfe");
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        let _env = unsafe { EnvGuard::new().set("XDG_CONFIG_HOME", non_utf8) };
+
+        let result = config_dir();
+        assert_eq!(result, Some(PathBuf::from(non_utf8)));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_data_dir_handles_non_utf8_xdg() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let original = env::var_os("XDG_DATA_HOME");
+        let non_utf8 = OsStr::from_bytes(b"/tmp/\xff\xfe/data");
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { env::set_var("XDG_DATA_HOME", non_utf8) };
+
+        let result = data_dir();
+        assert_eq!(result, Some(PathBuf::from(non_utf8)));
+
+        restore_var_os("XDG_DATA_HOME", original);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_cache_dir_handles_non_utf8_xdg() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let original = env::var_os("XDG_CACHE_HOME");
+        let non_utf8 = OsStr::from_bytes(b"/tmp/\xff\xfe/cache");
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { env::set_var("XDG_CACHE_HOME", non_utf8) };
+
+        let result = cache_dir();
+        assert_eq!(result, Some(PathBuf::from(non_utf8)));
+
+        restore_var_os("XDG_CACHE_HOME", original);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
+    fn macos_config_dir_handles_non_utf8_home() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let original = env::var_os("HOME");
+        let non_utf8_home = OsStr::from_bytes(b"/Users/\xff\xfe");
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { env::set_var("HOME", non_utf8_home) };
+
+        let result = config_dir();
+        let mut expected = PathBuf::from(non_utf8_home);
+        expected.push("Library");
+        expected.push("Application Support");
+        assert_eq!(result, Some(expected));
+
+        restore_var_os("HOME", original);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", feature = "macos-split"))]
+    fn macos_split_data_dir_differs_from_config_dir() {
+        let env = MapEnv::new().with("HOME", "/Users/testuser");
+        assert_eq!(
+            data_dir_styled_with(&env, DirStyle::Native),
+            Some(PathBuf::from(
+                "/Users/testuser/Library/Application Support/Data"
+            ))
+        );
+        assert_eq!(
+            config_dir_styled_with(&env, DirStyle::Native),
+            Some(PathBuf::from("/Users/testuser/Library/Application Support"))
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", not(feature = "macos-split")))]
+    fn macos_data_dir_equals_config_dir_without_split() {
+        let env = MapEnv::new().with("HOME", "/Users/testuser");
+        assert_eq!(
+            data_dir_styled_with(&env, DirStyle::Native),
+            config_dir_styled_with(&env, DirStyle::Native)
+        );
+    }
+
+    #[test]
+    #[cfg(all(
+        target_os = "macos",
+        not(feature = "favor-xdg-style"),
+        not(feature = "macos-split")
+    ))]
+    fn macos_data_dir_handles_non_utf8_home() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let original = env::var_os("HOME");
+        let non_utf8_home = OsStr::from_bytes(b"/Users/\xff\xfe");
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { env::set_var("HOME", non_utf8_home) };
+
+        let result = data_dir();
+        let mut expected = PathBuf::from(non_utf8_home);
+        expected.push("Library");
+        expected.push("Application Support");
+        assert_eq!(result, Some(expected));
+
+        restore_var_os("HOME", original);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
+    fn macos_cache_dir_handles_non_utf8_home() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let original = env::var_os("HOME");
+        let non_utf8_home = OsStr::from_bytes(b"/Users/\xff\xfe");
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        unsafe { env::set_var("HOME", non_utf8_home) };
+
+        let result = cache_dir();
+        let mut expected = PathBuf::from(non_utf8_home);
+        expected.push("Library");
+        expected.push("Caches");
+        assert_eq!(result, Some(expected));
+
+        restore_var_os("HOME", original);
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn state_dir_returns_some() {
+        let result = state_dir();
+        assert!(
+            result.is_some(),
+            "state_dir should return Some on supported platforms"
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_state_dir_uses_xdg_state_home_when_set() {
+        let result = run_in_child(&[("XDG_STATE_HOME", "/custom/state")], "state_dir");
+        assert_eq!(result, Some(PathBuf::from("/custom/state")));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_state_dir_falls_back_to_home_when_xdg_unset() {
+        let result = run_in_child(&[("HOME", "/home/testuser")], "state_dir");
+        assert_eq!(result, Some(PathBuf::from("/home/testuser/.local/state")));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_state_dir_ignores_empty_xdg() {
+        let result = run_in_child(
+            &[("XDG_STATE_HOME", ""), ("HOME", "/home/testuser")],
+            "state_dir",
+        );
+        assert_eq!(result, Some(PathBuf::from("/home/testuser/.local/state")));
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
+    fn macos_state_dir_uses_library_application_support() {
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        let _env = unsafe { EnvGuard::new().set("HOME", "/Users/testuser") };
+
+        let result = state_dir();
+        assert_eq!(
+            result,
+            Some(PathBuf::from("/Users/testuser/Library/Application Support"))
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", feature = "favor-xdg-style"))]
+    fn macos_state_dir_uses_xdg_style() {
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        let _env = unsafe { EnvGuard::new().set("HOME", "/Users/testuser") };
+
+        let result = state_dir();
+        assert_eq!(result, Some(PathBuf::from("/Users/testuser/.local/state")));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_log_dir_uses_state_dir() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_STATE_HOME", "/custom/state");
+        assert_eq!(log_dir_with(&env), Some(PathBuf::from("/custom/state")));
+
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(
+            log_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.local/state"))
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
+    fn macos_log_dir_uses_library_logs() {
+        let env = MapEnv::new().with("HOME", "/Users/testuser");
+        assert_eq!(
+            log_dir_with(&env),
+            Some(PathBuf::from("/Users/testuser/Library/Logs"))
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", feature = "favor-xdg-style"))]
+    fn macos_log_dir_uses_xdg_style() {
+        let env = MapEnv::new().with("HOME", "/Users/testuser");
+        assert_eq!(
+            log_dir_with(&env),
+            Some(PathBuf::from("/Users/testuser/.local/state"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_log_dir_uses_localappdata() {
+        let env = MapEnv::new().with("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local");
+        assert_eq!(
+            log_dir_with(&env),
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Local"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_font_dir_uses_data_dir() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_DATA_HOME", "/custom/data");
+        assert_eq!(
+            font_dir_with(&env),
+            Some(PathBuf::from("/custom/data/fonts"))
+        );
+
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(
+            font_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.local/share/fonts"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_font_dir_accepts_legacy_fonts() {
+        let home = tempfile::tempdir().unwrap();
+        let env = MapEnv::new().with("HOME", home.path());
+
+        std::fs::create_dir(home.path().join(".fonts")).unwrap();
+        assert_eq!(font_dir_with(&env), Some(home.path().join(".fonts")));
+
+        let fonts = home.path().join(".local/share/fonts");
+        std::fs::create_dir_all(&fonts).unwrap();
+        assert_eq!(font_dir_with(&env), Some(fonts));
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_font_dir_uses_library_fonts() {
+        let env = MapEnv::new().with("HOME", "/Users/testuser");
+        assert_eq!(
+            font_dir_with(&env),
+            Some(PathBuf::from("/Users/testuser/Library/Fonts"))
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "windows", not(feature = "windows-known-folders")))]
+    fn windows_state_dir_uses_localappdata() {
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        let _env =
+            unsafe { EnvGuard::new().set("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local") };
+
+        let result = state_dir();
+        assert_eq!(
+            result,
+            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Local"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_runtime_dir_uses_xdg_runtime_dir_when_set() {
+        let result = run_in_child(&[("XDG_RUNTIME_DIR", "/run/user/1001")], "runtime_dir");
+        assert_eq!(result, Some(PathBuf::from("/run/user/1001")));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_runtime_dir_is_none_when_xdg_unset() {
+        assert_eq!(run_in_child(&[], "runtime_dir"), None);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_runtime_dir_ignores_empty_xdg() {
+        assert_eq!(
+            run_in_child(&[("XDG_RUNTIME_DIR", "")], "runtime_dir"),
+            None
+        );
+    }
 
-    unsafe fn set_var(key: &str, value: &str) {
-        unsafe { env::set_var(key, value) };
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_documents_portal_dir_is_in_runtime_dir() {
+        let env = MapEnv::new().with("XDG_RUNTIME_DIR", "/run/user/1001");
+        assert_eq!(
+            documents_portal_dir_with(&env),
+            Some(PathBuf::from("/run/user/1001/doc"))
+        );
+        assert_eq!(documents_portal_dir_with(&MapEnv::new()), None);
     }
 
-    unsafe fn remove_var(key: &str) {
-        unsafe { env::remove_var(key) };
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_autostart_dir_is_in_config_dir() {
+        let env = MapEnv::new().with("XDG_CONFIG_HOME", "/etc/alice");
+        assert_eq!(
+            autostart_dir_with(&env),
+            Some(PathBuf::from("/etc/alice/autostart"))
+        );
+        let env = MapEnv::new().with("HOME", "/home/alice");
+        assert_eq!(
+            autostart_dir_with(&env),
+            Some(PathBuf::from("/home/alice/.config/autostart"))
+        );
     }
 
-    fn restore_var(key: &str, original: Option<String>) {
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe {
-            match original {
-                Some(val) => set_var(key, &val),
-                None => remove_var(key),
-            }
-        }
+    #[test]
+    #[cfg(all(target_os = "linux", feature = "respect-snap"))]
+    fn linux_autostart_dir_ignores_snap_confinement() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/alice")
+            .with("SNAP", "/snap/app/1")
+            .with("SNAP_USER_DATA", "/home/alice/snap/app/1");
+        assert_eq!(
+            autostart_dir_with(&env),
+            Some(PathBuf::from("/home/alice/.config/autostart"))
+        );
     }
 
-    #[cfg(any(target_os = "linux", target_os = "macos"))]
-    fn restore_var_os(key: &str, original: Option<std::ffi::OsString>) {
+    #[test]
+    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
+    fn runtime_dir_is_none_without_xdg_style() {
+        let original = env::var("XDG_RUNTIME_DIR").ok();
         // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe {
-            match original {
-                Some(val) => env::set_var(key, val),
-                None => env::remove_var(key),
-            }
-        }
+        unsafe { set_var("XDG_RUNTIME_DIR", "/run/user/1001") };
+
+        let result = runtime_dir();
+        assert_eq!(result, None);
+
+        restore_var("XDG_RUNTIME_DIR", original);
     }
 
     #[test]
-    fn config_dir_returns_some() {
-        let result = config_dir();
+    #[cfg(all(target_os = "windows", not(feature = "favor-xdg-style")))]
+    fn windows_runtime_dir_is_session_folder_in_temp() {
+        let dir = runtime_dir().unwrap();
+        assert!(dir.starts_with(temp_dir()), "{dir:?}");
+        assert_eq!(dir.parent(), Some(temp_dir().as_path()));
         assert!(
-            result.is_some(),
-            "config_dir should return Some on supported platforms"
+            dir.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("session-")),
+            "{dir:?}"
         );
     }
 
     #[test]
-    #[cfg(target_os = "linux")]
-    fn linux_uses_xdg_config_home_when_set() {
-        let original = env::var("XDG_CONFIG_HOME").ok();
+    #[cfg(all(
+        any(target_os = "macos", target_os = "windows"),
+        feature = "favor-xdg-style"
+    ))]
+    fn runtime_dir_falls_back_to_cache_dir_with_xdg_style() {
+        let original = env::var("XDG_RUNTIME_DIR").ok();
         // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { set_var("XDG_CONFIG_HOME", "/custom/config") };
+        unsafe { remove_var("XDG_RUNTIME_DIR") };
 
-        let result = config_dir();
-        assert_eq!(result, Some(PathBuf::from("/custom/config/.config")));
+        let result = runtime_dir();
+        assert_eq!(result, cache_dir());
 
-        restore_var("XDG_CONFIG_HOME", original);
+        restore_var("XDG_RUNTIME_DIR", original);
     }
 
     #[test]
-    #[cfg(target_os = "linux")]
-    fn linux_falls_back_to_home_when_xdg_unset() {
-        let original_xdg = env::var("XDG_CONFIG_HOME").ok();
-        let original_home = env::var("HOME").ok();
-
+    #[cfg(feature = "global-prefix-env")]
+    fn global_prefix_env_roots_all_dirs() {
+        let original = env::var("DIRS_LITE_PREFIX").ok();
         // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe {
-            remove_var("XDG_CONFIG_HOME");
-            set_var("HOME", "/home/testuser");
-        }
+        unsafe { set_var("DIRS_LITE_PREFIX", "/tmp/dirs-lite-prefix") };
 
-        let result = config_dir();
+        assert_eq!(
+            config_dir(),
+            Some(PathBuf::from("/tmp/dirs-lite-prefix/config"))
+        );
+        assert_eq!(
+            data_dir(),
+            Some(PathBuf::from("/tmp/dirs-lite-prefix/data"))
+        );
+        assert_eq!(
+            cache_dir(),
+            Some(PathBuf::from("/tmp/dirs-lite-prefix/cache"))
+        );
+
+        restore_var("DIRS_LITE_PREFIX", original);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_config_dir_ignores_empty_xdg() {
+        let result = run_in_child(
+            &[("XDG_CONFIG_HOME", ""), ("HOME", "/home/testuser")],
+            "config_dir",
+        );
         assert_eq!(result, Some(PathBuf::from("/home/testuser/.config")));
+    }
 
-        restore_var("XDG_CONFIG_HOME", original_xdg);
-        restore_var("HOME", original_home);
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_data_dir_ignores_empty_xdg() {
+        let result = run_in_child(
+            &[("XDG_DATA_HOME", ""), ("HOME", "/home/testuser")],
+            "data_dir",
+        );
+        assert_eq!(result, Some(PathBuf::from("/home/testuser/.local/share")));
     }
 
     #[test]
-    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
-    fn macos_config_dir_uses_library_application_support() {
-        let original = env::var("HOME").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { set_var("HOME", "/Users/testuser") };
+    #[cfg(target_os = "linux")]
+    fn linux_cache_dir_ignores_empty_xdg() {
+        let result = run_in_child(
+            &[("XDG_CACHE_HOME", ""), ("HOME", "/home/testuser")],
+            "cache_dir",
+        );
+        assert_eq!(result, Some(PathBuf::from("/home/testuser/.cache")));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_ignores_relative_xdg() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CONFIG_HOME", "relative/config")
+            .with("XDG_DATA_HOME", "relative/data")
+            .with("XDG_CACHE_HOME", "relative/cache")
+            .with("XDG_STATE_HOME", "relative/state")
+            .with("XDG_RUNTIME_DIR", "relative/runtime");
 
-        let result = config_dir();
         assert_eq!(
-            result,
-            Some(PathBuf::from("/Users/testuser/Library/Application Support"))
+            config_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.config"))
+        );
+        assert_eq!(
+            data_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.local/share"))
         );
+        assert_eq!(
+            cache_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.cache"))
+        );
+        assert_eq!(
+            state_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.local/state"))
+        );
+        assert_eq!(
+            runtime_dir_result_with(&env),
+            Err(DirError::RelativeEnvVar {
+                key: "XDG_RUNTIME_DIR"
+            })
+        );
+    }
 
-        restore_var("HOME", original);
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_expands_tilde_in_xdg() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CONFIG_HOME", "~/cfg")
+            .with("XDG_DATA_HOME", "~");
+
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/cfg"))
+        );
+        assert_eq!(data_dir_with(&env), Some(PathBuf::from("/home/testuser")));
     }
 
     #[test]
-    #[cfg(all(target_os = "macos", feature = "favor-xdg-style"))]
-    fn macos_config_dir_uses_xdg_style() {
-        let original = env::var("HOME").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { set_var("HOME", "/Users/testuser") };
+    #[cfg(target_os = "linux")]
+    fn linux_expands_tilde_user_in_xdg() {
+        struct PasswdEnv(MapEnv);
+        impl Env for PasswdEnv {
+            fn var_os(&self, key: &str) -> Option<std::ffi::OsString> {
+                self.0.var_os(key)
+            }
+            fn user_home(&self, name: &str) -> Option<PathBuf> {
+                (name == "bob").then(|| PathBuf::from("/home/bob"))
+            }
+        }
+        let env = PasswdEnv(
+            MapEnv::new()
+                .with("HOME", "/home/testuser")
+                .with("XDG_CONFIG_HOME", "~bob/cfg")
+                .with("XDG_CACHE_HOME", "~nobody/cache"),
+        );
 
-        let result = config_dir();
-        assert_eq!(result, Some(PathBuf::from("/Users/testuser/.config")));
+        assert_eq!(config_dir_with(&env), Some(PathBuf::from("/home/bob/cfg")));
+        assert_eq!(
+            cache_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.cache"))
+        );
+    }
 
-        restore_var("HOME", original);
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_host_xdg_vars_win_with_container_host() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CONFIG_HOME", "/container/config")
+            .with("HOST_XDG_CONFIG_HOME", "/host/config")
+            .with("XDG_DATA_HOME", "/container/data")
+            .with("HOST_XDG_DATA_HOME", "/host/data")
+            .with("HOST_XDG_CACHE_HOME", "relative/cache");
+
+        let expected = |host: &str, container: &str| {
+            PathBuf::from(if cfg!(feature = "container-host") {
+                host
+            } else {
+                container
+            })
+        };
+        assert_eq!(
+            config_dir_with(&env),
+            Some(expected("/host/config", "/container/config"))
+        );
+        assert_eq!(
+            data_dir_with(&env),
+            Some(expected("/host/data", "/container/data"))
+        );
+        assert_eq!(
+            cache_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.cache"))
+        );
     }
 
     #[test]
-    #[cfg(target_os = "windows")]
-    fn windows_uses_appdata() {
-        let original = env::var("APPDATA").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { set_var("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming") };
+    #[cfg(target_os = "linux")]
+    fn linux_strips_trailing_slash_from_xdg() {
+        let env = MapEnv::new()
+            .with("XDG_CONFIG_HOME", "/custom/config/")
+            .with("XDG_DATA_HOME", "/custom/data//")
+            .with("XDG_CONFIG_DIRS", "/etc/xdg/:/opt/xdg//");
 
-        let result = config_dir();
+        let config = config_dir_with(&env).unwrap();
+        assert_eq!(config.as_os_str(), "/custom/config");
+        let data = data_dir_with(&env).unwrap();
+        assert_eq!(data.as_os_str(), "/custom/data");
+        let system = system_config_dirs_with(&env);
         assert_eq!(
-            result,
-            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Roaming"))
+            system.iter().map(|dir| dir.as_os_str()).collect::<Vec<_>>(),
+            ["/etc/xdg", "/opt/xdg"]
         );
+    }
 
-        restore_var("APPDATA", original);
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_trailing_slash_does_not_leak_into_joined_paths() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let env = MapEnv::new()
+            .with("XDG_CACHE_HOME", "/custom/cache/")
+            .with("XDG_STATE_HOME", "/")
+            .with("XDG_DATA_HOME", OsStr::from_bytes(b"/tmp/\xff\xfe/"));
+
+        let cache = cache_dir_with(&env).unwrap();
+        assert_eq!(cache.as_os_str(), "/custom/cache");
+        assert_eq!(cache.join("foo").as_os_str(), "/custom/cache/foo");
+        let state = state_dir_with(&env).unwrap();
+        assert_eq!(state.as_os_str(), "/");
+        assert_eq!(state.join("foo").as_os_str(), "/foo");
+        let data = data_dir_with(&env).unwrap();
+        assert_eq!(data.as_os_str(), OsStr::from_bytes(b"/tmp/\xff\xfe"));
+        assert_eq!(
+            data.join("foo").as_os_str(),
+            OsStr::from_bytes(b"/tmp/\xff\xfe/foo")
+        );
     }
 
     #[test]
-    fn config_dir_path_is_absolute() {
-        let result = config_dir();
-        if let Some(path) = result {
-            assert!(
-                path.is_absolute(),
-                "config_dir should return an absolute path"
-            );
-        }
+    #[cfg(target_os = "linux")]
+    fn linux_warns_when_xdg_config_home_is_home() {
+        use std::sync::Mutex;
+
+        static WARNINGS: Mutex<Vec<Warning>> = Mutex::new(Vec::new());
+        set_warning_handler(|warning| WARNINGS.lock().unwrap().push(warning.clone()));
+
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CONFIG_HOME", "/home/testuser");
+        let result = config_dir_with(&env);
+        clear_warning_handler();
+
+        assert_eq!(result, Some(PathBuf::from("/home/testuser")));
+        assert_eq!(
+            *WARNINGS.lock().unwrap(),
+            [Warning::BaseDirIsHome {
+                key: "XDG_CONFIG_HOME",
+                path: PathBuf::from("/home/testuser"),
+            }]
+        );
     }
 
     #[test]
-    fn data_dir_returns_some() {
-        let result = data_dir();
-        assert!(
-            result.is_some(),
-            "data_dir should return Some on supported platforms"
+    fn deprecated_var_is_reported_once_while_resolving_config_dir() {
+        use std::sync::Mutex;
+
+        static WARNINGS: Mutex<Vec<Warning>> = Mutex::new(Vec::new());
+        deprecate_var("MYAPP_CONFIG", "MYAPP_CONFIG_DIR");
+        set_warning_handler(|warning| WARNINGS.lock().unwrap().push(warning.clone()));
+
+        config_dir_with(&MapEnv::new().with("MYAPP_CONFIG", ""));
+        config_dir_with(&MapEnv::new());
+        config_dir_with(&MapEnv::new().with("MYAPP_CONFIG", "/srv/myapp"));
+        config_dir_with(&MapEnv::new().with("MYAPP_CONFIG", "/srv/myapp"));
+        clear_deprecated_vars();
+        config_dir_with(&MapEnv::new().with("MYAPP_CONFIG", "/srv/myapp"));
+        clear_warning_handler();
+
+        let warnings = WARNINGS.lock().unwrap();
+        assert_eq!(
+            *warnings,
+            [Warning::DeprecatedVar {
+                key: "MYAPP_CONFIG",
+                replacement: "MYAPP_CONFIG_DIR",
+            }]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "MYAPP_CONFIG is deprecated, use MYAPP_CONFIG_DIR instead"
         );
     }
 
     #[test]
-    #[cfg(target_os = "linux")]
-    fn linux_data_dir_uses_xdg_data_home_when_set() {
-        let original = env::var("XDG_DATA_HOME").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { set_var("XDG_DATA_HOME", "/custom/data") };
+    #[cfg(all(target_os = "linux", feature = "checked-runtime-dir"))]
+    fn linux_runtime_dir_checked_rejects_shared_dir() {
+        use std::fs::{self, Permissions};
+        use std::os::unix::fs::PermissionsExt;
+        use std::sync::Mutex;
 
-        let result = data_dir();
-        assert_eq!(result, Some(PathBuf::from("/custom/data")));
+        static WARNINGS: Mutex<Vec<Warning>> = Mutex::new(Vec::new());
+
+        let dir = tempfile::tempdir().unwrap();
+        let env = MapEnv::new().with("XDG_RUNTIME_DIR", dir.path());
+        fs::set_permissions(dir.path(), Permissions::from_mode(0o700)).unwrap();
+        assert_eq!(
+            runtime_dir_checked_with(&env),
+            Some(dir.path().to_path_buf())
+        );
 
-        restore_var("XDG_DATA_HOME", original);
+        fs::set_permissions(dir.path(), Permissions::from_mode(0o755)).unwrap();
+        set_warning_handler(|warning| WARNINGS.lock().unwrap().push(warning.clone()));
+        let result = runtime_dir_checked_with(&env);
+        clear_warning_handler();
+
+        assert_eq!(result, None);
+        assert_eq!(runtime_dir_with(&env), Some(dir.path().to_path_buf()));
+        assert_eq!(
+            *WARNINGS.lock().unwrap(),
+            [Warning::InsecureRuntimeDir {
+                path: dir.path().to_path_buf(),
+            }]
+        );
     }
 
     #[test]
     #[cfg(target_os = "linux")]
-    fn linux_data_dir_falls_back_to_home_when_xdg_unset() {
-        let original_xdg = env::var("XDG_DATA_HOME").ok();
-        let original_home = env::var("HOME").ok();
+    fn linux_executable_dir_uses_xdg_bin_home_when_set() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_BIN_HOME", "/custom/bin");
+        assert_eq!(
+            executable_dir_with(&env),
+            Some(PathBuf::from("/custom/bin"))
+        );
+    }
 
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe {
-            remove_var("XDG_DATA_HOME");
-            set_var("HOME", "/home/testuser");
-        }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_executable_dir_falls_back_to_home_when_xdg_unset() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(
+            executable_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.local/bin"))
+        );
+    }
 
-        let result = data_dir();
-        assert_eq!(result, Some(PathBuf::from("/home/testuser/.local/share")));
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_executable_dir_falls_back_to_home_when_xdg_empty() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_BIN_HOME", "");
+        assert_eq!(
+            executable_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.local/bin"))
+        );
+    }
 
-        restore_var("XDG_DATA_HOME", original_xdg);
-        restore_var("HOME", original_home);
+    #[test]
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    fn executable_dir_is_none_outside_xdg() {
+        let env = MapEnv::new()
+            .with("HOME", "/Users/testuser")
+            .with("XDG_BIN_HOME", "/custom/bin");
+        assert_eq!(executable_dir_with(&env), None);
     }
 
     #[test]
-    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
-    fn macos_data_dir_uses_library_application_support() {
-        let original = env::var("HOME").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { set_var("HOME", "/Users/testuser") };
+    #[cfg(target_os = "linux")]
+    fn linux_flatpak_xdg_overrides_are_honored() {
+        let env = MapEnv::new()
+            .with("FLATPAK_ID", "org.example.App")
+            .with("HOME", "/home/alice")
+            .with(
+                "XDG_CONFIG_HOME",
+                "/home/alice/.var/app/org.example.App/config",
+            )
+            .with("XDG_DATA_HOME", "/home/alice/.var/app/org.example.App/data")
+            .with(
+                "XDG_CACHE_HOME",
+                "/home/alice/.var/app/org.example.App/cache",
+            )
+            .with("XDG_STATE_HOME", "");
 
-        let result = data_dir();
+        assert!(is_sandboxed_with(&env));
         assert_eq!(
-            result,
-            Some(PathBuf::from("/Users/testuser/Library/Application Support"))
+            config_dir_with(&env),
+            Some(PathBuf::from("/home/alice/.var/app/org.example.App/config"))
+        );
+        assert_eq!(
+            data_dir_with(&env),
+            Some(PathBuf::from("/home/alice/.var/app/org.example.App/data"))
+        );
+        assert_eq!(
+            cache_dir_with(&env),
+            Some(PathBuf::from("/home/alice/.var/app/org.example.App/cache"))
         );
+        assert_eq!(
+            state_dir_with(&env),
+            Some(PathBuf::from("/home/alice/.local/state"))
+        );
+    }
 
-        restore_var("HOME", original);
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_flatpak_xdg_overrides_survive_remapped_home() {
+        let env = MapEnv::new()
+            .with("FLATPAK_ID", "org.example.App")
+            .with("HOME", "")
+            .with(
+                "XDG_CONFIG_HOME",
+                "/var/home/alice/.var/app/org.example.App/config",
+            );
+
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from(
+                "/var/home/alice/.var/app/org.example.App/config"
+            ))
+        );
     }
 
     #[test]
-    #[cfg(all(target_os = "macos", feature = "favor-xdg-style"))]
-    fn macos_data_dir_uses_xdg_style() {
-        let original = env::var("HOME").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { set_var("HOME", "/Users/testuser") };
+    fn is_sandboxed_checks_markers() {
+        assert!(!is_sandboxed_with(&MapEnv::new()));
+        assert!(!is_sandboxed_with(&MapEnv::new().with("FLATPAK_ID", "")));
+        assert!(is_sandboxed_with(
+            &MapEnv::new().with("container", "podman")
+        ));
+    }
 
-        let result = data_dir();
-        assert_eq!(result, Some(PathBuf::from("/Users/testuser/.local/share")));
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_detects_headless_session() {
+        let env = MapEnv::new().with("HOME", "/home/ci");
+        assert!(is_headless_with(&env));
+        assert!(is_headless_with(&env.clone().with("DISPLAY", "")));
+        assert!(is_headless_with(
+            &env.clone().with("XDG_SESSION_TYPE", "tty")
+        ));
+        assert!(!is_headless_with(&env.clone().with("DISPLAY", ":0")));
+        assert!(!is_headless_with(
+            &env.clone().with("WAYLAND_DISPLAY", "wayland-0")
+        ));
+        assert!(!is_headless_with(
+            &env.clone().with("XDG_SESSION_TYPE", "wayland")
+        ));
 
-        restore_var("HOME", original);
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from("/home/ci/.config"))
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", feature = "respect-snap"))]
+    fn linux_snap_redirects_to_snap_user_dirs() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/alice/snap/app/42")
+            .with("XDG_CONFIG_HOME", "/home/alice/.config")
+            .with("SNAP", "/snap/app/42")
+            .with("SNAP_USER_DATA", "/home/alice/snap/app/42")
+            .with("SNAP_USER_COMMON", "/home/alice/snap/app/common");
+
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from("/home/alice/snap/app/42/.config"))
+        );
+        assert_eq!(
+            data_dir_with(&env),
+            Some(PathBuf::from("/home/alice/snap/app/42/.local/share"))
+        );
+        assert_eq!(
+            cache_dir_with(&env),
+            Some(PathBuf::from("/home/alice/snap/app/common/.cache"))
+        );
     }
 
     #[test]
-    #[cfg(target_os = "windows")]
-    fn windows_data_dir_uses_localappdata() {
-        let original = env::var("LOCALAPPDATA").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { set_var("LOCALAPPDATA", "C:\\Users\\runneradmin\\AppData\\Local") };
+    #[cfg(all(target_os = "linux", feature = "appimage"))]
+    fn linux_appimage_roots_dirs_beside_the_appimage() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/alice")
+            .with("XDG_CONFIG_HOME", "/home/alice/.config")
+            .with("APPIMAGE", "/media/usb/MyApp-x86_64.AppImage")
+            .with("OWD", "/home/alice/Downloads");
 
-        let result = data_dir();
         assert_eq!(
-            result,
-            Some(PathBuf::from("C:\\Users\\runneradmin\\AppData\\Local"))
+            config_dir_with(&env),
+            Some(PathBuf::from("/media/usb/.MyApp-x86_64/config"))
+        );
+        assert_eq!(
+            data_dir_with(&env),
+            Some(PathBuf::from("/media/usb/.MyApp-x86_64/data"))
+        );
+        assert_eq!(
+            cache_dir_with(&env),
+            Some(PathBuf::from("/media/usb/.MyApp-x86_64/cache"))
         );
-
-        restore_var("LOCALAPPDATA", original);
     }
 
     #[test]
-    fn data_dir_path_is_absolute() {
-        let result = data_dir();
-        if let Some(path) = result {
-            assert!(
-                path.is_absolute(),
-                "data_dir should return an absolute path"
-            );
-        }
+    #[cfg(target_os = "linux")]
+    fn linux_appimage_requires_absolute_path_and_feature() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/alice")
+            .with("APPIMAGE", "MyApp.AppImage");
+        assert_eq!(
+            data_dir_with(&env),
+            Some(PathBuf::from("/home/alice/.local/share"))
+        );
+
+        let env = env.with("APPIMAGE", "/opt/MyApp.AppImage");
+        let expected = if cfg!(feature = "appimage") {
+            "/opt/.MyApp/data"
+        } else {
+            "/home/alice/.local/share"
+        };
+        assert_eq!(data_dir_with(&env), Some(PathBuf::from(expected)));
     }
 
     #[test]
-    fn cache_dir_returns_some() {
-        let result = cache_dir();
-        assert!(
-            result.is_some(),
-            "cache_dir should return Some on supported platforms"
+    #[cfg(target_os = "linux")]
+    fn linux_snap_requires_snap_marker_and_feature() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/alice")
+            .with("SNAP_USER_DATA", "/home/alice/snap/app/42");
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from("/home/alice/.config"))
         );
+
+        let env = env.with("SNAP", "/snap/app/42");
+        let expected = if cfg!(feature = "respect-snap") {
+            "/home/alice/snap/app/42/.config"
+        } else {
+            "/home/alice/.config"
+        };
+        assert_eq!(config_dir_with(&env), Some(PathBuf::from(expected)));
     }
 
     #[test]
     #[cfg(target_os = "linux")]
-    fn linux_cache_dir_uses_xdg_cache_home_when_set() {
-        let original = env::var("XDG_CACHE_HOME").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { set_var("XDG_CACHE_HOME", "/custom/cache") };
+    fn linux_exists_probes_resolved_dirs() {
+        let home = env::temp_dir().join(format!("dirs-lite-exists-{}", std::process::id()));
+        std::fs::create_dir_all(home.join(".config")).unwrap();
+        let env = MapEnv::new().with("HOME", &home);
 
-        let result = cache_dir();
-        assert_eq!(result, Some(PathBuf::from("/custom/cache")));
+        let config_exists = config_dir_exists_with(&env);
+        let cache_exists = cache_dir_exists_with(&env);
+        std::fs::remove_dir_all(&home).unwrap();
 
-        restore_var("XDG_CACHE_HOME", original);
+        assert!(config_exists);
+        assert!(!cache_exists);
+        assert!(!data_dir_exists_with(&MapEnv::new()));
     }
 
     #[test]
     #[cfg(target_os = "linux")]
-    fn linux_cache_dir_falls_back_to_home_when_xdg_unset() {
-        let original_xdg = env::var("XDG_CACHE_HOME").ok();
-        let original_home = env::var("HOME").ok();
+    fn linux_executable_dir_on_path_checks_entries() {
+        let home = tempfile::tempdir().unwrap();
+        let bin = home.path().join(".local/bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        let env = |path: String| MapEnv::new().with("HOME", home.path()).with("PATH", path);
 
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe {
-            remove_var("XDG_CACHE_HOME");
-            set_var("HOME", "/home/testuser");
-        }
+        let path = format!("/usr/bin:{}/:/bin", bin.display());
+        assert_eq!(executable_dir_on_path_with(&env(path)), Some(true));
+        let path = "/usr/local/bin:/usr/bin:/bin".to_string();
+        assert_eq!(executable_dir_on_path_with(&env(path)), Some(false));
+        let env = MapEnv::new().with("HOME", home.path());
+        assert_eq!(executable_dir_on_path_with(&env), Some(false));
+    }
 
-        let result = cache_dir();
-        assert_eq!(result, Some(PathBuf::from("/home/testuser/.cache")));
+    #[test]
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    fn executable_dir_on_path_is_none_without_executable_dir() {
+        let env = MapEnv::new()
+            .with("HOME", "/Users/testuser")
+            .with("PATH", "/Users/testuser/.local/bin");
+        assert_eq!(executable_dir_on_path_with(&env), None);
+    }
 
-        restore_var("XDG_CACHE_HOME", original_xdg);
-        restore_var("HOME", original_home);
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    fn ci_targets_are_supported_platforms() {
+        const { assert!(is_supported_platform()) };
+        assert!(is_supported_platform());
     }
 
     #[test]
-    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
-    fn macos_cache_dir_uses_library_caches() {
-        let original = env::var("HOME").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { set_var("HOME", "/Users/testuser") };
+    #[cfg(not(dirs_lite_custom_xdg_config_dirs))]
+    fn default_xdg_config_dirs_is_etc_xdg() {
+        assert_eq!(DEFAULT_XDG_CONFIG_DIRS, "/etc/xdg");
+    }
 
-        let result = cache_dir();
+    #[test]
+    #[cfg(all(target_os = "linux", not(dirs_lite_custom_xdg_config_dirs)))]
+    fn linux_system_config_dirs_default_to_etc_xdg() {
+        let env = MapEnv::new();
+        assert_eq!(system_config_dirs_with(&env), [PathBuf::from("/etc/xdg")]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_system_config_dirs_split_xdg_config_dirs() {
+        let env = MapEnv::new().with("XDG_CONFIG_DIRS", "/etc/xdg/custom:/opt/xdg");
         assert_eq!(
-            result,
-            Some(PathBuf::from("/Users/testuser/Library/Caches"))
+            system_config_dirs_with(&env),
+            [PathBuf::from("/etc/xdg/custom"), PathBuf::from("/opt/xdg")]
         );
-
-        restore_var("HOME", original);
     }
 
     #[test]
-    #[cfg(all(target_os = "macos", feature = "favor-xdg-style"))]
-    fn macos_cache_dir_uses_xdg_style() {
-        let original = env::var("HOME").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { set_var("HOME", "/Users/testuser") };
+    #[cfg(target_os = "linux")]
+    fn linux_system_config_dirs_skip_empty_entries() {
+        let env = MapEnv::new().with("XDG_CONFIG_DIRS", ":/etc/xdg/custom::/opt/xdg:");
+        assert_eq!(
+            system_config_dirs_with(&env),
+            [PathBuf::from("/etc/xdg/custom"), PathBuf::from("/opt/xdg")]
+        );
 
-        let result = cache_dir();
-        assert_eq!(result, Some(PathBuf::from("/Users/testuser/.cache")));
+        let default = system_config_dirs_with(&MapEnv::new());
+        let env = MapEnv::new().with("XDG_CONFIG_DIRS", "");
+        assert_eq!(system_config_dirs_with(&env), default);
 
-        restore_var("HOME", original);
+        let env = MapEnv::new().with("XDG_CONFIG_DIRS", "::");
+        assert_eq!(system_config_dirs_with(&env), default);
     }
 
     #[test]
-    #[cfg(target_os = "windows")]
-    fn windows_cache_dir_uses_localappdata() {
-        let original = env::var("LOCALAPPDATA").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { set_var("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local") };
+    #[cfg(all(target_os = "linux", dirs_lite_custom_xdg_config_dirs))]
+    fn linux_system_config_dirs_default_to_build_time_value() {
+        let expected: Vec<PathBuf> =
+            default_dirs::parse_default_dirs(env!("DIRS_LITE_DEFAULT_XDG_CONFIG_DIRS"))
+                .unwrap()
+                .into_iter()
+                .map(PathBuf::from)
+                .collect();
+        assert_eq!(system_config_dirs_with(&MapEnv::new()), expected);
+    }
 
-        let result = cache_dir();
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_system_data_dirs_default_to_spec() {
+        let expected = [
+            PathBuf::from("/usr/local/share"),
+            PathBuf::from("/usr/share"),
+        ];
+        assert_eq!(system_data_dirs_with(&MapEnv::new()), expected);
+
+        let env = MapEnv::new().with("XDG_DATA_DIRS", "");
+        assert_eq!(system_data_dirs_with(&env), expected);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_system_data_dirs_split_xdg_data_dirs() {
+        let env = MapEnv::new().with("XDG_DATA_DIRS", "/opt/share:/usr/share");
         assert_eq!(
-            result,
-            Some(PathBuf::from("C:\\Users\\testuser\\AppData\\Local"))
+            system_data_dirs_with(&env),
+            [PathBuf::from("/opt/share"), PathBuf::from("/usr/share")]
         );
+    }
 
-        restore_var("LOCALAPPDATA", original);
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_system_data_dirs_skip_empty_and_relative_entries() {
+        let env = MapEnv::new().with("XDG_DATA_DIRS", "::/opt/share:relative/share::/usr/share:");
+        assert_eq!(
+            system_data_dirs_with(&env),
+            [PathBuf::from("/opt/share"), PathBuf::from("/usr/share")]
+        );
     }
 
     #[test]
-    fn cache_dir_path_is_absolute() {
-        let result = cache_dir();
-        if let Some(path) = result {
-            assert!(
-                path.is_absolute(),
-                "cache_dir should return an absolute path"
-            );
-        }
+    #[cfg(not(target_os = "linux"))]
+    fn system_data_dirs_are_empty_outside_linux() {
+        assert_eq!(system_data_dirs(), Vec::<PathBuf>::new());
     }
 
     #[test]
     #[cfg(target_os = "linux")]
-    fn linux_config_dir_handles_non_utf8_xdg() {
-        use std::ffi::OsStr;
-        use std::os::unix::ffi::OsStrExt;
-
-        let original = env::var_os("XDG_CONFIG_HOME");
-        let non_utf8 = OsStr::from_bytes(b"/tmp/\xff\xfe");
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { env::set_var("XDG_CONFIG_HOME", non_utf8) };
+    fn linux_find_config_file_prefers_user_config_dir() {
+        let home = tempfile::tempdir().unwrap();
+        let system = tempfile::tempdir().unwrap();
+        for dir in [
+            home.path().join(".config/myapp"),
+            system.path().join("myapp"),
+        ] {
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("config.toml"), "").unwrap();
+        }
+        let env = MapEnv::new()
+            .with("HOME", home.path())
+            .with("XDG_CONFIG_DIRS", system.path());
 
-        let result = config_dir();
-        let mut expected = PathBuf::from(non_utf8);
-        expected.push(".config");
-        assert_eq!(result, Some(expected));
+        assert_eq!(
+            find_config_file_with(&env, "myapp/config.toml"),
+            Some(home.path().join(".config/myapp/config.toml"))
+        );
 
-        restore_var_os("XDG_CONFIG_HOME", original);
+        std::fs::remove_dir_all(home.path().join(".config")).unwrap();
+        assert_eq!(
+            find_config_file_with(&env, "myapp/config.toml"),
+            Some(system.path().join("myapp/config.toml"))
+        );
     }
 
     #[test]
     #[cfg(target_os = "linux")]
-    fn linux_data_dir_handles_non_utf8_xdg() {
-        use std::ffi::OsStr;
-        use std::os::unix::ffi::OsStrExt;
+    fn linux_find_config_file_returns_none_without_file() {
+        let home = tempfile::tempdir().unwrap();
+        let env = MapEnv::new()
+            .with("HOME", home.path())
+            .with("XDG_CONFIG_DIRS", home.path());
 
-        let original = env::var_os("XDG_DATA_HOME");
-        let non_utf8 = OsStr::from_bytes(b"/tmp/\xff\xfe/data");
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { env::set_var("XDG_DATA_HOME", non_utf8) };
+        assert_eq!(find_config_file_with(&env, "myapp/config.toml"), None);
+    }
 
-        let result = data_dir();
-        assert_eq!(result, Some(PathBuf::from(non_utf8)));
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_find_data_file_searches_system_data_dirs() {
+        let home = tempfile::tempdir().unwrap();
+        let system = tempfile::tempdir().unwrap();
+        let dir = system.path().join("myapp");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("data.db"), "").unwrap();
+        let env = MapEnv::new()
+            .with("HOME", home.path())
+            .with("XDG_DATA_DIRS", system.path());
 
-        restore_var_os("XDG_DATA_HOME", original);
+        assert_eq!(
+            find_data_file_with(&env, "myapp/data.db"),
+            Some(system.path().join("myapp/data.db"))
+        );
+        assert_eq!(find_data_file_with(&env, "myapp/missing.db"), None);
     }
 
     #[test]
-    #[cfg(target_os = "linux")]
-    fn linux_cache_dir_handles_non_utf8_xdg() {
-        use std::ffi::OsStr;
-        use std::os::unix::ffi::OsStrExt;
+    #[cfg(target_os = "windows")]
+    fn windows_system_config_dirs_use_programdata() {
+        let env = MapEnv::new().with("PROGRAMDATA", "C:\\ProgramData");
+        assert_eq!(
+            system_config_dirs_with(&env),
+            [PathBuf::from("C:\\ProgramData")]
+        );
+    }
 
-        let original = env::var_os("XDG_CACHE_HOME");
-        let non_utf8 = OsStr::from_bytes(b"/tmp/\xff\xfe/cache");
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { env::set_var("XDG_CACHE_HOME", non_utf8) };
+    #[cfg(any(
+        target_os = "freebsd",
+        target_os = "openbsd",
+        target_os = "netbsd",
+        target_os = "dragonfly"
+    ))]
+    mod bsd {
+        use super::*;
 
-        let result = cache_dir();
-        assert_eq!(result, Some(PathBuf::from(non_utf8)));
+        #[test]
+        fn bsd_uses_xdg_vars_when_set() {
+            let env = MapEnv::new()
+                .with("HOME", "/home/testuser")
+                .with("XDG_CONFIG_HOME", "/custom/config")
+                .with("XDG_DATA_HOME", "/custom/data")
+                .with("XDG_CACHE_HOME", "/custom/cache")
+                .with("XDG_STATE_HOME", "/custom/state")
+                .with("XDG_RUNTIME_DIR", "/var/run/user/1001");
 
-        restore_var_os("XDG_CACHE_HOME", original);
-    }
+            assert_eq!(config_dir_with(&env), Some(PathBuf::from("/custom/config")));
+            assert_eq!(data_dir_with(&env), Some(PathBuf::from("/custom/data")));
+            assert_eq!(cache_dir_with(&env), Some(PathBuf::from("/custom/cache")));
+            assert_eq!(state_dir_with(&env), Some(PathBuf::from("/custom/state")));
+            assert_eq!(
+                runtime_dir_with(&env),
+                Some(PathBuf::from("/var/run/user/1001"))
+            );
+        }
 
-    #[test]
-    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
-    fn macos_config_dir_handles_non_utf8_home() {
-        use std::ffi::OsStr;
-        use std::os::unix::ffi::OsStrExt;
+        #[test]
+        fn bsd_falls_back_to_home_when_xdg_unset() {
+            let env = MapEnv::new().with("HOME", "/home/testuser");
 
-        let original = env::var_os("HOME");
-        let non_utf8_home = OsStr::from_bytes(b"/Users/\xff\xfe");
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { env::set_var("HOME", non_utf8_home) };
+            assert_eq!(
+                config_dir_with(&env),
+                Some(PathBuf::from("/home/testuser/.config"))
+            );
+            assert_eq!(
+                data_dir_with(&env),
+                Some(PathBuf::from("/home/testuser/.local/share"))
+            );
+            assert_eq!(
+                cache_dir_with(&env),
+                Some(PathBuf::from("/home/testuser/.cache"))
+            );
+            assert_eq!(
+                state_dir_with(&env),
+                Some(PathBuf::from("/home/testuser/.local/state"))
+            );
+            assert_eq!(runtime_dir_with(&env), None);
+        }
 
-        let result = config_dir();
-        let mut expected = PathBuf::from(non_utf8_home);
-        expected.push("Library");
-        expected.push("Application Support");
-        assert_eq!(result, Some(expected));
+        #[test]
+        fn bsd_ignores_empty_xdg() {
+            let env = MapEnv::new()
+                .with("HOME", "/home/testuser")
+                .with("XDG_CONFIG_HOME", "");
 
-        restore_var_os("HOME", original);
+            assert_eq!(
+                config_dir_with(&env),
+                Some(PathBuf::from("/home/testuser/.config"))
+            );
+        }
     }
 
     #[test]
-    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
-    fn macos_data_dir_handles_non_utf8_home() {
-        use std::ffi::OsStr;
-        use std::os::unix::ffi::OsStrExt;
-
-        let original = env::var_os("HOME");
-        let non_utf8_home = OsStr::from_bytes(b"/Users/\xff\xfe");
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { env::set_var("HOME", non_utf8_home) };
-
-        let result = data_dir();
-        let mut expected = PathBuf::from(non_utf8_home);
-        expected.push("Library");
-        expected.push("Application Support");
-        assert_eq!(result, Some(expected));
+    #[cfg(all(target_family = "wasm", not(target_os = "wasi")))]
+    fn wasm_resolves_no_dirs() {
+        assert_eq!(home_dir(), None);
+        assert_eq!(config_dir(), None);
+        assert_eq!(data_dir(), None);
+        assert_eq!(cache_dir(), None);
+        assert_eq!(
+            home_dir_result_with(&MapEnv::new().with("HOME", "/home/testuser")),
+            Err(DirError::UnsupportedPlatform)
+        );
+    }
 
-        restore_var_os("HOME", original);
+    #[test]
+    #[cfg(all(feature = "tracing", target_os = "linux"))]
+    #[tracing_test::traced_test]
+    fn linux_traces_xdg_config_home() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CONFIG_HOME", "/custom/config");
+        assert_eq!(config_dir_with(&env), Some(PathBuf::from("/custom/config")));
+        assert!(logs_contain(
+            r#"read environment variable key="XDG_CONFIG_HOME" value=Some("/custom/config")"#
+        ));
+        assert!(logs_contain("resolved directory kind=config"));
+        assert!(logs_contain(r#"platform=Linux dir=Ok("/custom/config")"#));
     }
 
     #[test]
-    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
-    fn macos_cache_dir_handles_non_utf8_home() {
-        use std::ffi::OsStr;
-        use std::os::unix::ffi::OsStrExt;
+    #[cfg(target_os = "wasi")]
+    fn wasi_resolves_xdg_dirs() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CACHE_HOME", "/tmp/cache");
+        assert_eq!(home_dir_with(&env), Some(PathBuf::from("/home/testuser")));
+        assert_eq!(
+            config_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.config"))
+        );
+        assert_eq!(
+            data_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/.local/share"))
+        );
+        assert_eq!(cache_dir_with(&env), Some(PathBuf::from("/tmp/cache")));
+        assert_eq!(config_dir_with(&MapEnv::new()), None);
+    }
 
-        let original = env::var_os("HOME");
-        let non_utf8_home = OsStr::from_bytes(b"/Users/\xff\xfe");
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe { env::set_var("HOME", non_utf8_home) };
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    mod documented_layout {
+        use super::*;
 
-        let result = cache_dir();
-        let mut expected = PathBuf::from(non_utf8_home);
-        expected.push("Library");
-        expected.push("Caches");
-        assert_eq!(result, Some(expected));
+        /// [`MapEnv`] in the Windows session `1` of the doc examples.
+        struct DocumentedEnv(MapEnv);
 
-        restore_var_os("HOME", original);
-    }
+        impl Env for DocumentedEnv {
+            fn var_os(&self, key: &str) -> Option<std::ffi::OsString> {
+                self.0.var_os(key)
+            }
 
-    #[test]
-    #[cfg(target_os = "linux")]
-    fn linux_config_dir_ignores_empty_xdg() {
-        let original_xdg = env::var("XDG_CONFIG_HOME").ok();
-        let original_home = env::var("HOME").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe {
-            set_var("XDG_CONFIG_HOME", "");
-            set_var("HOME", "/home/testuser");
+            fn session_id(&self) -> Option<u32> {
+                Some(1)
+            }
         }
 
-        let result = config_dir();
-        assert_eq!(result, Some(PathBuf::from("/home/testuser/.config")));
-
-        restore_var("XDG_CONFIG_HOME", original_xdg);
-        restore_var("HOME", original_home);
-    }
+        /// The environment the examples in the doc tables are based on, for the current platform.
+        fn documented_env() -> DocumentedEnv {
+            let vars: &[(&str, &str)] = if cfg!(target_os = "windows") {
+                &[
+                    ("USERPROFILE", "C:\\Users\\Alice"),
+                    ("APPDATA", "C:\\Users\\Alice\\AppData\\Roaming"),
+                    ("LOCALAPPDATA", "C:\\Users\\Alice\\AppData\\Local"),
+                    ("TEMP", "C:\\Users\\Alice\\AppData\\Local\\Temp"),
+                ]
+            } else if cfg!(target_os = "macos") {
+                &[("HOME", "/Users/Alice")]
+            } else {
+                &[
+                    ("HOME", "/home/alice"),
+                    ("XDG_RUNTIME_DIR", "/run/user/1001"),
+                ]
+            };
+            DocumentedEnv(vars.iter().copied().collect())
+        }
 
-    #[test]
-    #[cfg(target_os = "linux")]
-    fn linux_data_dir_ignores_empty_xdg() {
-        let original_xdg = env::var("XDG_DATA_HOME").ok();
-        let original_home = env::var("HOME").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe {
-            set_var("XDG_DATA_HOME", "");
-            set_var("HOME", "/home/testuser");
+        /// The examples from the doc tables, for the current platform.
+        fn documented_examples() -> Vec<(DirKind, Option<&'static str>)> {
+            let xdg_style = cfg!(feature = "favor-xdg-style");
+            if cfg!(target_os = "windows") {
+                vec![
+                    (DirKind::Config, Some("C:\\Users\\Alice\\AppData\\Roaming")),
+                    (DirKind::Data, Some("C:\\Users\\Alice\\AppData\\Local")),
+                    (DirKind::Cache, Some("C:\\Users\\Alice\\AppData\\Local")),
+                    (
+                        DirKind::Runtime,
+                        Some(if xdg_style {
+                            "C:\\Users\\Alice\\AppData\\Local"
+                        } else {
+                            "C:\\Users\\Alice\\AppData\\Local\\Temp\\session-1"
+                        }),
+                    ),
+                ]
+            } else if cfg!(target_os = "macos") && xdg_style {
+                vec![
+                    (DirKind::Config, Some("/Users/Alice/.config")),
+                    (DirKind::Data, Some("/Users/Alice/.local/share")),
+                    (DirKind::Cache, Some("/Users/Alice/.cache")),
+                    (DirKind::Runtime, Some("/Users/Alice/.cache")),
+                ]
+            } else if cfg!(target_os = "macos") {
+                vec![
+                    (
+                        DirKind::Config,
+                        Some("/Users/Alice/Library/Application Support"),
+                    ),
+                    (
+                        DirKind::Data,
+                        Some(if cfg!(feature = "macos-split") {
+                            "/Users/Alice/Library/Application Support/Data"
+                        } else {
+                            "/Users/Alice/Library/Application Support"
+                        }),
+                    ),
+                    (DirKind::Cache, Some("/Users/Alice/Library/Caches")),
+                    (DirKind::Runtime, None),
+                ]
+            } else {
+                vec![
+                    (DirKind::Config, Some("/home/alice/.config")),
+                    (DirKind::Data, Some("/home/alice/.local/share")),
+                    (DirKind::Cache, Some("/home/alice/.cache")),
+                    (DirKind::Runtime, Some("/run/user/1001")),
+                ]
+            }
         }
 
-        let result = data_dir();
-        assert_eq!(result, Some(PathBuf::from("/home/testuser/.local/share")));
+        /// Asserts that every resolver returns the example documented in its doc table.
+        fn assert_documented_layout() {
+            let env = documented_env();
+            for (kind, expected) in documented_examples() {
+                assert_eq!(
+                    kind.resolve_with(&env),
+                    expected.map(PathBuf::from),
+                    "{kind:?} does not match the documented example"
+                );
+            }
+        }
 
-        restore_var("XDG_DATA_HOME", original_xdg);
-        restore_var("HOME", original_home);
+        #[test]
+        fn resolvers_match_documented_examples() {
+            assert_documented_layout();
+        }
     }
 
-    #[test]
-    #[cfg(target_os = "linux")]
-    fn linux_cache_dir_ignores_empty_xdg() {
-        let original_xdg = env::var("XDG_CACHE_HOME").ok();
-        let original_home = env::var("HOME").ok();
-        // SAFETY: Tests run single-threaded with --test-threads=1
-        unsafe {
-            set_var("XDG_CACHE_HOME", "");
-            set_var("HOME", "/home/testuser");
+    // proptest needs a source of randomness, which wasm32-unknown-unknown lacks
+    #[cfg(not(target_family = "wasm"))]
+    mod never_panics {
+        use super::*;
+        use proptest::prelude::*;
+
+        const RELEVANT_KEYS: &[&str] = &[
+            "HOME",
+            "USERPROFILE",
+            "HOMEDRIVE",
+            "HOMEPATH",
+            "XDG_CONFIG_HOME",
+            "XDG_DATA_HOME",
+            "XDG_CACHE_HOME",
+            "APPDATA",
+            "LOCALAPPDATA",
+            "XDG_STATE_HOME",
+            "XDG_RUNTIME_DIR",
+            "XDG_CONFIG_DIRS",
+            "XDG_DATA_DIRS",
+            "XDG_BIN_HOME",
+            "PROGRAMDATA",
+            "DIRS_LITE_PREFIX",
+        ];
+
+        fn arbitrary_os_string() -> impl Strategy<Value = std::ffi::OsString> {
+            #[cfg(unix)]
+            {
+                use std::os::unix::ffi::OsStringExt;
+                proptest::collection::vec(any::<u8>(), 0..64).prop_map(std::ffi::OsString::from_vec)
+            }
+            #[cfg(windows)]
+            {
+                use std::os::windows::ffi::OsStringExt;
+                proptest::collection::vec(any::<u16>(), 0..64)
+                    .prop_map(|wide| std::ffi::OsString::from_wide(&wide))
+            }
+            #[cfg(not(any(unix, windows)))]
+            {
+                any::<String>().prop_map(std::ffi::OsString::from)
+            }
         }
 
-        let result = cache_dir();
-        assert_eq!(result, Some(PathBuf::from("/home/testuser/.cache")));
+        proptest! {
+            #[test]
+            fn resolution_never_panics_for_arbitrary_env(
+                vars in proptest::collection::hash_map(
+                    proptest::sample::select(RELEVANT_KEYS).prop_map(String::from),
+                    arbitrary_os_string(),
+                    0..RELEVANT_KEYS.len(),
+                )
+            ) {
+                let env: MapEnv = vars.into_iter().collect();
+                let _ = home_dir_with(&env);
+                let _ = config_dir_with(&env);
+                let _ = data_dir_with(&env);
+                let _ = cache_dir_with(&env);
+                let _ = state_dir_with(&env);
+                let _ = runtime_dir_with(&env);
+                let _ = executable_dir_with(&env);
+                let _ = system_config_dirs_with(&env);
+                let _ = system_data_dirs_with(&env);
+            }
+        }
+
+        /// Like [`arbitrary_os_string`], but also produces empty, very long and NUL containing values.
+        fn hostile_os_string() -> impl Strategy<Value = std::ffi::OsString> {
+            prop_oneof![
+                Just(std::ffi::OsString::new()),
+                arbitrary_os_string(),
+                arbitrary_os_string().prop_map(|value| {
+                    let mut long = std::ffi::OsString::from("/");
+                    for _ in 0..512 {
+                        long.push(&value);
+                        long.push("/");
+                    }
+                    long
+                }),
+                arbitrary_os_string().prop_map(|value| {
+                    let mut with_nul = value;
+                    with_nul.push("\0/%HOME%\0");
+                    with_nul
+                }),
+            ]
+        }
+
+        proptest! {
+            /// Like `resolution_never_panics_for_arbitrary_env`, but with long and NUL-containing
+            /// values, and covering the base and user directories, their styled, result and
+            /// search path variants, [`Resolver`] snapshots, [`Dirs`] and [`AppDirs`] with
+            /// arbitrary names, and the macOS migration. Run more cases with e.g.
+            /// `PROPTEST_CASES=4096`.
+            #[test]
+            fn public_resolvers_never_panic_for_hostile_env(
+                home in hostile_os_string(),
+                config in hostile_os_string(),
+                appdata in hostile_os_string(),
+                name in any::<String>(),
+                vars in proptest::collection::hash_map(
+                    proptest::sample::select(RELEVANT_KEYS).prop_map(String::from),
+                    hostile_os_string(),
+                    0..RELEVANT_KEYS.len(),
+                )
+            ) {
+                let mut env: MapEnv = vars.into_iter().collect();
+                env.set("HOME", home.clone());
+                env.set("USERPROFILE", home);
+                env.set("XDG_CONFIG_HOME", config);
+                env.set("APPDATA", appdata);
 
-        restore_var("XDG_CACHE_HOME", original_xdg);
-        restore_var("HOME", original_home);
+                let _ = home_dir_result_with(&env);
+                let _ = config_dir_result_with(&env);
+                let _ = config_local_dir_with(&env);
+                let _ = preference_dir_with(&env);
+                let _ = data_dir_result_with(&env);
+                let _ = data_local_dir_with(&env);
+                let _ = cache_dir_result_with(&env);
+                let _ = state_dir_result_with(&env);
+                let _ = log_dir_with(&env);
+                let _ = font_dir_with(&env);
+                let _ = runtime_dir_result_with(&env);
+                let _ = executable_dir_with(&env);
+                let _ = config_dirs_with(&env).count();
+                let _ = data_dirs_with(&env).count();
+                let _ = find_config_file_with(&env, "myapp/config.toml");
+                let _ = is_sandboxed_with(&env);
+                let _ = temp_dir_with(&env);
+                let _ = config_dir_for_invoking_user_with(&env);
+                for style in [DirStyle::Native, DirStyle::Xdg, DirStyle::Portable] {
+                    let _ = config_dir_styled_with(&env, style);
+                    let _ = data_dir_styled_with(&env, style);
+                    let _ = cache_dir_styled_with(&env, style);
+                    let _ = state_dir_styled_with(&env, style);
+                }
+                let _ = desktop_dir_with(&env);
+                let _ = documents_dir_with(&env);
+                let _ = download_dir_with(&env);
+                let _ = music_dir_with(&env);
+                let _ = pictures_dir_with(&env);
+                let _ = public_dir_with(&env);
+                let _ = templates_dir_with(&env);
+                let _ = videos_dir_with(&env);
+                let _ = Dirs::try_from_env(&env);
+                let resolver = Resolver::snapshot(&env);
+                let _ = (resolver.config_dir(), resolver.data_dir(), resolver.cache_dir());
+                let _ = resolver.state_dir();
+                let _ = AppDirs::new("com", "Example", &name);
+                for app in [
+                    AppDirs::from_env(&env, "com", "Example", "My App"),
+                    AppDirs::from_env(&env, &name, &name, &name),
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    let _ = app.profile(&name);
+                    let _ = app.with_home_var_with(&env, "HOME");
+                }
+                // A random home practically never contains `Library/Application Support/<app>` to move
+                let _ = migrate_macos_to_xdg_with(&env, "dirs-lite-proptest");
+            }
+        }
     }
 }
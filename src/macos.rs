@@ -0,0 +1,174 @@
+use std::ffi::{CString, c_char, c_int, c_void};
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// The extended attribute Time Machine checks to skip an item, as set by `tmutil addexclusion`.
+const BACKUP_EXCLUDE_XATTR: &str = "com.apple.metadata:com_apple_backup_excludeItem";
+
+/// The value of [`BACKUP_EXCLUDE_XATTR`], the string `com.apple.backupd` as a binary property list.
+const BACKUP_EXCLUDE_VALUE: &[u8] = b"bplist00_\x10\x11com.apple.backupd\x08\
+    \x00\x00\x00\x00\x00\x00\x01\x01\
+    \x00\x00\x00\x00\x00\x00\x00\x01\
+    \x00\x00\x00\x00\x00\x00\x00\x00\
+    \x00\x00\x00\x00\x00\x00\x00\x1c";
+
+unsafe extern "C" {
+    fn setxattr(
+        path: *const c_char,
+        name: *const c_char,
+        value: *const c_void,
+        size: usize,
+        position: u32,
+        options: c_int,
+    ) -> c_int;
+}
+
+type CFTypeRef = *const c_void;
+type CFIndex = isize;
+
+#[link(name = "CoreFoundation", kind = "framework")]
+unsafe extern "C" {
+    static kCFURLIsExcludedFromBackupKey: CFTypeRef;
+    static kCFBooleanTrue: CFTypeRef;
+
+    fn CFURLCreateFromFileSystemRepresentation(
+        allocator: CFTypeRef,
+        buffer: *const u8,
+        len: CFIndex,
+        is_directory: u8,
+    ) -> CFTypeRef;
+    fn CFURLSetResourcePropertyForKey(
+        url: CFTypeRef,
+        key: CFTypeRef,
+        value: CFTypeRef,
+        error: *mut CFTypeRef,
+    ) -> u8;
+    fn CFRelease(cf: CFTypeRef);
+}
+
+/// Sets the `NSURLIsExcludedFromBackupKey` resource value of the directory `path`, which
+/// excludes it from iCloud and Time Machine backups.
+pub(crate) fn set_excluded_from_backup(path: &Path) -> io::Result<()> {
+    let bytes = path.as_os_str().as_bytes();
+    let len = CFIndex::try_from(bytes.len()).map_err(io::Error::other)?;
+    // SAFETY: `bytes` is valid for `len` bytes, and a null allocator selects the default one.
+    let url = unsafe {
+        CFURLCreateFromFileSystemRepresentation(std::ptr::null(), bytes.as_ptr(), len, 1)
+    };
+    if url.is_null() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot create a URL for the path",
+        ));
+    }
+    let mut error = std::ptr::null();
+    // SAFETY: `url` is a valid `CFURL`, and the key and value are constants of CoreFoundation.
+    let set = unsafe {
+        CFURLSetResourcePropertyForKey(
+            url,
+            kCFURLIsExcludedFromBackupKey,
+            kCFBooleanTrue,
+            &mut error,
+        )
+    };
+    // SAFETY: `url` and, if set, `error` are owned by us after the calls above.
+    unsafe {
+        CFRelease(url);
+        if !error.is_null() {
+            CFRelease(error);
+        }
+    }
+    if set != 0 {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            "cannot exclude the directory from backups",
+        ))
+    }
+}
+
+/// Excludes `path` from Time Machine backups.
+pub(crate) fn exclude_from_backup(path: &Path) -> io::Result<()> {
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    let name = CString::new(BACKUP_EXCLUDE_XATTR)?;
+    // SAFETY: `path` and `name` are null-terminated, and `value` is valid for `size` bytes.
+    let result = unsafe {
+        setxattr(
+            path.as_ptr(),
+            name.as_ptr(),
+            BACKUP_EXCLUDE_VALUE.as_ptr().cast(),
+            BACKUP_EXCLUDE_VALUE.len(),
+            0,
+            0,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(test)]
+unsafe extern "C" {
+    fn getxattr(
+        path: *const c_char,
+        name: *const c_char,
+        value: *mut c_void,
+        size: usize,
+        position: u32,
+        options: c_int,
+    ) -> isize;
+}
+
+/// Returns the value of the Time Machine exclusion attribute of `path`, if any.
+#[cfg(test)]
+pub(crate) fn backup_exclusion(path: &Path) -> Option<Vec<u8>> {
+    let path = CString::new(path.as_os_str().as_bytes()).unwrap();
+    let name = CString::new(BACKUP_EXCLUDE_XATTR).unwrap();
+    let mut value = vec![0u8; 256];
+    // SAFETY: `path` and `name` are null-terminated, and `value` is valid for its length.
+    let len = unsafe {
+        getxattr(
+            path.as_ptr(),
+            name.as_ptr(),
+            value.as_mut_ptr().cast(),
+            value.len(),
+            0,
+            0,
+        )
+    };
+    let len = usize::try_from(len).ok()?;
+    value.truncate(len);
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_exclude_value_is_a_binary_plist() {
+        assert_eq!(BACKUP_EXCLUDE_VALUE.len(), 61);
+        assert_eq!(BACKUP_EXCLUDE_VALUE[0x1c], 0x08);
+    }
+
+    #[test]
+    fn set_excluded_from_backup_sets_xattr() {
+        let dir = tempfile::tempdir().unwrap();
+        set_excluded_from_backup(dir.path()).unwrap();
+        assert!(backup_exclusion(dir.path()).is_some());
+    }
+
+    #[test]
+    fn exclude_from_backup_sets_xattr() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(backup_exclusion(dir.path()), None);
+        exclude_from_backup(dir.path()).unwrap();
+        assert_eq!(
+            backup_exclusion(dir.path()).as_deref(),
+            Some(BACKUP_EXCLUDE_VALUE)
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Prints the directories resolved by `dirs-lite`, for use in scripts.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use dirs_lite::{AppDirs, DirKind};
+
+const USAGE: &str = "\
+usage: dirs-lite <config|data|cache|state|runtime> [--app <name>]
+       dirs-lite --all [--json]";
+
+const ALL: [DirKind; 5] = [
+    DirKind::Config,
+    DirKind::Data,
+    DirKind::Cache,
+    DirKind::State,
+    DirKind::Runtime,
+];
+
+fn main() -> ExitCode {
+    let mut kind = None;
+    let mut app = None;
+    let mut all = false;
+    let mut json = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" => all = true,
+            "--json" => json = true,
+            "--app" => match args.next() {
+                Some(name) => app = Some(name),
+                None => return usage("--app requires a name"),
+            },
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ if kind.is_none() => match arg.parse::<DirKind>() {
+                Ok(parsed) => kind = Some(parsed),
+                Err(e) => return usage(&e.to_string()),
+            },
+            _ => return usage(&format!("unexpected argument `{arg}`")),
+        }
+    }
+
+    if all {
+        if kind.is_some() || app.is_some() {
+            return usage("--all cannot be combined with a directory or --app");
+        }
+        print_all(json);
+        return ExitCode::SUCCESS;
+    }
+    let Some(kind) = kind else {
+        return usage("missing directory");
+    };
+
+    let dir = match app {
+        Some(app) => match app_dir(kind, &app) {
+            Ok(dir) => dir,
+            Err(message) => return usage(&message),
+        },
+        None => resolve(kind),
+    };
+    match dir {
+        Some(dir) if json => println!("{}", json_string(&dir.to_string_lossy())),
+        Some(dir) => println!("{}", dir.display()),
+        None => {
+            eprintln!("dirs-lite: cannot resolve the {kind} directory");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn resolve(kind: DirKind) -> Option<PathBuf> {
+    match kind {
+        DirKind::Config => dirs_lite::config_dir(),
+        DirKind::Data => dirs_lite::data_dir(),
+        DirKind::Cache => dirs_lite::cache_dir(),
+        DirKind::State => dirs_lite::state_dir(),
+        DirKind::Runtime => dirs_lite::runtime_dir(),
+        _ => None,
+    }
+}
+
+fn app_dir(kind: DirKind, app: &str) -> Result<Option<PathBuf>, String> {
+    let dirs = AppDirs::new("", "", app);
+    match kind {
+        DirKind::Config => Ok(dirs.map(|dirs| dirs.config_dir().to_path_buf())),
+        DirKind::Data => Ok(dirs.map(|dirs| dirs.data_dir().to_path_buf())),
+        DirKind::Cache => Ok(dirs.map(|dirs| dirs.cache_dir().to_path_buf())),
+        _ => Err(format!("--app is not supported for the {kind} directory")),
+    }
+}
+
+fn print_all(json: bool) {
+    if json {
+        let fields: Vec<String> = ALL
+            .iter()
+            .map(|kind| {
+                let value = resolve(*kind).map_or_else(
+                    || "null".to_string(),
+                    |dir| json_string(&dir.to_string_lossy()),
+                );
+                format!("{}:{value}", json_string(&kind.to_string()))
+            })
+            .collect();
+        println!("{{{}}}", fields.join(","));
+    } else {
+        for kind in ALL {
+            if let Some(dir) = resolve(kind) {
+                println!("{kind}\t{}", dir.display());
+            }
+        }
+    }
+}
+
+/// Quotes `value` as a JSON string.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn usage(message: &str) -> ExitCode {
+    eprintln!("dirs-lite: {message}\n{USAGE}");
+    ExitCode::from(2)
+}
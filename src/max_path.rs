@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use crate::{Env, ProcessEnv, data_dir_with};
+
+/// The length of the longest path the Windows API accepts without long path support, i.e.
+/// `MAX_PATH` without the terminating NUL, in UTF-16 code units.
+const MAX_PATH_LEN: usize = 259;
+
+/// The room [`data_dir_checked`](crate::data_dir_checked) requires the data directory to leave
+/// below [`MAX_PATH_LEN`], for the application's directories and files in it.
+pub(crate) const MIN_HEADROOM: usize = 100;
+
+/// Returns how many characters a path below [`data_dir`](crate::data_dir) may add before it
+/// exceeds `MAX_PATH`, 260 characters including the terminating NUL, on Windows.
+///
+/// Windows rejects longer paths unless long path support is enabled both in the registry and
+/// the manifest of the application, which this does not check. Returns `None` on other
+/// platforms and if the directory cannot be resolved.
+pub fn max_path_headroom() -> Option<usize> {
+    max_path_headroom_with(&ProcessEnv)
+}
+
+/// Like [`max_path_headroom`], but resolves against the given environment instead of the one of the current process.
+pub fn max_path_headroom_with<E: Env + ?Sized>(env: &E) -> Option<usize> {
+    if !cfg!(target_os = "windows") {
+        return None;
+    }
+    data_dir_with(env).map(|dir| headroom(&dir))
+}
+
+/// Returns how many UTF-16 code units can be appended to `path` before it exceeds `MAX_PATH`.
+pub(crate) fn headroom(path: &Path) -> usize {
+    // Unpaired surrogates become U+FFFD, which takes a single code unit just like them
+    let len = path.as_os_str().to_string_lossy().encode_utf16().count();
+    MAX_PATH_LEN.saturating_sub(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    fn headroom_counts_utf16_code_units() {
+        assert_eq!(headroom(Path::new("C:\\Users")), 251);
+        assert_eq!(headroom(Path::new("C:\\\u{1F600}")), 254);
+        assert_eq!(headroom(Path::new(&"a".repeat(300))), 0);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn max_path_headroom_is_none_elsewhere() {
+        let env = MapEnv::new().with("HOME", "/home/alice");
+        assert_eq!(max_path_headroom_with(&env), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_max_path_headroom_of_long_localappdata() {
+        let short = MapEnv::new().with("LOCALAPPDATA", "C:\\Users\\Alice\\AppData\\Local");
+        assert_eq!(max_path_headroom_with(&short), Some(231));
+
+        let long = format!("C:\\Users\\Alice\\{}\\AppData\\Local", "x".repeat(200));
+        let env = MapEnv::new().with("LOCALAPPDATA", &long);
+        assert_eq!(max_path_headroom_with(&env), Some(30));
+        assert_eq!(
+            crate::data_dir_checked_with(&env),
+            Err(crate::DirError::PathTooLong {
+                path: long.into(),
+                headroom: 30
+            })
+        );
+        assert!(crate::data_dir_checked_with(&short).is_ok());
+    }
+}
@@ -0,0 +1,230 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::{
+    DirStyle, Env, ProcessEnv, cache_dir_styled_with, config_dir_styled_with, data_dir_styled_with,
+};
+
+/// Moves the directories of the application `app` from the native macOS layout to the XDG
+/// layout, e.g. after switching to the feature `favor-xdg-style`.
+///
+/// Each of `$HOME/Library/Application Support/<app>` and `$HOME/Library/Caches/<app>` is moved
+/// if it exists and its XDG counterpart does not. As config and data share
+/// `Application Support` on macOS, it is moved to the config directory, `$HOME/.config/<app>`.
+/// Directories are renamed, or copied and then removed if they are on another volume. A copy is
+/// made next to the target first and renamed into place once complete, so an interrupted copy
+/// never leaves a partial target that would stop the migration from being retried.
+///
+/// Returns whether anything was moved. This is a no-op returning `false` on other platforms,
+/// and once the migration is done.
+pub fn migrate_macos_to_xdg(app: &str) -> io::Result<bool> {
+    migrate_macos_to_xdg_with(&ProcessEnv, app)
+}
+
+/// Like [`migrate_macos_to_xdg`], but resolves against the given environment instead of the one of the current process.
+pub fn migrate_macos_to_xdg_with<E: Env + ?Sized>(env: &E, app: &str) -> io::Result<bool> {
+    if !cfg!(target_os = "macos") {
+        return Ok(false);
+    }
+    let styled = [
+        config_dir_styled_with,
+        data_dir_styled_with,
+        cache_dir_styled_with,
+    ];
+    let mut migrated = false;
+    for dir in styled {
+        let (Some(native), Some(xdg)) = (dir(env, DirStyle::Native), dir(env, DirStyle::Xdg))
+        else {
+            continue;
+        };
+        let (from, to) = (native.join(app), xdg.join(app));
+        if from == to || !from.is_dir() || to.exists() {
+            continue;
+        }
+        move_dir(&from, &to)?;
+        migrated = true;
+    }
+    Ok(migrated)
+}
+
+/// Moves `from` to `to`, creating the missing parents of `to`.
+fn move_dir(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            copy_dir_atomically(from, to)?;
+            fs::remove_dir_all(from)
+        }
+        result => result,
+    }
+}
+
+/// Copies `from` to a temporary sibling of `to` and renames it to `to` once complete.
+fn copy_dir_atomically(from: &Path, to: &Path) -> io::Result<()> {
+    let mut name = std::ffi::OsString::from(".");
+    name.push(to.file_name().unwrap_or_default());
+    name.push(format!(".migrating-{}", std::process::id()));
+    let partial = to.with_file_name(name);
+    if partial.exists() {
+        fs::remove_dir_all(&partial)?;
+    }
+    let copied = copy_dir(from, &partial).and_then(|()| fs::rename(&partial, to));
+    if copied.is_err() {
+        // Best effort, the error of the copy is more useful than the one of the cleanup
+        let _ = fs::remove_dir_all(&partial);
+    }
+    copied
+}
+
+/// Copies the directory `from` with all its contents to `to`, symlinks are copied as links.
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            copy_symlink(&entry.path(), &target)?;
+        } else if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Creates the symlink `to` pointing to the same target as the symlink `from`.
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    let link = fs::read_link(from)?;
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(link, to)
+    }
+    #[cfg(windows)]
+    {
+        if fs::metadata(from).is_ok_and(|target| target.is_dir()) {
+            std::os::windows::fs::symlink_dir(link, to)
+        } else {
+            std::os::windows::fs::symlink_file(link, to)
+        }
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = (link, to);
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "symlinks are not supported on this platform",
+        ))
+    }
+}
+
+#[cfg(all(test, not(target_family = "wasm")))]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    fn copy_dir_copies_nested_contents() {
+        let root = tempfile::tempdir().unwrap();
+        let from = root.path().join("from");
+        fs::create_dir_all(from.join("nested")).unwrap();
+        fs::write(from.join("nested/settings.toml"), "answer = 42").unwrap();
+
+        copy_dir(&from, &root.path().join("to")).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.path().join("to/nested/settings.toml")).unwrap(),
+            "answer = 42"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn copy_dir_copies_symlinks_as_links() {
+        let root = tempfile::tempdir().unwrap();
+        let from = root.path().join("from");
+        fs::create_dir_all(from.join("nested")).unwrap();
+        std::os::unix::fs::symlink("nested", from.join("current")).unwrap();
+        std::os::unix::fs::symlink("missing", from.join("dangling")).unwrap();
+
+        let to = root.path().join("to");
+        copy_dir(&from, &to).unwrap();
+        assert_eq!(
+            fs::read_link(to.join("current")).unwrap(),
+            Path::new("nested")
+        );
+        assert_eq!(
+            fs::read_link(to.join("dangling")).unwrap(),
+            Path::new("missing")
+        );
+    }
+
+    #[test]
+    fn copy_dir_atomically_leaves_no_partial_target() {
+        let root = tempfile::tempdir().unwrap();
+        let from = root.path().join("from");
+        fs::create_dir_all(&from).unwrap();
+        fs::write(from.join("settings.toml"), "answer = 42").unwrap();
+
+        let to = root.path().join("to");
+        copy_dir_atomically(&from, &to).unwrap();
+        assert!(to.join("settings.toml").is_file());
+        assert_eq!(fs::read_dir(root.path()).unwrap().count(), 2);
+
+        let missing = root.path().join("missing");
+        assert!(copy_dir_atomically(&missing, &root.path().join("other")).is_err());
+        assert_eq!(fs::read_dir(root.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn migrate_is_noop_off_macos() {
+        let home = tempfile::tempdir().unwrap();
+        fs::create_dir_all(home.path().join("Library/Caches/myapp")).unwrap();
+        let env = MapEnv::new().with("HOME", home.path());
+
+        assert!(!migrate_macos_to_xdg_with(&env, "myapp").unwrap());
+        assert!(home.path().join("Library/Caches/myapp").is_dir());
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_migrate_moves_library_dirs_once() {
+        let home = tempfile::tempdir().unwrap();
+        let support = home.path().join("Library/Application Support/myapp");
+        let caches = home.path().join("Library/Caches/myapp");
+        fs::create_dir_all(&support).unwrap();
+        fs::create_dir_all(&caches).unwrap();
+        fs::write(support.join("settings.toml"), "answer = 42").unwrap();
+        fs::write(caches.join("index"), "").unwrap();
+        let env = MapEnv::new().with("HOME", home.path());
+
+        assert!(migrate_macos_to_xdg_with(&env, "myapp").unwrap());
+        assert!(!support.exists());
+        assert!(!caches.exists());
+        assert_eq!(
+            fs::read_to_string(home.path().join(".config/myapp/settings.toml")).unwrap(),
+            "answer = 42"
+        );
+        assert!(home.path().join(".cache/myapp/index").is_file());
+
+        assert!(!migrate_macos_to_xdg_with(&env, "myapp").unwrap());
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_migrate_keeps_existing_target() {
+        let home = tempfile::tempdir().unwrap();
+        let caches = home.path().join("Library/Caches/myapp");
+        let target = home.path().join(".cache/myapp");
+        fs::create_dir_all(&caches).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        let env = MapEnv::new().with("HOME", home.path());
+
+        assert!(!migrate_macos_to_xdg_with(&env, "myapp").unwrap());
+        assert!(caches.is_dir());
+    }
+}
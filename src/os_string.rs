@@ -0,0 +1,78 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use crate::{Env, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with};
+
+/// Like [`config_dir`](crate::config_dir), but returns the path as an [`OsString`], e.g. to
+/// pass it on to a C API.
+///
+/// The resolution is shared with [`config_dir`](crate::config_dir), and the conversion does
+/// not allocate.
+pub fn config_dir_os() -> Option<OsString> {
+    config_dir_os_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_os`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_os_with<E: Env + ?Sized>(env: &E) -> Option<OsString> {
+    config_dir_with(env).map(PathBuf::into_os_string)
+}
+
+/// Like [`data_dir`](crate::data_dir), but returns the path as an [`OsString`], see [`config_dir_os`].
+pub fn data_dir_os() -> Option<OsString> {
+    data_dir_os_with(&ProcessEnv)
+}
+
+/// Like [`data_dir_os`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_os_with<E: Env + ?Sized>(env: &E) -> Option<OsString> {
+    data_dir_with(env).map(PathBuf::into_os_string)
+}
+
+/// Like [`cache_dir`](crate::cache_dir), but returns the path as an [`OsString`], see [`config_dir_os`].
+pub fn cache_dir_os() -> Option<OsString> {
+    cache_dir_os_with(&ProcessEnv)
+}
+
+/// Like [`cache_dir_os`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_os_with<E: Env + ?Sized>(env: &E) -> Option<OsString> {
+    cache_dir_with(env).map(PathBuf::into_os_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    fn os_dirs_match_path_dirs() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming")
+            .with("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local");
+        assert_eq!(
+            config_dir_os_with(&env).as_deref(),
+            config_dir_with(&env).as_ref().map(|dir| dir.as_os_str())
+        );
+        assert_eq!(
+            data_dir_os_with(&env).as_deref(),
+            data_dir_with(&env).as_ref().map(|dir| dir.as_os_str())
+        );
+        assert_eq!(
+            cache_dir_os_with(&env).as_deref(),
+            cache_dir_with(&env).as_ref().map(|dir| dir.as_os_str())
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_os_dirs_keep_non_utf8_home() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let env = MapEnv::new().with("HOME", OsStr::from_bytes(b"/home/\xff"));
+        assert_eq!(
+            config_dir_os_with(&env),
+            Some(OsStr::from_bytes(b"/home/\xff/.config").to_os_string())
+        );
+        assert_eq!(config_dir_os_with(&MapEnv::new()), None);
+    }
+}
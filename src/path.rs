@@ -0,0 +1,163 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf, is_separator};
+
+/// Expands a leading `$ORIGIN` or `${ORIGIN}` token in `input` to `origin`.
+///
+/// This follows the ELF rpath convention and lets e.g. plugins express their config
+/// paths relative to the directory they were loaded from. Any other input, including
+/// absolute paths, is returned untouched.
+///
+/// ```
+/// use std::path::{Path, PathBuf};
+///
+/// let origin = Path::new("/opt/plugins/foo");
+/// assert_eq!(
+///     dirs_lite::expand_origin("$ORIGIN/data", origin),
+///     PathBuf::from("/opt/plugins/foo/data")
+/// );
+/// ```
+pub fn expand_origin(input: &str, origin: &Path) -> PathBuf {
+    for token in ["${ORIGIN}", "$ORIGIN"] {
+        let Some(rest) = input.strip_prefix(token) else {
+            continue;
+        };
+        if rest.is_empty() {
+            return origin.to_path_buf();
+        }
+        if let Some(rest) = rest.strip_prefix(is_separator) {
+            return origin.join(rest);
+        }
+    }
+    PathBuf::from(input)
+}
+
+/// Returns whether `a` and `b` name the same directory by the conventions of the current
+/// platform, without touching the file system.
+///
+/// Trailing and repeated separators as well as `.` components are ignored, and so is the
+/// difference between `/` and `\` on Windows. On Windows and macOS, whose file systems are
+/// case-insensitive by default, the comparison is case-insensitive too. Symlinks and `..`
+/// components are not resolved, see [`config_dir_canonical`](crate::config_dir_canonical) for that.
+///
+/// ```
+/// use std::path::Path;
+///
+/// assert!(dirs_lite::same_dir(
+///     Path::new("/home/alice/.config/"),
+///     Path::new("/home/alice//.config"),
+/// ));
+/// ```
+pub fn same_dir(a: &Path, b: &Path) -> bool {
+    a.components().map(folded).eq(b.components().map(folded))
+}
+
+/// Returns `component` case-folded if paths are case-insensitive on the current platform.
+///
+/// Components that are not valid Unicode are compared as-is.
+pub(crate) fn folded(component: Component<'_>) -> Cow<'_, OsStr> {
+    let component = component.as_os_str();
+    if !cfg!(any(target_os = "windows", target_os = "macos")) {
+        return Cow::Borrowed(component);
+    }
+    match component.to_str() {
+        Some(s) if s.chars().any(char::is_uppercase) => Cow::Owned(s.to_lowercase().into()),
+        _ => Cow::Borrowed(component),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_plain_origin_token() {
+        assert_eq!(
+            expand_origin("$ORIGIN/data", Path::new("/opt/plugin")),
+            PathBuf::from("/opt/plugin/data")
+        );
+    }
+
+    #[test]
+    fn expands_braced_origin_token() {
+        assert_eq!(
+            expand_origin("${ORIGIN}/cfg", Path::new("/opt/plugin")),
+            PathBuf::from("/opt/plugin/cfg")
+        );
+    }
+
+    #[test]
+    fn expands_bare_origin_token() {
+        assert_eq!(
+            expand_origin("$ORIGIN", Path::new("/opt/plugin")),
+            PathBuf::from("/opt/plugin")
+        );
+    }
+
+    #[test]
+    fn leaves_absolute_path_untouched() {
+        assert_eq!(
+            expand_origin("/etc/plugin/cfg", Path::new("/opt/plugin")),
+            PathBuf::from("/etc/plugin/cfg")
+        );
+    }
+
+    #[test]
+    fn leaves_longer_variable_names_untouched() {
+        assert_eq!(
+            expand_origin("$ORIGINAL/cfg", Path::new("/opt/plugin")),
+            PathBuf::from("$ORIGINAL/cfg")
+        );
+    }
+
+    #[test]
+    fn same_dir_ignores_trailing_and_repeated_separators() {
+        assert!(same_dir(
+            Path::new("/home/alice/.config/"),
+            Path::new("/home/alice/.config")
+        ));
+        assert!(same_dir(
+            Path::new("/home//alice/./.config"),
+            Path::new("/home/alice/.config")
+        ));
+        assert!(!same_dir(
+            Path::new("/home/alice/.config"),
+            Path::new("/home/alice/.config/myapp")
+        ));
+        assert!(!same_dir(
+            Path::new("/home/alice/.config"),
+            Path::new("home/alice/.config")
+        ));
+    }
+
+    #[test]
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    fn same_dir_is_case_sensitive() {
+        assert!(!same_dir(
+            Path::new("/home/alice/.config"),
+            Path::new("/home/Alice/.config")
+        ));
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_same_dir_is_case_insensitive() {
+        assert!(same_dir(
+            Path::new("/Users/Alice/Library/Application Support"),
+            Path::new("/users/alice/library/application support/")
+        ));
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_same_dir_ignores_separators_and_case() {
+        assert!(same_dir(
+            Path::new(r"C:\Users\Alice\AppData\Roaming\"),
+            Path::new("c:/users/alice/AppData/Roaming")
+        ));
+        assert!(!same_dir(
+            Path::new(r"C:\Users\Alice\AppData\Roaming"),
+            Path::new(r"D:\Users\Alice\AppData\Roaming")
+        ));
+    }
+}
@@ -0,0 +1,62 @@
+//! The commonly used functions and types, to import them all at once.
+//!
+//! ```
+//! use dirs_lite::prelude::*;
+//!
+//! let settings = config_dir().map(|dir| dir.join("myapp/settings.toml"));
+//! let cache: Result<_, DirError> = cache_dir_result();
+//! assert_eq!(cache.ok(), cache_dir());
+//! ```
+//!
+//! Every directory function of the crate root is included, a test keeps the list in sync. Only
+//! the variants resolving against the environment of the current process are included, the
+//! `_with` variants and [`Env`](crate::Env) have to be imported from the crate root.
+
+pub use crate::{
+    AppDirs, DirError, DirExt, DirStyle, Dirs, all_base_dirs, app_temp_dir, autostart_dir, bin_dir,
+    cache_dir, cache_dir_result, config_dir, config_dir_result, config_dirs, config_local_dir,
+    config_roaming_dir, data_dir, data_dir_result, data_dirs, data_local_dir, desktop_dir,
+    documents_dir, documents_portal_dir, download_dir, executable_dir, font_dir,
+    group_container_dir, home_dir, home_dir_result, log_dir, music_dir, pictures_dir,
+    preference_dir, public_dir, roaming_data_dir, runtime_dir, runtime_dir_result, state_dir,
+    state_dir_result, system_config_dirs, system_data_dirs, temp_dir, templates_dir, vendor_dir,
+    videos_dir,
+};
+
+#[cfg(test)]
+mod tests {
+    /// Returns the names in the `pub use` lists and `pub fn` items of `source`.
+    fn public_names(source: &str) -> Vec<&str> {
+        let mut names = Vec::new();
+        for item in source.split("\npub ").skip(1) {
+            if let Some(list) = item.strip_prefix("use ") {
+                let list = &list[..list.find(';').unwrap_or(list.len())];
+                let list = list.rsplit("::").next().unwrap_or(list);
+                names.extend(
+                    list.split(|c: char| !c.is_alphanumeric() && c != '_')
+                        .filter(|name| !name.is_empty()),
+                );
+            } else if let Some(item) = item.strip_prefix("fn ") {
+                names.extend(item.split(['(', '<']).next());
+            }
+        }
+        names
+    }
+
+    #[test]
+    fn prelude_exports_every_dir_function() {
+        let prelude = public_names(include_str!("prelude.rs"));
+        let missing: Vec<&str> = public_names(include_str!("lib.rs"))
+            .into_iter()
+            .filter(|name| {
+                ["_dir", "_dirs", "_dir_result"]
+                    .iter()
+                    .any(|s| name.ends_with(s))
+            })
+            // Creating a directory and comparing two paths are not directory lookups
+            .filter(|name| !name.starts_with("ensure_") && *name != "same_dir")
+            .filter(|name| !prelude.contains(name))
+            .collect();
+        assert!(missing.is_empty(), "missing in the prelude: {missing:?}");
+    }
+}
@@ -0,0 +1,326 @@
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+
+use crate::{
+    Env, KnownFolder, MapEnv, PREFIX_ENV, ProcessEnv, cache_dir_with, config_dir_with,
+    data_dir_with, runtime_dir_with, state_dir_with,
+};
+
+/// The environment variables captured by [`Resolver::from_env`].
+const SNAPSHOT_VARS: &[&str] = &[
+    "HOME",
+    "XDG_CONFIG_HOME",
+    "XDG_DATA_HOME",
+    "XDG_CACHE_HOME",
+    "XDG_STATE_HOME",
+    "XDG_RUNTIME_DIR",
+    "XDG_BIN_HOME",
+    "XDG_CONFIG_DIRS",
+    "XDG_DATA_DIRS",
+    "SNAP",
+    "SNAP_USER_DATA",
+    "SNAP_USER_COMMON",
+    "APPIMAGE",
+    "HOST_XDG_CONFIG_HOME",
+    "HOST_XDG_DATA_HOME",
+    "HOST_XDG_CACHE_HOME",
+    "SUDO_USER",
+    "APP_SANDBOX_CONTAINER_ID",
+    "TMPDIR",
+    "TMP",
+    "TEMP",
+    PREFIX_ENV,
+    "USERPROFILE",
+    "HOMEDRIVE",
+    "HOMEPATH",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "PROGRAMDATA",
+    "SystemDrive",
+    "SystemRoot",
+];
+
+/// The Windows known folders captured by [`Resolver::from_env`].
+const SNAPSHOT_FOLDERS: [KnownFolder; 5] = [
+    KnownFolder::RoamingAppData,
+    KnownFolder::LocalAppData,
+    KnownFolder::Music,
+    KnownFolder::Pictures,
+    KnownFolder::Videos,
+];
+
+/// A snapshot of the environment the directories are resolved from, taken once.
+///
+/// Unlike the plain functions, which read the environment on every call, a resolver returns
+/// the same directories for its whole lifetime, even if the environment of the process changes
+/// in the meantime. It implements [`Env`], so it can be passed to any `_with` function as well:
+///
+/// ```
+/// let resolver = dirs_lite::Resolver::from_env();
+/// let config = resolver.config_dir();
+/// let music = dirs_lite::music_dir_with(&resolver);
+/// ```
+///
+/// Only the variables this crate reads are captured, together with the home directory from
+/// the password database and the Windows known folders, if enabled, and the Windows session.
+/// Of the other users, only the home directories of `$SUDO_USER` and of users referred to as
+/// `~user` in the captured variables are looked up.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Resolver {
+    vars: MapEnv,
+    passwd_home: Option<PathBuf>,
+    user_homes: Vec<(String, PathBuf)>,
+    known_folders: Vec<(KnownFolder, PathBuf)>,
+    session_id: Option<u32>,
+}
+
+impl Resolver {
+    /// Captures the environment of the current process.
+    pub fn from_env() -> Self {
+        Self::snapshot(&ProcessEnv)
+    }
+
+    /// Like [`Resolver::from_env`], but captures the given environment instead of the one of the current process.
+    pub fn snapshot<E: Env + ?Sized>(env: &E) -> Self {
+        let mut vars = MapEnv::new();
+        let mut user_homes: Vec<(String, PathBuf)> = Vec::new();
+        for key in SNAPSHOT_VARS {
+            if let Some(value) = env.var_os(key) {
+                if let Some(user) = referenced_user(key, &value) {
+                    if !user_homes.iter().any(|(name, _)| name == user) {
+                        if let Some(home) = env.user_home(user) {
+                            user_homes.push((user.to_string(), home));
+                        }
+                    }
+                }
+                vars.set(*key, value);
+            }
+        }
+        Resolver {
+            vars,
+            passwd_home: env.passwd_home(),
+            user_homes,
+            known_folders: SNAPSHOT_FOLDERS
+                .into_iter()
+                .filter_map(|folder| Some((folder, env.known_folder(folder)?)))
+                .collect(),
+            session_id: env.session_id(),
+        }
+    }
+
+    /// Returns [`config_dir`](crate::config_dir) as of the snapshot.
+    pub fn config_dir(&self) -> Option<PathBuf> {
+        config_dir_with(self)
+    }
+
+    /// Returns [`data_dir`](crate::data_dir) as of the snapshot.
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        data_dir_with(self)
+    }
+
+    /// Returns [`cache_dir`](crate::cache_dir) as of the snapshot.
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        cache_dir_with(self)
+    }
+
+    /// Returns [`state_dir`](crate::state_dir) as of the snapshot.
+    pub fn state_dir(&self) -> Option<PathBuf> {
+        state_dir_with(self)
+    }
+
+    /// Returns [`runtime_dir`](crate::runtime_dir) as of the snapshot.
+    pub fn runtime_dir(&self) -> Option<PathBuf> {
+        runtime_dir_with(self)
+    }
+}
+
+impl Env for Resolver {
+    fn var_os(&self, key: &str) -> Option<OsString> {
+        self.vars.var_os(key)
+    }
+
+    fn known_folder(&self, folder: KnownFolder) -> Option<PathBuf> {
+        self.known_folders
+            .iter()
+            .find(|(known, _)| *known == folder)
+            .map(|(_, path)| path.clone())
+    }
+
+    fn passwd_home(&self) -> Option<PathBuf> {
+        self.passwd_home.clone()
+    }
+
+    fn user_home(&self, name: &str) -> Option<PathBuf> {
+        self.user_homes
+            .iter()
+            .find(|(user, _)| user == name)
+            .map(|(_, home)| home.clone())
+    }
+
+    fn session_id(&self) -> Option<u32> {
+        self.session_id
+    }
+}
+
+/// Returns the user whose home directory the variable `key` refers to, i.e. `$SUDO_USER` itself
+/// or the `user` of a leading `~user`, see [`Env::user_home`].
+fn referenced_user<'a>(key: &str, value: &'a OsStr) -> Option<&'a str> {
+    let value = value.to_str()?;
+    let user = if key == "SUDO_USER" {
+        value
+    } else {
+        value
+            .strip_prefix('~')?
+            .split(std::path::is_separator)
+            .next()?
+    };
+    (!user.is_empty()).then_some(user)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_matches_the_captured_env() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming")
+            .with("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local")
+            .with("UNRELATED", "value");
+        let resolver = Resolver::snapshot(&env);
+
+        assert_eq!(resolver.config_dir(), config_dir_with(&env));
+        assert_eq!(resolver.data_dir(), data_dir_with(&env));
+        assert_eq!(resolver.cache_dir(), cache_dir_with(&env));
+        assert_eq!(resolver.var_os("UNRELATED"), None);
+    }
+
+    #[test]
+    fn snapshot_vars_cover_every_var_read() {
+        struct RecordingEnv(MapEnv, std::cell::RefCell<Vec<String>>);
+
+        impl Env for RecordingEnv {
+            fn var_os(&self, key: &str) -> Option<OsString> {
+                self.1.borrow_mut().push(key.to_string());
+                self.0.var_os(key)
+            }
+        }
+
+        let mut all = MapEnv::new();
+        for key in SNAPSHOT_VARS {
+            all.set(*key, "/set/by/test");
+        }
+        for vars in [
+            MapEnv::new(),
+            MapEnv::new().with("HOME", "/home/testuser"),
+            all,
+        ] {
+            let env = RecordingEnv(vars, Default::default());
+            let _ = (
+                crate::home_dir_with(&env),
+                config_dir_with(&env),
+                crate::config_local_dir_with(&env),
+                data_dir_with(&env),
+                crate::data_local_dir_with(&env),
+                cache_dir_with(&env),
+                state_dir_with(&env),
+                runtime_dir_with(&env),
+                crate::bin_dir_with(&env),
+                crate::executable_dir_with(&env),
+                crate::font_dir_with(&env),
+                crate::log_dir_with(&env),
+                crate::autostart_dir_with(&env),
+                crate::preference_dir_with(&env),
+                crate::temp_dir_with(&env),
+                crate::config_dirs_with(&env),
+                crate::data_dirs_with(&env),
+                crate::config_dir_for_invoking_user_with(&env),
+            );
+            for key in env.1.take() {
+                assert!(SNAPSHOT_VARS.contains(&&*key), "${key} is not captured");
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_snapshot_captures_referenced_user_homes() {
+        struct PasswdEnv(MapEnv);
+
+        impl Env for PasswdEnv {
+            fn var_os(&self, key: &str) -> Option<OsString> {
+                self.0.var_os(key)
+            }
+
+            fn user_home(&self, name: &str) -> Option<PathBuf> {
+                Some(PathBuf::from("/home").join(name))
+            }
+        }
+
+        let env = PasswdEnv(
+            MapEnv::new()
+                .with("HOME", "/root")
+                .with("SUDO_USER", "alice")
+                .with("XDG_CACHE_HOME", "~bob/cache"),
+        );
+        let resolver = Resolver::snapshot(&env);
+
+        assert_eq!(
+            resolver.user_home("alice"),
+            Some(PathBuf::from("/home/alice"))
+        );
+        assert_eq!(resolver.user_home("bob"), Some(PathBuf::from("/home/bob")));
+        assert_eq!(resolver.user_home("mallory"), None);
+        assert_eq!(resolver.cache_dir(), Some(PathBuf::from("/home/bob/cache")));
+        assert_eq!(
+            crate::config_dir_for_invoking_user_with(&resolver),
+            crate::config_dir_for_invoking_user_with(&env)
+        );
+    }
+
+    #[test]
+    fn snapshot_captures_the_session_and_temp_dir() {
+        struct SessionEnv(MapEnv);
+
+        impl Env for SessionEnv {
+            fn var_os(&self, key: &str) -> Option<OsString> {
+                self.0.var_os(key)
+            }
+
+            fn session_id(&self) -> Option<u32> {
+                Some(2)
+            }
+        }
+
+        let env = SessionEnv(
+            MapEnv::new()
+                .with("TMPDIR", "/tmp/user")
+                .with("TEMP", "C:\\Users\\testuser\\AppData\\Local\\Temp"),
+        );
+        let resolver = Resolver::snapshot(&env);
+
+        assert_eq!(resolver.session_id(), Some(2));
+        assert_eq!(crate::temp_dir_with(&resolver), crate::temp_dir_with(&env));
+        assert_eq!(runtime_dir_with(&resolver), runtime_dir_with(&env));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_resolver_ignores_later_env_changes() {
+        use crate::env_guard::EnvGuard;
+
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        let _env = unsafe { EnvGuard::new().set("XDG_CONFIG_HOME", "/snapshot/config") };
+        let resolver = Resolver::from_env();
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        let _changed = unsafe { EnvGuard::new().set("XDG_CONFIG_HOME", "/changed/config") };
+
+        assert_eq!(crate::config_dir(), Some(PathBuf::from("/changed/config")));
+        assert_eq!(
+            resolver.config_dir(),
+            Some(PathBuf::from("/snapshot/config"))
+        );
+        assert_eq!(resolver.clone().config_dir(), resolver.config_dir());
+    }
+}
@@ -0,0 +1,110 @@
+use std::path::PathBuf;
+
+use crate::{
+    DirError, DirLayout, DirSource, DirStyle, Env, ProcessEnv, cache_dir_styled_into,
+    config_dir_styled_into, data_dir_styled_into,
+};
+
+/// Writes [`config_dir`](crate::config_dir) into `buf`, replacing its contents, and returns
+/// whether it could be resolved.
+///
+/// `buf` is left empty if the directory cannot be resolved. The directory is resolved directly
+/// into `buf` and its capacity is kept, so a caller that builds paths in a loop can reuse one
+/// buffer instead of allocating a new `PathBuf` for every path:
+///
+/// ```
+/// let mut path = std::path::PathBuf::new();
+/// for name in ["a.toml", "b.toml"] {
+///     if dirs_lite::config_dir_into(&mut path) {
+///         path.push("myapp");
+///         path.push(name);
+///     }
+/// }
+/// ```
+pub fn config_dir_into(buf: &mut PathBuf) -> bool {
+    config_dir_into_with(&ProcessEnv, buf)
+}
+
+/// Like [`config_dir_into`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_into_with<E: Env + ?Sized>(env: &E, buf: &mut PathBuf) -> bool {
+    let resolved = config_dir_styled_into(env, DirStyle::default(), &DirLayout::default(), buf);
+    finish(resolved, buf)
+}
+
+/// Writes [`data_dir`](crate::data_dir) into `buf`, see [`config_dir_into`].
+pub fn data_dir_into(buf: &mut PathBuf) -> bool {
+    data_dir_into_with(&ProcessEnv, buf)
+}
+
+/// Like [`data_dir_into`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_into_with<E: Env + ?Sized>(env: &E, buf: &mut PathBuf) -> bool {
+    let resolved = data_dir_styled_into(env, DirStyle::default(), &DirLayout::default(), buf);
+    finish(resolved, buf)
+}
+
+/// Writes [`cache_dir`](crate::cache_dir) into `buf`, see [`config_dir_into`].
+pub fn cache_dir_into(buf: &mut PathBuf) -> bool {
+    cache_dir_into_with(&ProcessEnv, buf)
+}
+
+/// Like [`cache_dir_into`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_into_with<E: Env + ?Sized>(env: &E, buf: &mut PathBuf) -> bool {
+    let resolved = cache_dir_styled_into(env, DirStyle::default(), &DirLayout::default(), buf);
+    finish(resolved, buf)
+}
+
+/// Empties `buf`, keeping its capacity, if the directory could not be resolved into it.
+fn finish(resolved: Result<DirSource, DirError>, buf: &mut PathBuf) -> bool {
+    if resolved.is_err() {
+        buf.as_mut_os_string().clear();
+    }
+    resolved.is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MapEnv, cache_dir_with, config_dir_with, data_dir_with};
+
+    #[test]
+    fn dirs_into_match_dirs() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming")
+            .with("LOCALAPPDATA", "C:\\Users\\testuser\\AppData\\Local");
+        let mut buf = PathBuf::from("/some/previous/path");
+
+        assert!(config_dir_into_with(&env, &mut buf));
+        assert_eq!(Some(&buf), config_dir_with(&env).as_ref());
+        assert!(data_dir_into_with(&env, &mut buf));
+        assert_eq!(Some(&buf), data_dir_with(&env).as_ref());
+        assert!(cache_dir_into_with(&env, &mut buf));
+        assert_eq!(Some(&buf), cache_dir_with(&env).as_ref());
+    }
+
+    #[test]
+    fn dirs_are_resolved_into_the_buffer() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CONFIG_HOME", "/home/testuser//xdg-config/")
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming");
+        let mut buf = PathBuf::with_capacity(256);
+        let ptr = buf.as_os_str().as_encoded_bytes().as_ptr();
+
+        assert!(config_dir_into_with(&env, &mut buf));
+        assert_eq!(Some(&buf), config_dir_with(&env).as_ref());
+        assert_eq!(buf.as_os_str().as_encoded_bytes().as_ptr(), ptr);
+        assert!(cache_dir_into_with(&env, &mut buf));
+        assert_eq!(buf.as_os_str().as_encoded_bytes().as_ptr(), ptr);
+    }
+
+    #[test]
+    fn unresolved_dir_leaves_buffer_empty() {
+        let mut buf = PathBuf::from("/some/previous/path");
+        let capacity = buf.capacity();
+
+        assert!(!config_dir_into_with(&MapEnv::new(), &mut buf));
+        assert_eq!(buf, PathBuf::new());
+        assert_eq!(buf.capacity(), capacity);
+    }
+}
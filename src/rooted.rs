@@ -0,0 +1,102 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::{CACHE_DIR, CONFIG_DIR, DATA_DIR};
+
+/// Returns the XDG config directory below the given home directory, `<home>/.config`.
+///
+/// No environment variable is read, the suffix is applied to `home` on every platform. This is
+/// meant for hosts without a conventional `$HOME`, e.g. a minimal embedded Linux that is
+/// provisioned with its base directory.
+pub fn config_dir_rooted(home: &Path) -> PathBuf {
+    home.join(CONFIG_DIR)
+}
+
+/// Returns the XDG data directory below the given home directory, `<home>/.local/share`.
+///
+/// See [`config_dir_rooted`] for details.
+pub fn data_dir_rooted(home: &Path) -> PathBuf {
+    home.join(DATA_DIR)
+}
+
+/// Returns the XDG cache directory below the given home directory, `<home>/.cache`.
+///
+/// See [`config_dir_rooted`] for details.
+pub fn cache_dir_rooted(home: &Path) -> PathBuf {
+    home.join(CACHE_DIR)
+}
+
+/// Returns the XDG config directory of `home`, `<home>/.config`, re-rooted under `root`, e.g.
+/// `/mnt/image/home/alice/.config` for the home `/home/alice` in an image mounted at `/mnt/image`.
+///
+/// The root of `home` is stripped before joining it to `root`, i.e. the leading `/` on Unix,
+/// and the drive or UNC prefix and root on Windows, e.g. `C:\Users\alice` becomes
+/// `<root>\Users\alice`. Like [`config_dir_rooted`], no environment variable is read.
+///
+/// Returns `None` if `home` has a `..` component, e.g. `/../../etc` read from the `/etc/passwd`
+/// of the image, which could lead out of `root`.
+pub fn config_dir_under(root: &Path, home: &Path) -> Option<PathBuf> {
+    let mut dir = root.to_path_buf();
+    for component in config_dir_rooted(home).components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => {}
+            Component::ParentDir => return None,
+            component => dir.push(component),
+        }
+    }
+    Some(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_xdg_suffixes_to_any_root() {
+        for root in ["/data/provisioned", "relative/root", "/"] {
+            let root = Path::new(root);
+            assert_eq!(config_dir_rooted(root), root.join(".config"));
+            assert_eq!(data_dir_rooted(root), root.join(".local").join("share"));
+            assert_eq!(cache_dir_rooted(root), root.join(".cache"));
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn config_dir_under_reroots_unix_home() {
+        assert_eq!(
+            config_dir_under(Path::new("/mnt/image"), Path::new("/home/alice")),
+            Some(PathBuf::from("/mnt/image/home/alice/.config"))
+        );
+        assert_eq!(
+            config_dir_under(Path::new("/mnt/image/"), Path::new("home/alice/")),
+            Some(PathBuf::from("/mnt/image/home/alice/.config"))
+        );
+    }
+
+    #[test]
+    fn config_dir_under_rejects_parent_components() {
+        for home in ["/../../etc", "/home/alice/../../../etc", "home/.."] {
+            assert_eq!(
+                config_dir_under(Path::new("/mnt/image"), Path::new(home)),
+                None,
+                "{home}"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn config_dir_under_reroots_windows_home() {
+        assert_eq!(
+            config_dir_under(Path::new(r"D:\mnt\image"), Path::new(r"C:\Users\alice")),
+            Some(PathBuf::from(r"D:\mnt\image\Users\alice\.config"))
+        );
+        assert_eq!(
+            config_dir_under(
+                Path::new(r"D:\mnt\image"),
+                Path::new(r"\\server\share\alice")
+            ),
+            Some(PathBuf::from(r"D:\mnt\image\alice\.config"))
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use std::path::PathBuf;
+
+use crate::{
+    DirError, DirLayout, DirSource, DirStyle, Env, ProcessEnv, cache_dir_styled_into,
+    config_dir_styled_into, data_dir_styled_into,
+};
+
+/// The directory convention a path was resolved with, see [`config_dir_with_scheme`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Scheme {
+    /// The XDG Base Directory convention, e.g. `$HOME/.config`, on Linux and the BSDs, or on
+    /// macOS if the feature `favor-xdg-style` is enabled.
+    Xdg,
+    /// The native macOS convention, e.g. `$HOME/Library/Application Support`.
+    MacNative,
+    /// A Windows known folder, e.g. `%APPDATA%`.
+    WindowsKnownFolder,
+    /// Any other source, i.e. `$DIRS_LITE_PREFIX` with the feature `global-prefix-env`, the
+    /// directory of a Snap or an AppImage, the portable style, or a platform with a convention
+    /// of its own like Haiku.
+    Other,
+}
+
+impl Scheme {
+    /// Returns the scheme of a directory resolved from `source` in `style`.
+    fn of(source: DirSource, style: DirStyle) -> Scheme {
+        match source {
+            DirSource::HostXdg | DirSource::Xdg | DirSource::XdgHome => Scheme::Xdg,
+            DirSource::Macos if style == DirStyle::Xdg => Scheme::Xdg,
+            DirSource::Macos => Scheme::MacNative,
+            DirSource::KnownFolder | DirSource::WindowsVar | DirSource::WindowsProfile => {
+                Scheme::WindowsKnownFolder
+            }
+            DirSource::GlobalPrefix
+            | DirSource::Portable
+            | DirSource::Snap
+            | DirSource::AppImage
+            | DirSource::Haiku => Scheme::Other,
+        }
+    }
+}
+
+/// Resolves a directory with `resolve` in the default style, together with its scheme.
+fn with_scheme(
+    resolve: impl FnOnce(DirStyle, &DirLayout, &mut PathBuf) -> Result<DirSource, DirError>,
+) -> Option<(PathBuf, Scheme)> {
+    let style = DirStyle::default();
+    let mut dir = PathBuf::new();
+    let source = resolve(style, &DirLayout::default(), &mut dir).ok()?;
+    Some((dir, Scheme::of(source, style)))
+}
+
+/// Returns [`config_dir`](crate::config_dir) together with the scheme that produced it, e.g. to
+/// log which convention is in effect.
+pub fn config_dir_with_scheme() -> Option<(PathBuf, Scheme)> {
+    config_dir_with_scheme_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_with_scheme`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_with_scheme_with<E: Env + ?Sized>(env: &E) -> Option<(PathBuf, Scheme)> {
+    with_scheme(|style, layout, buf| config_dir_styled_into(env, style, layout, buf))
+}
+
+/// Returns [`data_dir`](crate::data_dir) together with the scheme that produced it.
+pub fn data_dir_with_scheme() -> Option<(PathBuf, Scheme)> {
+    data_dir_with_scheme_with(&ProcessEnv)
+}
+
+/// Like [`data_dir_with_scheme`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_with_scheme_with<E: Env + ?Sized>(env: &E) -> Option<(PathBuf, Scheme)> {
+    with_scheme(|style, layout, buf| data_dir_styled_into(env, style, layout, buf))
+}
+
+/// Returns [`cache_dir`](crate::cache_dir) together with the scheme that produced it.
+pub fn cache_dir_with_scheme() -> Option<(PathBuf, Scheme)> {
+    cache_dir_with_scheme_with(&ProcessEnv)
+}
+
+/// Like [`cache_dir_with_scheme`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_with_scheme_with<E: Env + ?Sized>(env: &E) -> Option<(PathBuf, Scheme)> {
+    with_scheme(|style, layout, buf| cache_dir_styled_into(env, style, layout, buf))
+}
+
+#[cfg(all(
+    test,
+    any(target_os = "linux", target_os = "macos", target_os = "windows")
+))]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_scheme_is_xdg() {
+        let env = MapEnv::new().with("HOME", "/home/alice");
+        assert_eq!(
+            config_dir_with_scheme_with(&env),
+            Some((PathBuf::from("/home/alice/.config"), Scheme::Xdg))
+        );
+        assert_eq!(
+            cache_dir_with_scheme_with(&env).map(|(_, scheme)| scheme),
+            Some(Scheme::Xdg)
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", not(feature = "favor-xdg-style")))]
+    fn macos_scheme_is_native() {
+        let env = MapEnv::new().with("HOME", "/Users/Alice");
+        assert_eq!(
+            config_dir_with_scheme_with(&env),
+            Some((
+                PathBuf::from("/Users/Alice/Library/Application Support"),
+                Scheme::MacNative
+            ))
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "macos", feature = "favor-xdg-style"))]
+    fn macos_favor_xdg_style_scheme_is_xdg() {
+        let env = MapEnv::new().with("HOME", "/Users/Alice");
+        assert_eq!(
+            config_dir_with_scheme_with(&env),
+            Some((PathBuf::from("/Users/Alice/.config"), Scheme::Xdg))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_scheme_is_known_folder() {
+        let env = MapEnv::new().with("LOCALAPPDATA", r"C:\Users\Alice\AppData\Local");
+        assert_eq!(
+            data_dir_with_scheme_with(&env).map(|(_, scheme)| scheme),
+            Some(Scheme::WindowsKnownFolder)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "global-prefix-env")]
+    fn global_prefix_scheme_is_other() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/alice")
+            .with(crate::PREFIX_ENV, "/opt/app");
+        assert_eq!(
+            data_dir_with_scheme_with(&env).map(|(_, scheme)| scheme),
+            Some(Scheme::Other)
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", feature = "respect-snap"))]
+    fn linux_snap_scheme_is_other() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/alice")
+            .with("SNAP", "/snap/myapp/1")
+            .with("SNAP_USER_DATA", "/home/alice/snap/myapp/1");
+        assert_eq!(
+            config_dir_with_scheme_with(&env),
+            Some((
+                PathBuf::from("/home/alice/snap/myapp/1/.config"),
+                Scheme::Other
+            ))
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", feature = "appimage"))]
+    fn linux_appimage_scheme_is_other() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/alice")
+            .with("APPIMAGE", "/media/usb/MyApp.AppImage");
+        assert_eq!(
+            cache_dir_with_scheme_with(&env),
+            Some((PathBuf::from("/media/usb/.MyApp/cache"), Scheme::Other))
+        );
+    }
+
+    #[test]
+    fn portable_and_haiku_schemes_are_other() {
+        assert_eq!(
+            Scheme::of(DirSource::Portable, DirStyle::Portable),
+            Scheme::Other
+        );
+        assert_eq!(
+            Scheme::of(DirSource::Haiku, DirStyle::Native),
+            Scheme::Other
+        );
+        assert_eq!(Scheme::of(DirSource::Macos, DirStyle::Xdg), Scheme::Xdg);
+    }
+
+    #[test]
+    fn unresolved_dir_has_no_scheme() {
+        assert_eq!(config_dir_with_scheme_with(&MapEnv::new()), None);
+    }
+}
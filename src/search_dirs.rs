@@ -0,0 +1,172 @@
+use std::iter::FusedIterator;
+use std::path::PathBuf;
+
+use crate::{
+    Env, ProcessEnv, config_dir_with, data_dir_with, system_config_dirs_with, system_data_dirs_with,
+};
+
+/// Returns the config directories in the order they should be searched, the user's
+/// [`config_dir`](crate::config_dir) first, followed by the [`system_config_dirs`](crate::system_config_dirs).
+///
+/// ```
+/// let existing = dirs_lite::config_dirs()
+///     .map(|dir| dir.join("myapp/config.toml"))
+///     .find(|path| path.exists());
+/// ```
+pub fn config_dirs() -> ConfigDirs<'static, ProcessEnv> {
+    config_dirs_with(&ProcessEnv)
+}
+
+/// Like [`config_dirs`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dirs_with<E: Env + ?Sized>(env: &E) -> ConfigDirs<'_, E> {
+    ConfigDirs(SearchDirs::new(
+        env,
+        config_dir_with,
+        system_config_dirs_with,
+    ))
+}
+
+/// Returns the data directories in the order they should be searched, the user's
+/// [`data_dir`](crate::data_dir) first, followed by the [`system_data_dirs`](crate::system_data_dirs).
+pub fn data_dirs() -> DataDirs<'static, ProcessEnv> {
+    data_dirs_with(&ProcessEnv)
+}
+
+/// Like [`data_dirs`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dirs_with<E: Env + ?Sized>(env: &E) -> DataDirs<'_, E> {
+    DataDirs(SearchDirs::new(env, data_dir_with, system_data_dirs_with))
+}
+
+/// An iterator over the config directories, see [`config_dirs`].
+///
+/// The system config directories are only resolved once the user's config directory has
+/// been yielded, so stopping after it does not resolve them.
+#[derive(Debug)]
+pub struct ConfigDirs<'a, E: Env + ?Sized>(SearchDirs<'a, E>);
+
+/// An iterator over the data directories, see [`data_dirs`].
+///
+/// Like [`ConfigDirs`], the system data directories are only resolved when needed.
+#[derive(Debug)]
+pub struct DataDirs<'a, E: Env + ?Sized>(SearchDirs<'a, E>);
+
+/// The user directory resolved by `user`, followed by the system directories resolved by `system`.
+struct SearchDirs<'a, E: Env + ?Sized> {
+    env: &'a E,
+    user: Option<fn(&E) -> Option<PathBuf>>,
+    system: fn(&E) -> Vec<PathBuf>,
+    system_dirs: Option<std::vec::IntoIter<PathBuf>>,
+}
+
+impl<'a, E: Env + ?Sized> SearchDirs<'a, E> {
+    fn new(env: &'a E, user: fn(&E) -> Option<PathBuf>, system: fn(&E) -> Vec<PathBuf>) -> Self {
+        SearchDirs {
+            env,
+            user: Some(user),
+            system,
+            system_dirs: None,
+        }
+    }
+
+    fn next(&mut self) -> Option<PathBuf> {
+        if let Some(dir) = self.user.take().and_then(|user| user(self.env)) {
+            return Some(dir);
+        }
+        let (env, system) = (self.env, self.system);
+        self.system_dirs
+            .get_or_insert_with(|| system(env).into_iter())
+            .next()
+    }
+}
+
+impl<E: Env + ?Sized> std::fmt::Debug for SearchDirs<'_, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SearchDirs")
+            .field("user_pending", &self.user.is_some())
+            .field("system_dirs", &self.system_dirs)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E: Env + ?Sized> Iterator for ConfigDirs<'_, E> {
+    type Item = PathBuf;
+
+    fn next(&mut self) -> Option<PathBuf> {
+        self.0.next()
+    }
+}
+
+impl<E: Env + ?Sized> FusedIterator for ConfigDirs<'_, E> {}
+
+impl<E: Env + ?Sized> Iterator for DataDirs<'_, E> {
+    type Item = PathBuf;
+
+    fn next(&mut self) -> Option<PathBuf> {
+        self.0.next()
+    }
+}
+
+impl<E: Env + ?Sized> FusedIterator for DataDirs<'_, E> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_config_dirs_yield_user_dir_first() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CONFIG_DIRS", "/etc/xdg/custom:/opt/xdg");
+        assert_eq!(
+            config_dirs_with(&env).collect::<Vec<_>>(),
+            [
+                PathBuf::from("/home/testuser/.config"),
+                PathBuf::from("/etc/xdg/custom"),
+                PathBuf::from("/opt/xdg"),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", not(dirs_lite_custom_xdg_config_dirs)))]
+    fn linux_config_dirs_skip_unresolved_user_dir() {
+        let env = MapEnv::new();
+        assert_eq!(
+            config_dirs_with(&env).collect::<Vec<_>>(),
+            [PathBuf::from("/etc/xdg")]
+        );
+    }
+
+    #[test]
+    fn config_dirs_match_config_dir_and_system_config_dirs() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming")
+            .with("PROGRAMDATA", "C:\\ProgramData");
+        let expected = config_dir_with(&env)
+            .into_iter()
+            .chain(system_config_dirs_with(&env))
+            .collect::<Vec<_>>();
+        let mut dirs = config_dirs_with(&env);
+        assert_eq!(dirs.by_ref().collect::<Vec<_>>(), expected);
+        assert_eq!(dirs.next(), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_data_dirs_yield_user_dir_first() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_DATA_DIRS", "/opt/share:/usr/share");
+        assert_eq!(
+            data_dirs_with(&env).collect::<Vec<_>>(),
+            [
+                PathBuf::from("/home/testuser/.local/share"),
+                PathBuf::from("/opt/share"),
+                PathBuf::from("/usr/share"),
+            ]
+        );
+    }
+}
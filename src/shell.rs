@@ -0,0 +1,98 @@
+use crate::{Env, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with};
+
+/// A shell [`export_script`] can emit statements for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+}
+
+impl Shell {
+    fn export(self, key: &str, value: &str) -> String {
+        match self {
+            Shell::Bash | Shell::Zsh => {
+                format!("export {key}='{}'\n", value.replace('\'', r"'\''"))
+            }
+            Shell::Fish => format!(
+                "set -gx {key} '{}'\n",
+                value.replace('\\', r"\\").replace('\'', r"\'")
+            ),
+            Shell::PowerShell => format!("$env:{key} = '{}'\n", value.replace('\'', "''")),
+        }
+    }
+}
+
+/// Returns a script that exports `XDG_CONFIG_HOME`, `XDG_DATA_HOME` and `XDG_CACHE_HOME`
+/// set to [`config_dir`](crate::config_dir), [`data_dir`](crate::data_dir) and
+/// [`cache_dir`](crate::cache_dir) in the syntax of the given `shell`.
+///
+/// Meant for `eval "$(mytool dirs --export)"`. The values are quoted so paths containing
+/// spaces or quotes survive. Directories that cannot be resolved, or that are not valid
+/// UTF-8, are left out.
+pub fn export_script(shell: Shell) -> String {
+    export_script_with(&ProcessEnv, shell)
+}
+
+/// Like [`export_script`], but resolves against the given environment instead of the one of the current process.
+pub fn export_script_with<E: Env + ?Sized>(env: &E, shell: Shell) -> String {
+    [
+        ("XDG_CONFIG_HOME", config_dir_with(env)),
+        ("XDG_DATA_HOME", data_dir_with(env)),
+        ("XDG_CACHE_HOME", cache_dir_with(env)),
+    ]
+    .into_iter()
+    .filter_map(|(key, dir)| Some(shell.export(key, dir?.to_str()?)))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bash_quotes_path_with_space() {
+        assert_eq!(
+            Shell::Bash.export("XDG_CONFIG_HOME", "/home/alice/my config"),
+            "export XDG_CONFIG_HOME='/home/alice/my config'\n"
+        );
+        assert_eq!(
+            Shell::Bash.export("XDG_CONFIG_HOME", "/home/alice/it's"),
+            "export XDG_CONFIG_HOME='/home/alice/it'\\''s'\n"
+        );
+    }
+
+    #[test]
+    fn fish_quotes_path_with_space() {
+        assert_eq!(
+            Shell::Fish.export("XDG_CONFIG_HOME", "/home/alice/my config"),
+            "set -gx XDG_CONFIG_HOME '/home/alice/my config'\n"
+        );
+        assert_eq!(
+            Shell::Fish.export("XDG_CONFIG_HOME", r"/home/alice/it's\"),
+            "set -gx XDG_CONFIG_HOME '/home/alice/it\\'s\\\\'\n"
+        );
+    }
+
+    #[test]
+    fn powershell_doubles_single_quotes() {
+        assert_eq!(
+            Shell::PowerShell.export("XDG_CONFIG_HOME", r"C:\Users\O'Brien"),
+            "$env:XDG_CONFIG_HOME = 'C:\\Users\\O''Brien'\n"
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_export_script_with_space_in_home() {
+        let env = crate::MapEnv::new().with("HOME", "/home/alice smith");
+
+        assert_eq!(
+            export_script_with(&env, Shell::Bash),
+            "export XDG_CONFIG_HOME='/home/alice smith/.config'\n\
+             export XDG_DATA_HOME='/home/alice smith/.local/share'\n\
+             export XDG_CACHE_HOME='/home/alice smith/.cache'\n"
+        );
+    }
+}
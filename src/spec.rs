@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+
+use crate::{DirKind, Env, home_dir_result_with};
+
+/// How a single directory is resolved on a [`PlatformSpec`].
+///
+/// The directory is the value of the environment variable `env`, if it is set and not
+/// empty, otherwise the home directory with `home` appended. Either may be omitted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct DirRule {
+    /// The environment variable holding the directory, e.g. `XDG_CONFIG_HOME`.
+    pub env: Option<String>,
+    /// The path relative to the home directory, e.g. `.config`.
+    pub home: Option<String>,
+}
+
+/// A platform layout described as data, to resolve directories for platforms this
+/// crate has no built-in convention for.
+///
+/// With the feature `serde` enabled, a spec can be loaded from a fixture, e.g. in TOML:
+///
+/// ```toml
+/// [config]
+/// env = "XDG_CONFIG_HOME"
+/// home = ".config"
+///
+/// [cache]
+/// home = ".cache"
+/// ```
+///
+/// Directories without a rule are not resolved.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct PlatformSpec {
+    /// See [`config_dir`](crate::config_dir).
+    pub config: Option<DirRule>,
+    /// See [`data_dir`](crate::data_dir).
+    pub data: Option<DirRule>,
+    /// See [`cache_dir`](crate::cache_dir).
+    pub cache: Option<DirRule>,
+    /// See [`state_dir`](crate::state_dir).
+    pub state: Option<DirRule>,
+    /// See [`runtime_dir`](crate::runtime_dir).
+    pub runtime: Option<DirRule>,
+}
+
+impl PlatformSpec {
+    /// Returns the rule for the given kind of directory, if any.
+    pub fn rule(&self, kind: DirKind) -> Option<&DirRule> {
+        match kind {
+            DirKind::Config => self.config.as_ref(),
+            DirKind::Data => self.data.as_ref(),
+            DirKind::Cache => self.cache.as_ref(),
+            DirKind::State => self.state.as_ref(),
+            DirKind::Runtime => self.runtime.as_ref(),
+        }
+    }
+}
+
+/// Resolves the directory of the given kind following `spec`, against the given environment.
+///
+/// Returns `None` if `spec` has no rule for `kind`, or neither the variable of the rule nor
+/// the home directory is available.
+pub fn resolve_with_spec<E: Env + ?Sized>(
+    spec: &PlatformSpec,
+    kind: DirKind,
+    env: &E,
+) -> Option<PathBuf> {
+    let rule = spec.rule(kind)?;
+    if let Some(dir) = rule
+        .env
+        .as_deref()
+        .and_then(|key| env.var_os(key))
+        .filter(|dir| !dir.is_empty())
+    {
+        return Some(PathBuf::from(dir));
+    }
+    let subdir = rule.home.as_deref()?;
+    home_dir_result_with(env).ok().map(|home| home.join(subdir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    fn xdg_config_spec() -> PlatformSpec {
+        PlatformSpec {
+            config: Some(DirRule {
+                env: Some("XDG_CONFIG_HOME".into()),
+                home: Some(".config".into()),
+            }),
+            ..PlatformSpec::default()
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn resolve_with_spec_falls_back_to_home() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(
+            resolve_with_spec(&xdg_config_spec(), DirKind::Config, &env),
+            Some(PathBuf::from("/home/testuser/.config"))
+        );
+    }
+
+    #[test]
+    fn resolve_with_spec_prefers_env() {
+        let env = MapEnv::new().with("XDG_CONFIG_HOME", "/custom/config");
+        assert_eq!(
+            resolve_with_spec(&xdg_config_spec(), DirKind::Config, &env),
+            Some(PathBuf::from("/custom/config"))
+        );
+        assert_eq!(
+            resolve_with_spec(&xdg_config_spec(), DirKind::Cache, &env),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde", not(target_os = "windows")))]
+    fn resolve_with_deserialized_spec() {
+        const FIXTURE: &str = r#"{
+            "config": { "env": "PLAN9_CONFIG", "home": "lib" },
+            "cache": { "home": "tmp/cache" }
+        }"#;
+        let spec: PlatformSpec = serde_json::from_str(FIXTURE).unwrap();
+        let env = MapEnv::new().with("HOME", "/usr/glenda");
+
+        assert_eq!(
+            resolve_with_spec(&spec, DirKind::Config, &env),
+            Some(PathBuf::from("/usr/glenda/lib"))
+        );
+        assert_eq!(
+            resolve_with_spec(&spec, DirKind::Cache, &env),
+            Some(PathBuf::from("/usr/glenda/tmp/cache"))
+        );
+        assert_eq!(resolve_with_spec(&spec, DirKind::Data, &env), None);
+    }
+}
@@ -0,0 +1,45 @@
+//! The config, data and cache directories as `&'static Path`, resolved once per process:
+//!
+//! ```
+//! use dirs_lite::static_dirs;
+//!
+//! if let Some(config) = static_dirs::config_dir() {
+//!     println!("{}", config.display());
+//! }
+//! ```
+//!
+//! These are the functions [`config_dir_cached`](crate::config_dir_cached),
+//! [`data_dir_cached`](crate::data_dir_cached) and [`cache_dir_cached`](crate::cache_dir_cached)
+//! under the names of the plain functions, so a long-running service can swap its imports to
+//! avoid allocating on every call. They share the same storage, so both return the same
+//! reference.
+//!
+//! The resolved paths are intentionally kept in memory for the lifetime of the process and
+//! never freed. The first call snapshots the environment, later changes to it are not picked up
+//! until [`invalidate_cache`](crate::invalidate_cache) is called.
+
+pub use crate::cache_dir_cached as cache_dir;
+pub use crate::config_dir_cached as config_dir;
+pub use crate::data_dir_cached as data_dir;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn same(first: Option<&Path>, second: Option<&Path>) -> bool {
+        match (first, second) {
+            (Some(first), Some(second)) => std::ptr::eq(first, second),
+            (first, second) => first == second,
+        }
+    }
+
+    #[test]
+    fn repeated_calls_return_the_same_reference() {
+        assert!(same(config_dir(), config_dir()));
+        assert!(same(data_dir(), data_dir()));
+        assert!(same(cache_dir(), cache_dir()));
+        assert!(same(config_dir(), crate::config_dir_cached()));
+    }
+}
@@ -0,0 +1,109 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::{Env, ProcessEnv, config_dir_with};
+
+/// Returns `relative` joined to [`config_dir`](crate::config_dir), or `None` if it would
+/// escape the config directory or the config directory cannot be resolved.
+///
+/// This is meant for names from untrusted sources, e.g. plugin names read from a manifest.
+/// `relative` is rejected if it contains a `..` component, is absolute, or on Windows has a
+/// drive or UNC prefix like `C:\` or `\\server\share`. The longest existing ancestor of the
+/// joined path is canonicalized as well, and the path is rejected unless that ancestor is still
+/// inside the canonicalized config directory, so that a symlink within it cannot lead out of it,
+/// also for a file that is yet to be created below the symlink.
+///
+/// ```
+/// use std::path::Path;
+///
+/// assert_eq!(dirs_lite::config_subpath(Path::new("../.ssh")), None);
+/// ```
+pub fn config_subpath(relative: &Path) -> Option<PathBuf> {
+    config_subpath_with(&ProcessEnv, relative)
+}
+
+/// Like [`config_subpath`], but resolves against the given environment instead of the one of the current process.
+pub fn config_subpath_with<E: Env + ?Sized>(env: &E, relative: &Path) -> Option<PathBuf> {
+    if !is_confined(relative) {
+        return None;
+    }
+    let base = config_dir_with(env)?;
+    let path = base.join(relative);
+    // Nothing below the base exists yet, so there is no symlink to follow
+    let Some(existing) = path
+        .ancestors()
+        .take_while(|ancestor| ancestor.starts_with(&base))
+        .find(|ancestor| fs::symlink_metadata(ancestor).is_ok())
+    else {
+        return Some(path);
+    };
+    let canonical = fs::canonicalize(existing).ok()?;
+    canonical
+        .starts_with(fs::canonicalize(&base).ok()?)
+        .then_some(path)
+}
+
+/// Returns whether `relative` stays below the directory it is joined to, judging by its
+/// components alone.
+pub(crate) fn is_confined(relative: &Path) -> bool {
+    relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_escaping_components() {
+        assert!(is_confined(Path::new("myplugin")));
+        assert!(is_confined(Path::new("plugins/./myplugin/settings.toml")));
+        assert!(!is_confined(Path::new("../.ssh")));
+        assert!(!is_confined(Path::new("plugins/../../.ssh")));
+        assert!(!is_confined(Path::new("/etc/passwd")));
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn windows_rejects_prefixes() {
+        assert!(!is_confined(Path::new(r"C:\Windows")));
+        assert!(!is_confined(Path::new("C:Windows")));
+        assert!(!is_confined(Path::new(r"\\server\share\plugin")));
+        assert!(!is_confined(Path::new(r"\Windows")));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_config_subpath_stays_in_config_dir() {
+        let home = tempfile::tempdir().unwrap();
+        let config = home.path().join(".config");
+        let env = crate::MapEnv::new().with("HOME", home.path());
+
+        assert_eq!(
+            config_subpath_with(&env, Path::new("plugins/myplugin")),
+            Some(config.join("plugins/myplugin"))
+        );
+        assert_eq!(config_subpath_with(&env, Path::new("../.ssh")), None);
+        assert_eq!(config_subpath_with(&env, Path::new("/etc/passwd")), None);
+
+        // An existing symlink leading out of the config dir is rejected
+        fs::create_dir_all(&config).unwrap();
+        std::os::unix::fs::symlink(home.path(), config.join("escape")).unwrap();
+        assert_eq!(config_subpath_with(&env, Path::new("escape")), None);
+        assert_eq!(config_subpath_with(&env, Path::new("escape/newfile")), None);
+        assert_eq!(
+            config_subpath_with(&env, Path::new("escape/new/nested")),
+            None
+        );
+        fs::create_dir(config.join("inside")).unwrap();
+        assert_eq!(
+            config_subpath_with(&env, Path::new("inside")),
+            Some(config.join("inside"))
+        );
+        assert_eq!(
+            config_subpath_with(&env, Path::new("inside/newfile")),
+            Some(config.join("inside/newfile"))
+        );
+    }
+}
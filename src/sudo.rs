@@ -0,0 +1,165 @@
+use std::path::PathBuf;
+
+use crate::{Env, MapEnv, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with};
+
+/// Returns the path to the config directory of the user who invoked `sudo`.
+///
+/// If `$SUDO_USER` is set on Unix, the home directory of that user is looked up in the
+/// password database and the config directory is derived from it as [`config_dir`](crate::config_dir)
+/// would, e.g. `/home/alice/.config` instead of `/root/.config`. The `$XDG_*` variables of the
+/// current process are ignored in that case, since they belong to the elevated user. Returns
+/// `None` if the user cannot be looked up, rather than falling back to the elevated user's
+/// directory. Without `$SUDO_USER`, and on Windows, this is the same as [`config_dir`](crate::config_dir).
+///
+/// NOTE: the lookup requires the feature `passwd-fallback`, without it every user is unknown.
+///
+/// NOTE: `$SUDO_USER` is set by the caller and not verified. A process running as root that
+/// writes to the returned directory acts on another user's files, so take care to not
+/// create files owned by root in there.
+pub fn config_dir_for_invoking_user() -> Option<PathBuf> {
+    config_dir_for_invoking_user_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_for_invoking_user`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_for_invoking_user_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    let sudo_user = env.var_os("SUDO_USER").filter(|user| !user.is_empty());
+    match sudo_user {
+        Some(user) if cfg!(unix) => config_dir_for_user_with(env, user.to_str()?),
+        _ => config_dir_with(env),
+    }
+}
+
+/// Returns the path to the config directory of the user `name`, e.g. to read the configs of
+/// all accounts in a backup sweep.
+///
+/// The home directory of the user is looked up in the password database on Unix, and the
+/// config directory is derived from it as [`config_dir`](crate::config_dir) would. The
+/// `$XDG_*` variables of the current process are ignored, since they belong to the current
+/// user. Returns `None` if the user is unknown, and on Windows.
+///
+/// NOTE: the lookup requires the feature `passwd-fallback`, without it every user is unknown.
+pub fn config_dir_for_user(name: &str) -> Option<PathBuf> {
+    config_dir_for_user_with(&ProcessEnv, name)
+}
+
+/// Like [`config_dir_for_user`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_for_user_with<E: Env + ?Sized>(env: &E, name: &str) -> Option<PathBuf> {
+    config_dir_with(&user_env(env, name)?)
+}
+
+/// Returns the path to the data directory of the user `name`, see [`config_dir_for_user`].
+pub fn data_dir_for_user(name: &str) -> Option<PathBuf> {
+    data_dir_for_user_with(&ProcessEnv, name)
+}
+
+/// Like [`data_dir_for_user`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_for_user_with<E: Env + ?Sized>(env: &E, name: &str) -> Option<PathBuf> {
+    data_dir_with(&user_env(env, name)?)
+}
+
+/// Returns the path to the cache directory of the user `name`, see [`config_dir_for_user`].
+pub fn cache_dir_for_user(name: &str) -> Option<PathBuf> {
+    cache_dir_for_user_with(&ProcessEnv, name)
+}
+
+/// Like [`cache_dir_for_user`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_for_user_with<E: Env + ?Sized>(env: &E, name: &str) -> Option<PathBuf> {
+    cache_dir_with(&user_env(env, name)?)
+}
+
+/// Returns an environment with only `$HOME` set, to the home directory of the user `name`.
+fn user_env<E: Env + ?Sized>(env: &E, name: &str) -> Option<MapEnv> {
+    if !cfg!(unix) {
+        return None;
+    }
+    let home = env.user_home(name)?;
+    Some(MapEnv::new().with("HOME", home))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    struct PasswdEnv(MapEnv);
+
+    impl Env for PasswdEnv {
+        fn var_os(&self, key: &str) -> Option<std::ffi::OsString> {
+            self.0.var_os(key)
+        }
+
+        fn user_home(&self, name: &str) -> Option<PathBuf> {
+            (name == "alice").then(|| PathBuf::from("/home/alice"))
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_config_dir_for_invoking_user_uses_sudo_user() {
+        let env = PasswdEnv(
+            MapEnv::new()
+                .with("HOME", "/root")
+                .with("XDG_CONFIG_HOME", "/root/.custom")
+                .with("SUDO_USER", "alice"),
+        );
+        assert_eq!(
+            config_dir_for_invoking_user_with(&env),
+            Some(PathBuf::from("/home/alice/.config"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_dirs_for_user_use_their_home() {
+        let env = PasswdEnv(
+            MapEnv::new()
+                .with("HOME", "/root")
+                .with("XDG_DATA_HOME", "/root/.data"),
+        );
+        assert_eq!(
+            config_dir_for_user_with(&env, "alice"),
+            Some(PathBuf::from("/home/alice/.config"))
+        );
+        assert_eq!(
+            data_dir_for_user_with(&env, "alice"),
+            Some(PathBuf::from("/home/alice/.local/share"))
+        );
+        assert_eq!(
+            cache_dir_for_user_with(&env, "alice"),
+            Some(PathBuf::from("/home/alice/.cache"))
+        );
+        assert_eq!(config_dir_for_user_with(&env, "mallory"), None);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", feature = "passwd-fallback"))]
+    fn linux_config_dir_for_current_user_matches_config_dir() {
+        let name = crate::unix::current_user_name().expect("current user has a passwd entry");
+        let home = crate::unix::passwd_home().unwrap();
+        // SAFETY: Tests run single-threaded with --test-threads=1
+        let _env = unsafe {
+            crate::env_guard::EnvGuard::new()
+                .set("HOME", home)
+                .remove("XDG_CONFIG_HOME")
+        };
+        assert_eq!(config_dir_for_user(&name), crate::config_dir());
+    }
+
+    #[test]
+    fn config_dir_for_invoking_user_rejects_unknown_sudo_user() {
+        let env = PasswdEnv(
+            MapEnv::new()
+                .with("HOME", "/root")
+                .with("SUDO_USER", "mallory"),
+        );
+        assert_eq!(config_dir_for_invoking_user_with(&env), None);
+    }
+
+    #[test]
+    fn config_dir_for_invoking_user_without_sudo_user_matches_config_dir() {
+        let env = PasswdEnv(MapEnv::new().with("HOME", "/root").with("SUDO_USER", ""));
+        assert_eq!(
+            config_dir_for_invoking_user_with(&env),
+            config_dir_with(&env)
+        );
+    }
+}
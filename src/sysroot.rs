@@ -0,0 +1,133 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::{
+    Env, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with, home_dir_with,
+    runtime_dir_with, state_dir_with,
+};
+
+/// Directories resolved as usual, but placed inside a target root, see [`with_sysroot`].
+#[derive(Debug, Clone)]
+pub struct Sysroot<E = ProcessEnv> {
+    root: PathBuf,
+    env: E,
+}
+
+/// Resolves directories as if the file system of the current user was mounted at `root`.
+///
+/// This is meant for installers and image builders operating on a target root, e.g. with
+/// `root` being `/mnt/target` and `$HOME` being `/home/alice`, the config directory on Linux
+/// is `/mnt/target/home/alice/.config`. Resolved paths never escape `root`, `..` components
+/// stop at it.
+///
+/// ```
+/// use std::path::Path;
+///
+/// let target = dirs_lite::with_sysroot(Path::new("/mnt/target"));
+/// if let Some(config) = target.config_dir() {
+///     assert!(config.starts_with("/mnt/target"));
+/// }
+/// ```
+pub fn with_sysroot(root: &Path) -> Sysroot {
+    Sysroot {
+        root: root.to_path_buf(),
+        env: ProcessEnv,
+    }
+}
+
+impl<E: Env> Sysroot<E> {
+    /// Resolves against the given environment instead of the one of the current process.
+    pub fn with_env<F: Env>(self, env: F) -> Sysroot<F> {
+        Sysroot {
+            root: self.root,
+            env,
+        }
+    }
+
+    /// Returns the target root.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns [`home_dir`](crate::home_dir) inside the target root.
+    pub fn home_dir(&self) -> Option<PathBuf> {
+        home_dir_with(&self.env).map(|dir| self.rebase(&dir))
+    }
+
+    /// Returns [`config_dir`](crate::config_dir) inside the target root.
+    pub fn config_dir(&self) -> Option<PathBuf> {
+        config_dir_with(&self.env).map(|dir| self.rebase(&dir))
+    }
+
+    /// Returns [`data_dir`](crate::data_dir) inside the target root.
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        data_dir_with(&self.env).map(|dir| self.rebase(&dir))
+    }
+
+    /// Returns [`cache_dir`](crate::cache_dir) inside the target root.
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        cache_dir_with(&self.env).map(|dir| self.rebase(&dir))
+    }
+
+    /// Returns [`state_dir`](crate::state_dir) inside the target root.
+    pub fn state_dir(&self) -> Option<PathBuf> {
+        state_dir_with(&self.env).map(|dir| self.rebase(&dir))
+    }
+
+    /// Returns [`runtime_dir`](crate::runtime_dir) inside the target root.
+    pub fn runtime_dir(&self) -> Option<PathBuf> {
+        runtime_dir_with(&self.env).map(|dir| self.rebase(&dir))
+    }
+
+    /// Joins `path` onto the root, dropping its prefix and root and keeping `..` within the root.
+    fn rebase(&self, path: &Path) -> PathBuf {
+        let mut rebased = self.root.clone();
+        let mut depth = 0;
+        for component in path.components() {
+            match component {
+                Component::Normal(part) => {
+                    rebased.push(part);
+                    depth += 1;
+                }
+                Component::ParentDir if depth > 0 => {
+                    rebased.pop();
+                    depth -= 1;
+                }
+                Component::Prefix(_)
+                | Component::RootDir
+                | Component::CurDir
+                | Component::ParentDir => {}
+            }
+        }
+        rebased
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_sysroot_prefixes_resolved_dirs() {
+        let target = with_sysroot(Path::new("/mnt/target"))
+            .with_env(crate::MapEnv::new().with("HOME", "/home/alice"));
+
+        assert_eq!(
+            target.home_dir(),
+            Some(PathBuf::from("/mnt/target/home/alice"))
+        );
+        assert_eq!(
+            target.config_dir(),
+            Some(PathBuf::from("/mnt/target/home/alice/.config"))
+        );
+    }
+
+    #[test]
+    fn sysroot_does_not_escape_root() {
+        let target = with_sysroot(Path::new("/mnt/target"));
+        assert_eq!(
+            target.rebase(Path::new("/home/../../../etc")),
+            PathBuf::from("/mnt/target/etc")
+        );
+    }
+}
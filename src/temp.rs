@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use crate::{Env, ProcessEnv};
+
+/// The variables pointing to the temporary directory, in the order they are consulted.
+const TEMP_VARS: [&str; 3] = ["TMPDIR", "TMP", "TEMP"];
+
+/// Returns the path to the system's temporary directory.
+///
+/// The first one of `$TMPDIR`, `$TMP` and `$TEMP` set to an absolute path is used, falling back
+/// to [`std::env::temp_dir`], e.g. `/tmp` on Linux. Temporary files below a relative path would
+/// depend on the working directory, so an empty or relative value counts as unset.
+///
+/// # Panics
+///
+/// Like [`std::env::temp_dir`], on targets without a file system, e.g. `wasm32-unknown-unknown`,
+/// if none of the variables is set.
+pub fn temp_dir() -> PathBuf {
+    temp_dir_with(&ProcessEnv)
+}
+
+/// Like [`temp_dir`], but resolves against the given environment instead of the one of the current process.
+///
+/// The fallback is still [`std::env::temp_dir`], which reads the environment of the current process.
+pub fn temp_dir_with<E: Env + ?Sized>(env: &E) -> PathBuf {
+    TEMP_VARS
+        .iter()
+        .filter_map(|key| env.var_os(key))
+        .map(PathBuf::from)
+        .find(|dir| dir.is_absolute())
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+/// Returns the path to a temporary directory of the application `app`, i.e. [`temp_dir`] with
+/// `app` appended.
+///
+/// The directory is not created.
+pub fn app_temp_dir(app: &str) -> PathBuf {
+    app_temp_dir_with(&ProcessEnv, app)
+}
+
+/// Like [`app_temp_dir`], but resolves against the given environment instead of the one of the current process.
+pub fn app_temp_dir_with<E: Env + ?Sized>(env: &E, app: &str) -> PathBuf {
+    temp_dir_with(env).join(app)
+}
+
+#[cfg(all(test, not(target_family = "wasm")))]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    fn temp_dir_uses_tmpdir() {
+        // An absolute path on every platform
+        let custom = std::env::temp_dir().join("custom");
+        let env = MapEnv::new()
+            .with("TMPDIR", &custom)
+            .with("TMP", custom.join("other"));
+        assert_eq!(temp_dir_with(&env), custom);
+        assert_eq!(app_temp_dir_with(&env, "myapp"), custom.join("myapp"));
+    }
+
+    #[test]
+    fn temp_dir_skips_empty_and_relative_vars() {
+        let custom = std::env::temp_dir().join("custom");
+        let env = MapEnv::new()
+            .with("TMPDIR", "")
+            .with("TMP", "relative/tmp")
+            .with("TEMP", &custom);
+        assert_eq!(temp_dir_with(&env), custom);
+    }
+
+    #[test]
+    fn temp_dir_falls_back_to_std() {
+        let env = MapEnv::new().with("TMPDIR", "");
+        assert_eq!(temp_dir_with(&env), std::env::temp_dir());
+
+        let env = MapEnv::new().with("TMPDIR", "tmp");
+        assert_eq!(temp_dir_with(&env), std::env::temp_dir());
+    }
+}
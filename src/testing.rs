@@ -0,0 +1,53 @@
+//! Helpers for crates testing their own use of dirs-lite, without touching the environment of
+//! the test process.
+//!
+//! ```
+//! # #[cfg(target_os = "linux")]
+//! dirs_lite::testing::assert_data_dir(&[("HOME", "/home/alice")], "/home/alice/.local/share");
+//! ```
+//!
+//! NOTE: requires the feature `testing`.
+
+use std::path::PathBuf;
+
+use crate::{MapEnv, data_dir_with};
+
+/// Asserts that [`data_dir`](crate::data_dir) resolves to `expected` in an environment holding
+/// only the variables `env`.
+///
+/// # Panics
+///
+/// If the data directory resolves to a different path or cannot be resolved, with a message
+/// showing the environment and both paths.
+#[track_caller]
+pub fn assert_data_dir(env: &[(&str, &str)], expected: &str) {
+    let actual = data_dir_with(&env.iter().copied().collect::<MapEnv>());
+    let expected = PathBuf::from(expected);
+    if actual.as_ref() != Some(&expected) {
+        panic!(
+            "data dir mismatch in environment {env:?}\n  expected: {}\n    actual: {}",
+            expected.display(),
+            actual.map_or_else(|| "None".into(), |dir| dir.display().to_string())
+        );
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linux_asserts_xdg_data_dir() {
+        assert_data_dir(&[("HOME", "/home/alice")], "/home/alice/.local/share");
+        assert_data_dir(
+            &[("HOME", "/home/alice"), ("XDG_DATA_HOME", "/srv/data")],
+            "/srv/data",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "data dir mismatch")]
+    fn linux_panics_on_mismatch() {
+        assert_data_dir(&[("HOME", "/home/alice")], "/home/bob/.local/share");
+    }
+}
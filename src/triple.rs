@@ -0,0 +1,110 @@
+use std::path::PathBuf;
+
+use crate::{
+    CACHE_DIR, CONFIG_DIR, DATA_DIR, DirKind, DirStyle, Env, ProcessEnv, cache_dir_with,
+    config_dir_with, data_dir_with, home_dir_result_with, is_xdg_platform, traced, xdg_var,
+};
+
+/// Returns [`config_dir`](crate::config_dir), [`data_dir`](crate::data_dir) and
+/// [`cache_dir`](crate::cache_dir) at once, or `None` if any of them cannot be resolved.
+///
+/// On Linux and the BSDs, the home directory is read only once for all three and shared
+/// between the directories that fall back to it, which saves work at the startup of an
+/// application that needs all of them. Elsewhere, and with the features `global-prefix-env`,
+/// `respect-snap`, `appimage` or `container-host`, this is the same as calling the three
+/// functions.
+pub fn resolve_triple() -> Option<(PathBuf, PathBuf, PathBuf)> {
+    resolve_triple_with(&ProcessEnv)
+}
+
+/// Like [`resolve_triple`], but resolves against the given environment instead of the one of the current process.
+pub fn resolve_triple_with<E: Env + ?Sized>(env: &E) -> Option<(PathBuf, PathBuf, PathBuf)> {
+    if !is_xdg_platform()
+        || cfg!(any(
+            feature = "global-prefix-env",
+            feature = "respect-snap",
+            feature = "appimage",
+            feature = "container-host"
+        ))
+    {
+        return Some((
+            config_dir_with(env)?,
+            data_dir_with(env)?,
+            cache_dir_with(env)?,
+        ));
+    }
+
+    let [config, data, cache] =
+        ["XDG_CONFIG_HOME", "XDG_DATA_HOME", "XDG_CACHE_HOME"].map(|key| xdg_var(env, key).ok());
+    let mut missing = [&config, &data, &cache]
+        .iter()
+        .filter(|dir| dir.is_none())
+        .count();
+    let mut home = if missing > 0 {
+        Some(home_dir_result_with(env).ok()?)
+    } else {
+        None
+    };
+    let mut resolve = |kind: DirKind, dir: Option<PathBuf>, subdir: &str| {
+        let dir = dir.unwrap_or_else(|| {
+            missing -= 1;
+            // The last directory falling back to the home directory takes it over
+            let mut dir = if missing == 0 {
+                home.take()
+            } else {
+                home.clone()
+            }
+            .unwrap_or_default();
+            dir.push(subdir);
+            dir
+        });
+        traced(kind, DirStyle::default(), Ok(dir)).ok()
+    };
+    let config = resolve(DirKind::Config, config, CONFIG_DIR)?;
+    let data = resolve(DirKind::Data, data, DATA_DIR)?;
+    let cache = resolve(DirKind::Cache, cache, CACHE_DIR)?;
+    Some((config, data, cache))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triple_matches_individual_dirs() {
+        let individual = crate::config_dir()
+            .zip(crate::data_dir())
+            .zip(crate::cache_dir())
+            .map(|((config, data), cache)| (config, data, cache));
+        assert_eq!(resolve_triple(), individual);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_triple_matches_individual_dirs() {
+        use crate::MapEnv;
+
+        let envs = [
+            MapEnv::new(),
+            MapEnv::new().with("HOME", "/home/alice"),
+            MapEnv::new()
+                .with("HOME", "/home/alice")
+                .with("XDG_DATA_HOME", "/srv/data"),
+            MapEnv::new()
+                .with("XDG_CONFIG_HOME", "/etc/alice")
+                .with("XDG_DATA_HOME", "/srv/data")
+                .with("XDG_CACHE_HOME", "relative/cache"),
+            MapEnv::new()
+                .with("XDG_CONFIG_HOME", "/etc/alice")
+                .with("XDG_DATA_HOME", "/srv/data")
+                .with("XDG_CACHE_HOME", "/var/cache/alice"),
+        ];
+        for env in envs {
+            let individual = config_dir_with(&env)
+                .zip(data_dir_with(&env))
+                .zip(cache_dir_with(&env))
+                .map(|((config, data), cache)| (config, data, cache));
+            assert_eq!(resolve_triple_with(&env), individual, "{env:?}");
+        }
+    }
+}
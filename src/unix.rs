@@ -0,0 +1,162 @@
+#[cfg(feature = "passwd-fallback")]
+use std::ffi::{CStr, CString, OsString};
+#[cfg(feature = "passwd-fallback")]
+use std::os::unix::ffi::OsStringExt;
+#[cfg(feature = "checked-runtime-dir")]
+use std::path::Path;
+#[cfg(feature = "passwd-fallback")]
+use std::path::PathBuf;
+
+/// The buffer size to start with if `sysconf` does not suggest one.
+#[cfg(feature = "passwd-fallback")]
+const INITIAL_BUFFER_SIZE: usize = 1024;
+
+/// The buffer size to give up at, a password entry is never expected to be this large.
+#[cfg(feature = "passwd-fallback")]
+const MAX_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Returns the home directory of the current user from the password database, or `None`
+/// if there is no entry or it has no absolute home directory.
+#[cfg(feature = "passwd-fallback")]
+pub(crate) fn passwd_home() -> Option<PathBuf> {
+    // SAFETY: `getuid` always succeeds.
+    let uid = unsafe { libc::getuid() };
+    lookup_home(|passwd, buffer, size, result| {
+        // SAFETY: The caller passes pointers valid for writes, and `buffer` is `size` bytes long.
+        unsafe { libc::getpwuid_r(uid, passwd, buffer, size, result) }
+    })
+}
+
+/// Returns the home directory of the user `name` from the password database, or `None`
+/// if there is no such user or it has no absolute home directory.
+#[cfg(feature = "passwd-fallback")]
+pub(crate) fn user_home(name: &str) -> Option<PathBuf> {
+    let name = CString::new(name).ok()?;
+    lookup_home(|passwd, buffer, size, result| {
+        // SAFETY: `name` is null-terminated, the caller passes pointers valid for writes, and
+        // `buffer` is `size` bytes long.
+        unsafe { libc::getpwnam_r(name.as_ptr(), passwd, buffer, size, result) }
+    })
+}
+
+/// Returns the name of the current user from the password database.
+#[cfg(all(test, target_os = "linux", feature = "passwd-fallback"))]
+pub(crate) fn current_user_name() -> Option<String> {
+    // SAFETY: Tests run single-threaded with --test-threads=1, so the static entry returned by
+    // `getpwuid` is not overwritten while it is read.
+    unsafe {
+        let passwd = libc::getpwuid(libc::getuid());
+        if passwd.is_null() || (*passwd).pw_name.is_null() {
+            return None;
+        }
+        CStr::from_ptr((*passwd).pw_name)
+            .to_str()
+            .ok()
+            .map(String::from)
+    }
+}
+
+/// Calls the reentrant password database function `get`, growing the buffer while it is too
+/// small, and returns the home directory of the entry found.
+#[cfg(feature = "passwd-fallback")]
+fn lookup_home(
+    get: impl Fn(*mut libc::passwd, *mut libc::c_char, usize, *mut *mut libc::passwd) -> libc::c_int,
+) -> Option<PathBuf> {
+    // SAFETY: `sysconf` has no preconditions.
+    let suggested = unsafe { libc::sysconf(libc::_SC_GETPW_R_SIZE_MAX) };
+    let mut size = usize::try_from(suggested)
+        .ok()
+        .filter(|&size| size > 0)
+        .unwrap_or(INITIAL_BUFFER_SIZE);
+
+    loop {
+        let mut buffer = vec![0 as libc::c_char; size];
+        // SAFETY: `passwd` is plain old data, all zeroes is a valid value.
+        let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+        let error = get(&mut passwd, buffer.as_mut_ptr(), size, &mut result);
+        if error == libc::ERANGE && size < MAX_BUFFER_SIZE {
+            size *= 2;
+            continue;
+        }
+        if error != 0 || result.is_null() || passwd.pw_dir.is_null() {
+            return None;
+        }
+        // SAFETY: On success, `pw_dir` points to a null-terminated string within `buffer`,
+        // which is still alive.
+        let dir = unsafe { CStr::from_ptr(passwd.pw_dir) };
+        let dir = PathBuf::from(OsString::from_vec(dir.to_bytes().to_vec()));
+        return dir.is_absolute().then_some(dir);
+    }
+}
+
+/// Returns whether `path` is a directory owned by the effective user that only it can access,
+/// i.e. with mode `0700`.
+#[cfg(feature = "checked-runtime-dir")]
+pub(crate) fn is_private_dir(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    // SAFETY: `geteuid` always succeeds.
+    let euid = unsafe { libc::geteuid() };
+    metadata.is_dir() && metadata.uid() == euid && metadata.mode() & 0o777 == 0o700
+}
+
+/// Takes an exclusive advisory lock on `file` with `flock`, failing with
+/// [`ErrorKind::WouldBlock`](std::io::ErrorKind::WouldBlock) if another one holds it.
+///
+/// The lock is released when the file is closed.
+#[cfg(feature = "cache-lock")]
+pub(crate) fn try_lock_exclusive(file: &std::fs::File) -> std::io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    // SAFETY: `file` is an open file descriptor for the duration of the call.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "passwd-fallback")]
+    fn passwd_home_resolves_current_user() {
+        let home = passwd_home().expect("current user has a passwd entry");
+        assert!(home.is_absolute());
+    }
+
+    #[test]
+    #[cfg(feature = "passwd-fallback")]
+    fn user_home_resolves_root() {
+        assert!(user_home("root").is_some_and(|home| home.is_absolute()));
+    }
+
+    #[test]
+    #[cfg(feature = "passwd-fallback")]
+    fn user_home_rejects_unknown_and_invalid_names() {
+        assert_eq!(user_home("dirs-lite-no-such-user"), None);
+        assert_eq!(user_home("ro\0ot"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "checked-runtime-dir")]
+    fn is_private_dir_checks_mode() {
+        use std::fs::{self, Permissions};
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::set_permissions(dir.path(), Permissions::from_mode(0o700)).unwrap();
+        assert!(is_private_dir(dir.path()));
+
+        fs::set_permissions(dir.path(), Permissions::from_mode(0o755)).unwrap();
+        assert!(!is_private_dir(dir.path()));
+
+        assert!(!is_private_dir(&dir.path().join("missing")));
+    }
+}
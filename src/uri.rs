@@ -0,0 +1,141 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::{Env, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with};
+
+/// Returns [`config_dir`](crate::config_dir) as a `file://` URI, e.g. to open it in the file
+/// manager for a "Reveal config folder" button.
+///
+/// Every byte of the path but ASCII letters, digits, `-`, `.`, `_`, `~`, `:` and the separators
+/// is percent-encoded, e.g. `/home/alice/My Config` becomes `file:///home/alice/My%20Config`. On
+/// Windows, backslashes become slashes, `C:\Users\Alice` becomes `file:///C:/Users/Alice` and
+/// `\\server\share` becomes `file://server/share`.
+///
+/// Returns `None` if the directory cannot be resolved or its path is not valid Unicode, as the
+/// encoding of such a path in a URI is undefined.
+pub fn config_dir_uri() -> Option<String> {
+    config_dir_uri_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_uri`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_uri_with<E: Env + ?Sized>(env: &E) -> Option<String> {
+    file_uri(&config_dir_with(env)?)
+}
+
+/// Returns [`data_dir`](crate::data_dir) as a `file://` URI, see [`config_dir_uri`].
+pub fn data_dir_uri() -> Option<String> {
+    data_dir_uri_with(&ProcessEnv)
+}
+
+/// Like [`data_dir_uri`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_uri_with<E: Env + ?Sized>(env: &E) -> Option<String> {
+    file_uri(&data_dir_with(env)?)
+}
+
+/// Returns [`cache_dir`](crate::cache_dir) as a `file://` URI, see [`config_dir_uri`].
+pub fn cache_dir_uri() -> Option<String> {
+    cache_dir_uri_with(&ProcessEnv)
+}
+
+/// Like [`cache_dir_uri`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_uri_with<E: Env + ?Sized>(env: &E) -> Option<String> {
+    file_uri(&cache_dir_with(env)?)
+}
+
+fn file_uri(path: &Path) -> Option<String> {
+    Some(encode_file_uri(path.to_str()?, cfg!(target_os = "windows")))
+}
+
+/// Returns the `file://` URI of the absolute `path`, following the conventions of Windows if
+/// `windows` is set.
+fn encode_file_uri(path: &str, windows: bool) -> String {
+    let path = if windows {
+        // Verbatim paths are plain drive or UNC paths to the file manager
+        let path = match path.strip_prefix(r"\\?\UNC\") {
+            Some(unc) => format!(r"\\{unc}"),
+            None => path.strip_prefix(r"\\?\").unwrap_or(path).to_owned(),
+        };
+        path.replace('\\', "/")
+    } else {
+        path.to_owned()
+    };
+
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        // A drive path like C:/Users needs the empty authority of file:///
+        uri.push('/');
+    } else if windows && path.starts_with("//") {
+        // A UNC path like //server/share names the authority itself
+        uri.truncate("file:".len());
+    }
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~:/".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            let _ = write!(uri, "%{byte:02X}");
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_unix_paths() {
+        assert_eq!(
+            encode_file_uri("/home/alice/My Config", false),
+            "file:///home/alice/My%20Config"
+        );
+        assert_eq!(
+            encode_file_uri("/home/alice/#50% (ä)", false),
+            "file:///home/alice/%2350%25%20%28%C3%A4%29"
+        );
+    }
+
+    #[test]
+    fn encodes_windows_paths() {
+        assert_eq!(
+            encode_file_uri(r"C:\Users\Alice Smith\AppData\Roaming", true),
+            "file:///C:/Users/Alice%20Smith/AppData/Roaming"
+        );
+        assert_eq!(
+            encode_file_uri(r"\\?\C:\Users\Alice", true),
+            "file:///C:/Users/Alice"
+        );
+        assert_eq!(
+            encode_file_uri(r"\\server\share\Alice", true),
+            "file://server/share/Alice"
+        );
+        assert_eq!(
+            encode_file_uri(r"\\?\UNC\server\share\Alice", true),
+            "file://server/share/Alice"
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_dir_uris() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let env = crate::MapEnv::new().with("HOME", "/home/alice smith");
+        assert_eq!(
+            config_dir_uri_with(&env).as_deref(),
+            Some("file:///home/alice%20smith/.config")
+        );
+        assert_eq!(
+            data_dir_uri_with(&env).as_deref(),
+            Some("file:///home/alice%20smith/.local/share")
+        );
+        assert_eq!(
+            cache_dir_uri_with(&env).as_deref(),
+            Some("file:///home/alice%20smith/.cache")
+        );
+
+        let env = crate::MapEnv::new().with("HOME", OsStr::from_bytes(b"/home/\xff"));
+        assert_eq!(config_dir_uri_with(&env), None);
+        assert_eq!(config_dir_uri_with(&crate::MapEnv::new()), None);
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{Env, KnownFolder, ProcessEnv, config_dir_with, home_dir_with, is_xdg_platform};
+
+/// A user directory as defined by `xdg-user-dirs`.
+struct UserDir {
+    /// The key in `user-dirs.dirs`, e.g. `XDG_DESKTOP_DIR`.
+    key: &'static str,
+    /// The folder in the home directory if the key is not configured.
+    xdg_default: &'static str,
+    /// The folder in the home directory on macOS, if any.
+    macos: Option<&'static str>,
+    /// The known folder on Windows, if any.
+    windows: Option<KnownFolder>,
+}
+
+/// Resolves `dir` on the current platform.
+fn user_dir<E: Env + ?Sized>(env: &E, dir: UserDir) -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        // Windows: Requires the known folder API
+        return dir.windows.and_then(|folder| env.known_folder(folder));
+    }
+    let home = home_dir_with(env)?;
+    if is_xdg_platform() {
+        // Linux and BSDs: Use $XDG_CONFIG_HOME/user-dirs.dirs or $HOME/<default>
+        let configured = config_dir_with(env)
+            .and_then(|config| fs::read_to_string(config.join("user-dirs.dirs")).ok())
+            .and_then(|contents| parse_user_dirs(&contents, &home).remove(dir.key));
+        Some(configured.unwrap_or_else(|| home.join(dir.xdg_default)))
+    } else if cfg!(target_os = "macos") {
+        // macOS: Use the fixed folders in $HOME
+        dir.macos.map(|folder| home.join(folder))
+    } else {
+        None
+    }
+}
+
+/// Parses the contents of a `user-dirs.dirs` file into its keys and directories.
+///
+/// Each line has the form `XDG_DESKTOP_DIR="$HOME/Desktop"`, with the value in double quotes
+/// and `\` escaping the next character. A leading `$HOME` is replaced by `home`, any other
+/// value must be an absolute path. Comments, empty lines and malformed lines are skipped.
+fn parse_user_dirs(contents: &str, home: &Path) -> HashMap<String, PathBuf> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let (key, value) = line.split_once('=')?;
+            let value = unquote(value)?;
+            let dir = match value.strip_prefix("$HOME") {
+                Some("") => home.to_path_buf(),
+                Some(rest) => home.join(rest.strip_prefix('/')?),
+                None if Path::new(&value).is_absolute() => PathBuf::from(value),
+                None => return None,
+            };
+            Some((key.to_string(), dir))
+        })
+        .collect()
+}
+
+/// Removes the double quotes around `value` and resolves backslash escapes.
+///
+/// Returns `None` unless `value` is exactly one double-quoted string.
+fn unquote(value: &str) -> Option<String> {
+    let mut chars = value.strip_prefix('"')?.chars();
+    let mut unquoted = String::with_capacity(value.len());
+    loop {
+        match chars.next()? {
+            '"' => break,
+            '\\' => unquoted.push(chars.next()?),
+            c => unquoted.push(c),
+        }
+    }
+    chars.as_str().is_empty().then_some(unquoted)
+}
+
+macro_rules! user_dir_fns {
+    ($(($name:ident, $name_with:ident, $key:literal, $xdg:literal, $macos:expr, $windows:expr, $linux_example:literal, $macos_example:literal, $windows_example:literal);)*) => {$(
+        #[doc = concat!("Returns the path to the user's ", $xdg, " directory.")]
+        ///
+        /// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+        ///
+        /// |Platform | Value                                 | Example                                  |
+        /// | ------- | ------------------------------------- | ---------------------------------------- |
+        #[doc = concat!("| Linux   | `", $key, "` in `user-dirs.dirs` or `$HOME`/", $xdg, " | ", $linux_example, " |")]
+        #[doc = concat!("| macOS   | ", $macos_example, " |")]
+        #[doc = concat!("| Windows | ", $windows_example, " |")]
+        ///
+        /// On Linux, `user-dirs.dirs` is read from [`config_dir`](crate::config_dir), as written by `xdg-user-dirs-update`.
+        ///
+        /// NOTE: Windows requires the known folder API, i.e. the feature `windows-known-folders`
+        /// or an [`Env`] that implements [`Env::known_folder`], otherwise `None` is returned there.
+        pub fn $name() -> Option<PathBuf> {
+            $name_with(&ProcessEnv)
+        }
+
+        #[doc = concat!("Like [`", stringify!($name), "`], but resolves against the given environment instead of the one of the current process.")]
+        pub fn $name_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+            user_dir(
+                env,
+                UserDir {
+                    key: $key,
+                    xdg_default: $xdg,
+                    macos: $macos,
+                    windows: $windows,
+                },
+            )
+        }
+    )*};
+}
+
+user_dir_fns! {
+    (desktop_dir, desktop_dir_with, "XDG_DESKTOP_DIR", "Desktop", Some("Desktop"), None, "/home/alice/Desktop", "`$HOME`/Desktop | /Users/Alice/Desktop", "– | –");
+    (download_dir, download_dir_with, "XDG_DOWNLOAD_DIR", "Downloads", Some("Downloads"), None, "/home/alice/Downloads", "`$HOME`/Downloads | /Users/Alice/Downloads", "– | –");
+    (documents_dir, documents_dir_with, "XDG_DOCUMENTS_DIR", "Documents", Some("Documents"), None, "/home/alice/Documents", "`$HOME`/Documents | /Users/Alice/Documents", "– | –");
+    (pictures_dir, pictures_dir_with, "XDG_PICTURES_DIR", "Pictures", Some("Pictures"), Some(KnownFolder::Pictures), "/home/alice/Pictures", "`$HOME`/Pictures | /Users/Alice/Pictures", "`FOLDERID_Pictures` | C:\\Users\\Alice\\Pictures");
+    (music_dir, music_dir_with, "XDG_MUSIC_DIR", "Music", Some("Music"), Some(KnownFolder::Music), "/home/alice/Music", "`$HOME`/Music | /Users/Alice/Music", "`FOLDERID_Music` | C:\\Users\\Alice\\Music");
+    (videos_dir, videos_dir_with, "XDG_VIDEOS_DIR", "Videos", Some("Movies"), Some(KnownFolder::Videos), "/home/alice/Videos", "`$HOME`/Movies | /Users/Alice/Movies", "`FOLDERID_Videos` | C:\\Users\\Alice\\Videos");
+    (templates_dir, templates_dir_with, "XDG_TEMPLATES_DIR", "Templates", None, None, "/home/alice/Templates", "– | –", "– | –");
+    (public_dir, public_dir_with, "XDG_PUBLICSHARE_DIR", "Public", Some("Public"), None, "/home/alice/Public", "`$HOME`/Public | /Users/Alice/Public", "– | –");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    use crate::MapEnv;
+
+    fn parse(contents: &str) -> HashMap<String, PathBuf> {
+        parse_user_dirs(contents, Path::new("/home/testuser"))
+    }
+
+    #[test]
+    fn parse_user_dirs_expands_home() {
+        let dirs = parse("XDG_DESKTOP_DIR=\"$HOME/Desktop\"\nXDG_PUBLICSHARE_DIR=\"$HOME\"\n");
+        assert_eq!(
+            dirs["XDG_DESKTOP_DIR"],
+            Path::new("/home/testuser").join("Desktop")
+        );
+        assert_eq!(dirs["XDG_PUBLICSHARE_DIR"], Path::new("/home/testuser"));
+    }
+
+    #[test]
+    fn parse_user_dirs_skips_comments() {
+        let dirs = parse("# XDG_DESKTOP_DIR=\"$HOME/Old\"\n\n  XDG_MUSIC_DIR=\"$HOME/Music\"\n");
+        assert_eq!(dirs.len(), 1);
+        assert!(dirs.contains_key("XDG_MUSIC_DIR"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn parse_user_dirs_keeps_absolute_values() {
+        let dirs = parse("XDG_DOWNLOAD_DIR=\"/data/downloads\"\n");
+        assert_eq!(dirs["XDG_DOWNLOAD_DIR"], Path::new("/data/downloads"));
+    }
+
+    #[test]
+    fn parse_user_dirs_handles_spaces_and_escapes() {
+        let dirs = parse(r#"XDG_DOCUMENTS_DIR="$HOME/My Documents/\"Work\" \$stuff""#);
+        assert_eq!(
+            dirs["XDG_DOCUMENTS_DIR"],
+            Path::new("/home/testuser").join("My Documents/\"Work\" $stuff")
+        );
+    }
+
+    #[test]
+    fn parse_user_dirs_skips_malformed_lines() {
+        let dirs = parse(
+            "XDG_DESKTOP_DIR\n\
+             XDG_DOWNLOAD_DIR=$HOME/Downloads\n\
+             XDG_MUSIC_DIR=\"$HOME/Music\n\
+             XDG_VIDEOS_DIR=\"$HOME/Videos\" trailing\n\
+             XDG_TEMPLATES_DIR=\"relative/Templates\"\n\
+             XDG_PICTURES_DIR=\"$HOMEPictures\"\n\
+             XDG_PUBLICSHARE_DIR=\"$HOME/Public\\\n",
+        );
+        assert!(dirs.is_empty(), "{dirs:?}");
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_user_dirs_read_user_dirs_file() {
+        let config = tempfile::tempdir().unwrap();
+        fs::write(
+            config.path().join("user-dirs.dirs"),
+            "# written by xdg-user-dirs-update\n\
+             XDG_DESKTOP_DIR=\"$HOME/Schreibtisch\"\n\
+             XDG_DOWNLOAD_DIR=\"/data/downloads\"\n",
+        )
+        .unwrap();
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CONFIG_HOME", config.path());
+
+        assert_eq!(
+            desktop_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/Schreibtisch"))
+        );
+        assert_eq!(
+            download_dir_with(&env),
+            Some(PathBuf::from("/data/downloads"))
+        );
+        assert_eq!(
+            music_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/Music"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_user_dirs_fall_back_to_home() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/testuser")
+            .with("XDG_CONFIG_HOME", "/nonexistent/config");
+
+        assert_eq!(
+            desktop_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/Desktop"))
+        );
+        assert_eq!(
+            public_dir_with(&env),
+            Some(PathBuf::from("/home/testuser/Public"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn macos_user_dirs_use_home_folders() {
+        let env = MapEnv::new().with("HOME", "/Users/testuser");
+
+        assert_eq!(
+            videos_dir_with(&env),
+            Some(PathBuf::from("/Users/testuser/Movies"))
+        );
+        assert_eq!(templates_dir_with(&env), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_user_dirs_are_none() {
+        let env = MapEnv::new().with("USERPROFILE", "C:\\Users\\testuser");
+        assert_eq!(desktop_dir_with(&env), None);
+        assert_eq!(music_dir_with(&env), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_media_dirs_use_known_folders() {
+        struct KnownFolderEnv;
+
+        impl Env for KnownFolderEnv {
+            fn var_os(&self, _key: &str) -> Option<std::ffi::OsString> {
+                None
+            }
+
+            fn known_folder(&self, folder: KnownFolder) -> Option<PathBuf> {
+                Some(PathBuf::from(format!("D:\\Media\\{folder:?}")))
+            }
+        }
+
+        assert_eq!(
+            music_dir_with(&KnownFolderEnv),
+            Some(PathBuf::from("D:\\Media\\Music"))
+        );
+        assert_eq!(
+            pictures_dir_with(&KnownFolderEnv),
+            Some(PathBuf::from("D:\\Media\\Pictures"))
+        );
+        assert_eq!(
+            videos_dir_with(&KnownFolderEnv),
+            Some(PathBuf::from("D:\\Media\\Videos"))
+        );
+        assert_eq!(desktop_dir_with(&KnownFolderEnv), None);
+    }
+}
@@ -0,0 +1,69 @@
+use camino::Utf8PathBuf;
+
+use crate::{Env, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with};
+
+/// Like [`config_dir`](crate::config_dir), but returns `None` if the path is not valid UTF-8.
+pub fn config_dir_utf8() -> Option<Utf8PathBuf> {
+    config_dir_utf8_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_utf8`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_utf8_with<E: Env + ?Sized>(env: &E) -> Option<Utf8PathBuf> {
+    Utf8PathBuf::from_path_buf(config_dir_with(env)?).ok()
+}
+
+/// Like [`data_dir`](crate::data_dir), but returns `None` if the path is not valid UTF-8.
+pub fn data_dir_utf8() -> Option<Utf8PathBuf> {
+    data_dir_utf8_with(&ProcessEnv)
+}
+
+/// Like [`data_dir_utf8`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_utf8_with<E: Env + ?Sized>(env: &E) -> Option<Utf8PathBuf> {
+    Utf8PathBuf::from_path_buf(data_dir_with(env)?).ok()
+}
+
+/// Like [`cache_dir`](crate::cache_dir), but returns `None` if the path is not valid UTF-8.
+pub fn cache_dir_utf8() -> Option<Utf8PathBuf> {
+    cache_dir_utf8_with(&ProcessEnv)
+}
+
+/// Like [`cache_dir_utf8`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_utf8_with<E: Env + ?Sized>(env: &E) -> Option<Utf8PathBuf> {
+    Utf8PathBuf::from_path_buf(cache_dir_with(env)?).ok()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    fn linux_utf8_dirs_resolve_utf8_home() {
+        let env = MapEnv::new().with("HOME", "/home/testuser");
+        assert_eq!(
+            config_dir_utf8_with(&env),
+            Some(Utf8PathBuf::from("/home/testuser/.config"))
+        );
+        assert_eq!(
+            data_dir_utf8_with(&env),
+            Some(Utf8PathBuf::from("/home/testuser/.local/share"))
+        );
+        assert_eq!(
+            cache_dir_utf8_with(&env),
+            Some(Utf8PathBuf::from("/home/testuser/.cache"))
+        );
+    }
+
+    #[test]
+    fn linux_utf8_dirs_reject_non_utf8_home() {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+
+        let home = OsString::from_vec(b"/home/test\xffuser".to_vec());
+        let env = MapEnv::new().with("HOME", home);
+        assert!(config_dir_with(&env).is_some());
+        assert_eq!(config_dir_utf8_with(&env), None);
+        assert_eq!(data_dir_utf8_with(&env), None);
+        assert_eq!(cache_dir_utf8_with(&env), None);
+    }
+}
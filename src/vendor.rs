@@ -0,0 +1,107 @@
+use std::path::{Path, PathBuf};
+
+use crate::{Env, ProcessEnv, is_xdg_platform, system_data_dirs_with};
+
+/// Returns the path to the directory holding the read-only assets shipped with the application.
+///
+/// The returned value depends on the operating system and is either a `Some`, containing a value from the following table, or a `None`.
+///
+/// |Platform | Value                                     | Example                                     |
+/// | ------- | ----------------------------------------- | ------------------------------------------- |
+/// | Linux   | the first of [`system_data_dirs`](crate::system_data_dirs) | /usr/local/share               |
+/// | macOS   | `Contents/Resources` of the app bundle    | /Applications/My App.app/Contents/Resources |
+/// | Windows | the directory of the executable           | C:\Program Files\My App                     |
+///
+/// On Linux, append the name of the application, e.g. `myapp` for `/usr/local/share/myapp`.
+/// On macOS, `None` is returned if the executable is not inside `<bundle>.app/Contents/MacOS`.
+pub fn vendor_dir() -> Option<PathBuf> {
+    vendor_dir_with(&ProcessEnv)
+}
+
+/// Like [`vendor_dir`], but resolves against the given environment instead of the one of the current process.
+///
+/// The executable is still the one of the current process.
+pub fn vendor_dir_with<E: Env + ?Sized>(env: &E) -> Option<PathBuf> {
+    if is_xdg_platform() {
+        // Linux and BSDs: Use the most preferred system data dir
+        system_data_dirs_with(env).into_iter().next()
+    } else if cfg!(target_os = "macos") {
+        // macOS: Use the Resources of the app bundle
+        bundle_resources_dir(&std::env::current_exe().ok()?)
+    } else if cfg!(target_os = "windows") {
+        // Windows: Use the directory of the executable
+        exe_dir(&std::env::current_exe().ok()?)
+    } else {
+        // Unsupported platform
+        None
+    }
+}
+
+/// Returns `<bundle>.app/Contents/Resources` for an executable at `<bundle>.app/Contents/MacOS/<exe>`.
+fn bundle_resources_dir(exe: &Path) -> Option<PathBuf> {
+    let macos = exe.parent().filter(|dir| dir.ends_with("MacOS"))?;
+    let contents = macos.parent().filter(|dir| dir.ends_with("Contents"))?;
+    contents
+        .parent()
+        .filter(|bundle| bundle.extension().is_some_and(|ext| ext == "app"))?;
+    Some(contents.join("Resources"))
+}
+
+fn exe_dir(exe: &Path) -> Option<PathBuf> {
+    exe.parent().map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_resources_dir_requires_bundle_shape() {
+        assert_eq!(
+            bundle_resources_dir(Path::new("/Applications/My App.app/Contents/MacOS/myapp")),
+            Some(PathBuf::from("/Applications/My App.app/Contents/Resources"))
+        );
+        assert_eq!(
+            bundle_resources_dir(Path::new("/usr/local/bin/myapp")),
+            None
+        );
+        assert_eq!(
+            bundle_resources_dir(Path::new("/opt/Contents/MacOS/myapp")),
+            None
+        );
+        assert_eq!(
+            bundle_resources_dir(Path::new("/Applications/My App.app/Contents/myapp")),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_exe_dir_is_parent_of_executable() {
+        assert_eq!(
+            exe_dir(Path::new("C:\\Program Files\\My App\\myapp.exe")),
+            Some(PathBuf::from("C:\\Program Files\\My App"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_vendor_dir_uses_first_system_data_dir() {
+        let env = crate::MapEnv::new().with("XDG_DATA_DIRS", "/opt/share:/usr/share");
+        assert_eq!(vendor_dir_with(&env), Some(PathBuf::from("/opt/share")));
+        assert_eq!(
+            vendor_dir_with(&crate::MapEnv::new()),
+            Some(PathBuf::from("/usr/local/share"))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn windows_vendor_dir_is_exe_dir() {
+        let exe = std::env::current_exe().unwrap();
+        assert_eq!(
+            vendor_dir_with(&crate::MapEnv::new()).as_deref(),
+            exe.parent()
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::{
+    DirError, DirKind, Env, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with,
+    home_dir_result_with, is_xdg_platform, state_dir_with, xdg_var,
+};
+
+/// The XDG variables checked by [`verify_layout`].
+const XDG_KEYS: &[&str] = &[
+    "XDG_CONFIG_HOME",
+    "XDG_DATA_HOME",
+    "XDG_CACHE_HOME",
+    "XDG_STATE_HOME",
+    "XDG_RUNTIME_DIR",
+];
+
+/// A misconfiguration detected by [`verify_layout`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LayoutWarning {
+    /// The XDG variable `key` is set to the relative path `value`, so it is ignored.
+    RelativeEnvVar { key: &'static str, value: PathBuf },
+    /// The home directory is unknown, so no directory derived from it resolves.
+    MissingHome,
+    /// The directory of the given kind resolves to `path`, which does not exist yet.
+    MissingDir { kind: DirKind, path: PathBuf },
+    /// The cache directory `cache` lies inside the data directory `data`, so clearing caches
+    /// or backing up data affects both.
+    CacheInsideData { cache: PathBuf, data: PathBuf },
+}
+
+impl fmt::Display for LayoutWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutWarning::RelativeEnvVar { key, value } => write!(
+                f,
+                "{key} is set to the relative path {}, which is ignored",
+                value.display()
+            ),
+            LayoutWarning::MissingHome => f.write_str("home directory is unknown"),
+            LayoutWarning::MissingDir { kind, path } => {
+                write!(f, "the {kind} directory {} does not exist", path.display())
+            }
+            LayoutWarning::CacheInsideData { cache, data } => write!(
+                f,
+                "the cache directory {} is inside the data directory {}",
+                cache.display(),
+                data.display()
+            ),
+        }
+    }
+}
+
+/// Checks the environment for common misconfigurations and returns every problem found, e.g.
+/// to print in a support or `doctor` command.
+///
+/// This reports XDG variables set to relative paths, an unknown home directory, config, data,
+/// cache and state directories that do not exist, and a cache directory nested in the data
+/// directory. An empty `Vec` means no problem was found.
+pub fn verify_layout() -> Vec<LayoutWarning> {
+    verify_layout_with(&ProcessEnv)
+}
+
+/// Like [`verify_layout`], but resolves against the given environment instead of the one of the current process.
+pub fn verify_layout_with<E: Env + ?Sized>(env: &E) -> Vec<LayoutWarning> {
+    let mut warnings = Vec::new();
+    if is_xdg_platform() {
+        for &key in XDG_KEYS {
+            if let Err(DirError::RelativeEnvVar { key }) = xdg_var(env, key) {
+                let value = env.var_os(key).map(PathBuf::from).unwrap_or_default();
+                warnings.push(LayoutWarning::RelativeEnvVar { key, value });
+            }
+        }
+    }
+    if home_dir_result_with(env) == Err(DirError::MissingHome) {
+        warnings.push(LayoutWarning::MissingHome);
+    }
+    let dirs = [
+        (DirKind::Config, config_dir_with(env)),
+        (DirKind::Data, data_dir_with(env)),
+        (DirKind::Cache, cache_dir_with(env)),
+        (DirKind::State, state_dir_with(env)),
+    ];
+    for (kind, path) in &dirs {
+        if let Some(path) = path.as_ref().filter(|path| !path.exists()) {
+            warnings.push(LayoutWarning::MissingDir {
+                kind: *kind,
+                path: path.clone(),
+            });
+        }
+    }
+    // Windows shares %LOCALAPPDATA% between both on purpose, so only a strict nesting counts
+    if let (Some(data), Some(cache)) = (&dirs[1].1, &dirs[2].1) {
+        if cache != data && cache.starts_with(data) {
+            warnings.push(LayoutWarning::CacheInsideData {
+                cache: cache.clone(),
+                data: data.clone(),
+            });
+        }
+    }
+    warnings
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    fn reports_relative_xdg_var() {
+        let env = MapEnv::new()
+            .with("HOME", "/home/alice")
+            .with("XDG_CONFIG_HOME", "relative/config");
+        assert!(
+            verify_layout_with(&env).contains(&LayoutWarning::RelativeEnvVar {
+                key: "XDG_CONFIG_HOME",
+                value: PathBuf::from("relative/config"),
+            })
+        );
+    }
+
+    #[test]
+    fn reports_missing_home() {
+        assert_eq!(
+            verify_layout_with(&MapEnv::new()),
+            vec![LayoutWarning::MissingHome]
+        );
+    }
+
+    #[test]
+    fn reports_missing_dirs() {
+        let home = tempfile::tempdir().unwrap();
+        let env = MapEnv::new().with("HOME", home.path());
+        fs::create_dir(home.path().join(".config")).unwrap();
+
+        assert_eq!(
+            verify_layout_with(&env),
+            vec![
+                LayoutWarning::MissingDir {
+                    kind: DirKind::Data,
+                    path: home.path().join(".local/share"),
+                },
+                LayoutWarning::MissingDir {
+                    kind: DirKind::Cache,
+                    path: home.path().join(".cache"),
+                },
+                LayoutWarning::MissingDir {
+                    kind: DirKind::State,
+                    path: home.path().join(".local/state"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_cache_inside_data() {
+        let home = tempfile::tempdir().unwrap();
+        for dir in [".config", ".local/share/cache", ".local/state"] {
+            fs::create_dir_all(home.path().join(dir)).unwrap();
+        }
+        let env = MapEnv::new()
+            .with("HOME", home.path())
+            .with("XDG_CACHE_HOME", home.path().join(".local/share/cache"));
+
+        assert_eq!(
+            verify_layout_with(&env),
+            vec![LayoutWarning::CacheInsideData {
+                cache: home.path().join(".local/share/cache"),
+                data: home.path().join(".local/share"),
+            }]
+        );
+    }
+}
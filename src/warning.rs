@@ -0,0 +1,119 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+
+use crate::Env;
+
+/// A likely misconfiguration noticed while resolving a directory.
+///
+/// Warnings never change the resolved value, they are only reported to the handler
+/// installed with [`set_warning_handler`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// The environment variable `key` points to the home directory itself, so files
+    /// would end up directly in `path`, e.g. `XDG_CONFIG_HOME=$HOME` instead of `$HOME/.config`.
+    BaseDirIsHome { key: &'static str, path: PathBuf },
+    /// The runtime directory `path` does not exist, is not owned by the current user or is
+    /// accessible by other users. Reported by `runtime_dir_checked` with the feature `checked-runtime-dir`.
+    InsecureRuntimeDir { path: PathBuf },
+    /// The environment variable `key`, registered with [`deprecate_var`], is set. Reported
+    /// while resolving [`config_dir`](crate::config_dir).
+    DeprecatedVar {
+        key: &'static str,
+        replacement: &'static str,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::BaseDirIsHome { key, path } => write!(
+                f,
+                "{key} is set to the home directory {}, files will be placed directly in it",
+                path.display()
+            ),
+            Warning::InsecureRuntimeDir { path } => write!(
+                f,
+                "the runtime directory {} is not a directory only accessible by the current user",
+                path.display()
+            ),
+            Warning::DeprecatedVar { key, replacement } => {
+                write!(f, "{key} is deprecated, use {replacement} instead")
+            }
+        }
+    }
+}
+
+static HANDLER: RwLock<Option<fn(&Warning)>> = RwLock::new(None);
+
+/// The registered deprecated variables with their replacement and whether they were reported.
+static DEPRECATED_VARS: Mutex<Vec<(&'static str, &'static str, bool)>> = Mutex::new(Vec::new());
+
+/// Installs `handler` to be called with every [`Warning`], replacing the previous one.
+///
+/// No handler is installed by default, so warnings are dropped silently.
+///
+/// ```
+/// dirs_lite::set_warning_handler(|warning| eprintln!("warning: {warning}"));
+/// ```
+pub fn set_warning_handler(handler: fn(&Warning)) {
+    *HANDLER.write().unwrap_or_else(|e| e.into_inner()) = Some(handler);
+}
+
+/// Removes the handler installed with [`set_warning_handler`].
+pub fn clear_warning_handler() {
+    *HANDLER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Reports `warning` to the installed handler, if any.
+pub(crate) fn emit(warning: Warning) {
+    let handler = *HANDLER.read().unwrap_or_else(|e| e.into_inner());
+    if let Some(handler) = handler {
+        handler(&warning);
+    }
+}
+
+/// Registers the environment variable `key` as deprecated in favor of `replacement`, so that a
+/// [`Warning::DeprecatedVar`] is reported the first time it is set to a non-empty value while
+/// [`config_dir`](crate::config_dir) is resolved. Each variable is reported once per process.
+///
+/// This lets an application route the deprecation of its own variables through the handler
+/// installed with [`set_warning_handler`], e.g. of a legacy `MYAPP_CONFIG` replaced by
+/// `MYAPP_CONFIG_DIR`. The variable is only reported, it does not change the resolved value.
+///
+/// ```
+/// dirs_lite::deprecate_var("MYAPP_CONFIG", "MYAPP_CONFIG_DIR");
+/// ```
+pub fn deprecate_var(key: &'static str, replacement: &'static str) {
+    let mut vars = DEPRECATED_VARS.lock().unwrap_or_else(|e| e.into_inner());
+    if !vars.iter().any(|(registered, _, _)| *registered == key) {
+        vars.push((key, replacement, false));
+    }
+}
+
+/// Removes the variables registered with [`deprecate_var`], a variable registered again
+/// afterwards is reported again.
+pub fn clear_deprecated_vars() {
+    DEPRECATED_VARS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+}
+
+/// Reports a [`Warning::DeprecatedVar`] for every registered deprecated variable set in `env`
+/// that was not reported before.
+pub(crate) fn check_deprecated_vars<E: Env + ?Sized>(env: &E) {
+    let mut warnings = Vec::new();
+    {
+        let mut vars = DEPRECATED_VARS.lock().unwrap_or_else(|e| e.into_inner());
+        for (key, replacement, reported) in vars.iter_mut() {
+            if !*reported && env.var_os(key).is_some_and(|value| !value.is_empty()) {
+                *reported = true;
+                warnings.push(Warning::DeprecatedVar { key, replacement });
+            }
+        }
+    }
+    // The handler is called without the lock held, so it may resolve directories itself
+    warnings.into_iter().for_each(emit);
+}
@@ -0,0 +1,286 @@
+#[cfg(feature = "winapi-known-folders")]
+use std::path::{Component, Path, PathBuf};
+
+/// Returns the path to the config directory of the Windows user `username`.
+///
+/// The path is derived as `<profiles>\<username>\AppData\Roaming`, where `<profiles>` is
+/// the root of the user profiles from `GetProfilesDirectoryW`, e.g.
+/// `C:\Users\bob\AppData\Roaming`. This is a best effort: the profile folder of a user is
+/// assumed to be named after them, which does not hold e.g. for `bob.DOMAIN` created on a
+/// name clash, and the target user's profile is not loaded, so folder redirection configured
+/// for that user is not honored.
+///
+/// Returns `None` if the profiles directory is unknown, or if `username` is empty or is not
+/// a plain file name.
+///
+/// NOTE: reading or writing another user's `AppData` requires the calling process to run
+/// elevated (as an administrator or `SYSTEM`); this function only computes the path.
+#[cfg(feature = "winapi-known-folders")]
+pub fn config_dir_for_windows_user(username: &str) -> Option<PathBuf> {
+    let mut components = Path::new(username).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        return None;
+    }
+
+    let mut dir = profiles_dir()?;
+    dir.push(username);
+    dir.push("AppData");
+    dir.push("Roaming");
+    Some(dir)
+}
+
+/// Returns the directory holding the user profiles from `GetProfilesDirectoryW`, or `None` if
+/// the call fails.
+#[cfg(feature = "winapi-known-folders")]
+fn profiles_dir() -> Option<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+
+    #[link(name = "userenv")]
+    unsafe extern "system" {
+        fn GetProfilesDirectoryW(profile_dir: *mut u16, size: *mut u32) -> i32;
+    }
+
+    let mut size = 0;
+    // SAFETY: A null buffer with a size of 0 only queries the required size, in characters
+    // including the terminating null.
+    unsafe { GetProfilesDirectoryW(std::ptr::null_mut(), &mut size) };
+    let mut buffer = vec![0; size as usize];
+    // SAFETY: `buffer` is writable for `size` characters.
+    let ok = unsafe { GetProfilesDirectoryW(buffer.as_mut_ptr(), &mut size) };
+    if ok == 0 {
+        return None;
+    }
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Some(PathBuf::from(OsString::from_wide(&buffer[..len]))).filter(|dir| dir.is_absolute())
+}
+
+/// Returns the ID of the Remote Desktop Services session of the current process, or `None` if
+/// `ProcessIdToSessionId` fails.
+pub(crate) fn session_id() -> Option<u32> {
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn ProcessIdToSessionId(process_id: u32, session_id: *mut u32) -> i32;
+    }
+
+    let mut session = 0;
+    // SAFETY: `session` is a valid pointer to write the ID to.
+    let ok = unsafe { ProcessIdToSessionId(std::process::id(), &mut session) };
+    (ok != 0).then_some(session)
+}
+
+/// Takes an exclusive lock on the first byte of `file` with `LockFileEx`, failing with
+/// [`ErrorKind::WouldBlock`](std::io::ErrorKind::WouldBlock) if another handle holds it.
+///
+/// The lock is released when the file is closed.
+#[cfg(feature = "cache-lock")]
+pub(crate) fn try_lock_exclusive(file: &std::fs::File) -> std::io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+
+    use windows_sys::Win32::Foundation::ERROR_LOCK_VIOLATION;
+    use windows_sys::Win32::Storage::FileSystem::{
+        LOCKFILE_EXCLUSIVE_LOCK, LOCKFILE_FAIL_IMMEDIATELY, LockFileEx,
+    };
+    use windows_sys::Win32::System::IO::OVERLAPPED;
+
+    // SAFETY: `OVERLAPPED` is plain data, all zeros locks from offset 0.
+    let mut overlapped: OVERLAPPED = unsafe { std::mem::zeroed() };
+    // SAFETY: `file` is an open handle and `overlapped` is valid for the duration of the
+    // synchronous call.
+    let locked = unsafe {
+        LockFileEx(
+            file.as_raw_handle(),
+            LOCKFILE_EXCLUSIVE_LOCK | LOCKFILE_FAIL_IMMEDIATELY,
+            0,
+            1,
+            0,
+            &mut overlapped,
+        )
+    };
+    if locked != 0 {
+        return Ok(());
+    }
+    let error = std::io::Error::last_os_error();
+    if error.raw_os_error() == Some(ERROR_LOCK_VIOLATION as i32) {
+        return Err(std::io::Error::new(std::io::ErrorKind::WouldBlock, error));
+    }
+    Err(error)
+}
+
+/// Returns the path of `folder` from `SHGetKnownFolderPath`, or `None` if the call fails.
+#[cfg(feature = "windows-known-folders")]
+pub(crate) fn known_folder_path(folder: crate::KnownFolder) -> Option<std::path::PathBuf> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+
+    use windows_sys::Win32::System::Com::CoTaskMemFree;
+    use windows_sys::Win32::UI::Shell::{
+        FOLDERID_LocalAppData, FOLDERID_Music, FOLDERID_Pictures, FOLDERID_RoamingAppData,
+        FOLDERID_Videos, KF_FLAG_DEFAULT, SHGetKnownFolderPath,
+    };
+
+    let id = match folder {
+        crate::KnownFolder::RoamingAppData => &FOLDERID_RoamingAppData,
+        crate::KnownFolder::LocalAppData => &FOLDERID_LocalAppData,
+        crate::KnownFolder::Music => &FOLDERID_Music,
+        crate::KnownFolder::Pictures => &FOLDERID_Pictures,
+        crate::KnownFolder::Videos => &FOLDERID_Videos,
+    };
+    let mut buffer = std::ptr::null_mut();
+    // SAFETY: `id` points to a valid GUID and `buffer` to a writable pointer, a null token
+    // requests the folder of the current user.
+    let result = unsafe {
+        SHGetKnownFolderPath(
+            id,
+            KF_FLAG_DEFAULT as u32,
+            std::ptr::null_mut(),
+            &mut buffer,
+        )
+    };
+    let path = (result == 0 && !buffer.is_null()).then(|| {
+        // SAFETY: On success, `buffer` is a null-terminated UTF-16 string.
+        let wide = unsafe {
+            let len = (0..).take_while(|&i| *buffer.add(i) != 0).count();
+            std::slice::from_raw_parts(buffer, len)
+        };
+        std::path::PathBuf::from(OsString::from_wide(wide))
+    });
+    // SAFETY: The buffer is allocated by `SHGetKnownFolderPath` and must be freed by the caller,
+    // even if the call fails. Freeing null is a no-op.
+    unsafe { CoTaskMemFree(buffer.cast()) };
+    path.filter(|path| path.is_absolute())
+}
+
+#[cfg(all(test, feature = "windows-known-folders"))]
+mod known_folder_tests {
+    use super::*;
+    use crate::{Env, KnownFolder, MapEnv, ProcessEnv};
+
+    #[test]
+    fn known_folders_resolve_to_absolute_paths() {
+        for folder in [
+            KnownFolder::RoamingAppData,
+            KnownFolder::LocalAppData,
+            KnownFolder::Music,
+            KnownFolder::Pictures,
+            KnownFolder::Videos,
+        ] {
+            let path = known_folder_path(folder).expect("known folder is resolved");
+            assert!(path.is_absolute());
+            assert_eq!(ProcessEnv.known_folder(folder), Some(path));
+        }
+    }
+
+    #[test]
+    fn media_dirs_resolve_to_absolute_paths() {
+        for dir in [
+            crate::music_dir(),
+            crate::pictures_dir(),
+            crate::videos_dir(),
+        ] {
+            let dir = dir.expect("media folder is resolved");
+            assert!(dir.is_absolute(), "{dir:?}");
+        }
+    }
+
+    #[test]
+    fn config_dir_falls_back_to_env_without_known_folder() {
+        let env = MapEnv::new().with("APPDATA", "C:\\Users\\testuser\\AppData\\Roaming");
+        assert_eq!(
+            crate::config_dir_with(&env),
+            Some(std::path::PathBuf::from(
+                "C:\\Users\\testuser\\AppData\\Roaming"
+            ))
+        );
+    }
+}
+
+#[cfg(all(test, feature = "winapi-known-folders"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_dir_for_current_windows_user_matches_config_dir() {
+        let username = std::env::var("USERNAME").expect("USERNAME is set on Windows");
+        assert_eq!(config_dir_for_windows_user(&username), crate::config_dir());
+    }
+
+    #[test]
+    fn profiles_dir_contains_the_current_home() {
+        let profiles = profiles_dir().expect("profiles directory is resolved");
+        assert!(profiles.is_absolute());
+        let home = crate::home_dir().expect("home directory is resolved");
+        assert_eq!(home.parent(), Some(profiles.as_path()));
+    }
+
+    #[test]
+    fn config_dir_for_windows_user_rejects_paths() {
+        assert_eq!(config_dir_for_windows_user(""), None);
+        assert_eq!(config_dir_for_windows_user(".."), None);
+        assert_eq!(config_dir_for_windows_user("..\\Public"), None);
+        assert_eq!(config_dir_for_windows_user("C:\\Users\\bob"), None);
+    }
+}
+
+#[cfg(test)]
+mod utf16_tests {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::path::PathBuf;
+
+    use crate::MapEnv;
+
+    /// `C:\Users\` followed by U+1F600, an astral-plane character, and a lone high surrogate.
+    fn wide_dir(suffix: &str) -> OsString {
+        let mut wide: Vec<u16> = "C:\\Users\\".encode_utf16().collect();
+        wide.extend([0xD83D, 0xDE00, 0xD800]);
+        wide.extend(suffix.encode_utf16());
+        OsString::from_wide(&wide)
+    }
+
+    fn assert_verbatim(dir: Option<PathBuf>, expected: &OsString) {
+        let dir = dir.expect("directory is resolved");
+        assert_eq!(
+            dir.as_os_str().encode_wide().collect::<Vec<_>>(),
+            expected.encode_wide().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn dirs_preserve_non_unicode_wide_values() {
+        let roaming = wide_dir("\\AppData\\Roaming");
+        let local = wide_dir("\\AppData\\Local");
+        let profile = wide_dir("");
+        let env = MapEnv::new()
+            .with("APPDATA", roaming.clone())
+            .with("LOCALAPPDATA", local.clone())
+            .with("USERPROFILE", profile.clone());
+
+        assert_verbatim(crate::home_dir_with(&env), &profile);
+        assert_verbatim(crate::config_dir_with(&env), &roaming);
+        assert_verbatim(crate::roaming_data_dir_with(&env), &roaming);
+        assert_verbatim(crate::data_dir_with(&env), &local);
+        assert_verbatim(crate::data_local_dir_with(&env), &local);
+        assert_verbatim(crate::cache_dir_with(&env), &local);
+        assert_verbatim(crate::state_dir_with(&env), &local);
+    }
+
+    #[test]
+    fn references_in_non_unicode_values_are_expanded() {
+        let mut wide: Vec<u16> = "%USERPROFILE%\\".encode_utf16().collect();
+        wide.push(0xDC00);
+        let env = MapEnv::new()
+            .with("APPDATA", OsString::from_wide(&wide))
+            .with("USERPROFILE", "C:\\Users\\testuser");
+
+        let mut expanded: Vec<u16> = "C:\\Users\\testuser\\".encode_utf16().collect();
+        expanded.push(0xDC00);
+        assert_verbatim(
+            crate::config_dir_with(&env),
+            &OsString::from_wide(&expanded),
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use crate::path::folded;
+use crate::{Env, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with, same_dir};
+
+/// A directory that compares and hashes like the current platform treats paths, e.g. as a map key.
+///
+/// Two `Dir`s are equal if [`same_dir`] considers them the same, i.e. trailing and repeated
+/// separators are ignored, and so is case on Windows and macOS. [`Display`](fmt::Display) and
+/// [`AsRef<Path>`] expose the path as it was given.
+#[derive(Debug, Clone)]
+pub struct Dir(PathBuf);
+
+impl Dir {
+    /// Returns the path as it was given.
+    pub fn as_path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Returns the path as it was given, consuming the `Dir`.
+    pub fn into_path_buf(self) -> PathBuf {
+        self.0
+    }
+}
+
+impl From<PathBuf> for Dir {
+    fn from(path: PathBuf) -> Self {
+        Dir(path)
+    }
+}
+
+impl AsRef<Path> for Dir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl fmt::Display for Dir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.display().fmt(f)
+    }
+}
+
+impl PartialEq for Dir {
+    fn eq(&self, other: &Self) -> bool {
+        same_dir(&self.0, &other.0)
+    }
+}
+
+impl Eq for Dir {}
+
+impl Hash for Dir {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for component in self.0.components() {
+            folded(component).hash(state);
+        }
+    }
+}
+
+/// Returns [`config_dir`](crate::config_dir) as a [`Dir`].
+pub fn config_dir_wrapped() -> Option<Dir> {
+    config_dir_wrapped_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_wrapped`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_wrapped_with<E: Env + ?Sized>(env: &E) -> Option<Dir> {
+    config_dir_with(env).map(Dir)
+}
+
+/// Returns [`data_dir`](crate::data_dir) as a [`Dir`].
+pub fn data_dir_wrapped() -> Option<Dir> {
+    data_dir_wrapped_with(&ProcessEnv)
+}
+
+/// Like [`data_dir_wrapped`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_wrapped_with<E: Env + ?Sized>(env: &E) -> Option<Dir> {
+    data_dir_with(env).map(Dir)
+}
+
+/// Returns [`cache_dir`](crate::cache_dir) as a [`Dir`].
+pub fn cache_dir_wrapped() -> Option<Dir> {
+    cache_dir_wrapped_with(&ProcessEnv)
+}
+
+/// Like [`cache_dir_wrapped`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_wrapped_with<E: Env + ?Sized>(env: &E) -> Option<Dir> {
+    cache_dir_with(env).map(Dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn dir(path: &str) -> Dir {
+        Dir::from(PathBuf::from(path))
+    }
+
+    #[test]
+    fn trailing_separators_are_ignored() {
+        let set: HashSet<Dir> = [dir("/home/alice/.config/"), dir("/home/alice/.config")].into();
+        assert_eq!(set.len(), 1);
+        assert_eq!(
+            dir("/home/alice/.config/").to_string(),
+            "/home/alice/.config/"
+        );
+    }
+
+    #[test]
+    fn case_matters_only_on_case_insensitive_platforms() {
+        let a = dir("/Users/Alice/Library");
+        let b = dir("/users/alice/library");
+        let case_insensitive = cfg!(any(target_os = "windows", target_os = "macos"));
+        assert_eq!(a == b, case_insensitive);
+        assert_eq!(HashSet::from([a.clone(), b]).len() == 1, case_insensitive);
+        assert_eq!(a.as_path(), Path::new("/Users/Alice/Library"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn linux_resolvers_wrap_dirs() {
+        let env = crate::MapEnv::new().with("HOME", "/home/alice");
+        assert_eq!(
+            config_dir_wrapped_with(&env).map(Dir::into_path_buf),
+            Some(PathBuf::from("/home/alice/.config"))
+        );
+        assert_eq!(
+            cache_dir_wrapped_with(&env),
+            Some(dir("/home/alice/.cache/"))
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::{Env, ProcessEnv, cache_dir_with, config_dir_with, data_dir_with};
+
+/// Returns whether [`config_dir`](crate::config_dir) can be written to, or `None` if it cannot
+/// be resolved.
+///
+/// The check creates a uniquely named empty file in the directory and removes it again, so it
+/// also detects read-only mounts and access control lists. A missing directory is checked at
+/// its closest existing ancestor, i.e. whether it could be created.
+pub fn config_dir_writable() -> Option<bool> {
+    config_dir_writable_with(&ProcessEnv)
+}
+
+/// Like [`config_dir_writable`], but resolves against the given environment instead of the one of the current process.
+pub fn config_dir_writable_with<E: Env + ?Sized>(env: &E) -> Option<bool> {
+    config_dir_with(env).map(|dir| is_writable(&dir))
+}
+
+/// Returns whether [`data_dir`](crate::data_dir) can be written to, see [`config_dir_writable`].
+pub fn data_dir_writable() -> Option<bool> {
+    data_dir_writable_with(&ProcessEnv)
+}
+
+/// Like [`data_dir_writable`], but resolves against the given environment instead of the one of the current process.
+pub fn data_dir_writable_with<E: Env + ?Sized>(env: &E) -> Option<bool> {
+    data_dir_with(env).map(|dir| is_writable(&dir))
+}
+
+/// Returns whether [`cache_dir`](crate::cache_dir) can be written to, see [`config_dir_writable`].
+pub fn cache_dir_writable() -> Option<bool> {
+    cache_dir_writable_with(&ProcessEnv)
+}
+
+/// Like [`cache_dir_writable`], but resolves against the given environment instead of the one of the current process.
+pub fn cache_dir_writable_with<E: Env + ?Sized>(env: &E) -> Option<bool> {
+    cache_dir_with(env).map(|dir| is_writable(&dir))
+}
+
+/// Probes `dir`, or its closest existing ancestor, by creating and removing a file in it.
+fn is_writable(dir: &Path) -> bool {
+    static PROBES: AtomicU32 = AtomicU32::new(0);
+
+    let Some(existing) = dir.ancestors().find(|dir| dir.is_dir()) else {
+        return false;
+    };
+    let probe: PathBuf = existing.join(format!(
+        ".dirs-lite-probe-{}-{}",
+        std::process::id(),
+        PROBES.fetch_add(1, Ordering::Relaxed)
+    ));
+    match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+    {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+    use crate::MapEnv;
+
+    #[test]
+    fn writable_probe_follows_permissions() {
+        let root = tempfile::tempdir().unwrap();
+        let config = root.path().join("config");
+        fs::create_dir(&config).unwrap();
+        let env = MapEnv::new().with("XDG_CONFIG_HOME", &config);
+
+        assert_eq!(config_dir_writable_with(&env), Some(true));
+        assert_eq!(fs::read_dir(&config).unwrap().count(), 0);
+
+        fs::set_permissions(&config, fs::Permissions::from_mode(0o500)).unwrap();
+        // Permissions are not enforced for root, so compare with an actual write
+        let writable = fs::write(config.join("direct"), "").is_ok();
+        let _ = fs::remove_file(config.join("direct"));
+        assert_eq!(config_dir_writable_with(&env), Some(writable));
+        fs::set_permissions(&config, fs::Permissions::from_mode(0o700)).unwrap();
+    }
+
+    #[test]
+    fn missing_dir_is_probed_at_existing_ancestor() {
+        let root = tempfile::tempdir().unwrap();
+        let env = MapEnv::new().with("XDG_CACHE_HOME", root.path().join("missing/cache"));
+
+        assert_eq!(cache_dir_writable_with(&env), Some(true));
+        assert!(!root.path().join("missing").exists());
+    }
+
+    #[test]
+    fn unresolved_dir_is_none() {
+        assert_eq!(data_dir_writable_with(&MapEnv::new()), None);
+    }
+}
@@ -0,0 +1,62 @@
+#![cfg(all(feature = "bin", target_os = "linux"))]
+
+use std::process::Command;
+
+fn dirs_lite() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_dirs-lite"));
+    command
+        .env("HOME", "/home/alice")
+        .env_remove("XDG_CONFIG_HOME")
+        .env_remove("XDG_DATA_HOME")
+        .env_remove("XDG_CACHE_HOME")
+        .env_remove("XDG_STATE_HOME")
+        .env_remove("DIRS_LITE_PREFIX")
+        .env("XDG_RUNTIME_DIR", "/run/user/1001");
+    command
+}
+
+#[test]
+fn prints_config_dir() {
+    let output = dirs_lite().arg("config").output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "/home/alice/.config\n"
+    );
+}
+
+#[test]
+fn prints_app_data_dir() {
+    let output = dirs_lite()
+        .args(["data", "--app", "myapp"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "/home/alice/.local/share/myapp\n"
+    );
+}
+
+#[test]
+fn prints_all_dirs_as_json() {
+    let output = dirs_lite().args(["--all", "--json"]).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "{\"config\":\"/home/alice/.config\",\"data\":\"/home/alice/.local/share\",\
+         \"cache\":\"/home/alice/.cache\",\"state\":\"/home/alice/.local/state\",\
+         \"runtime\":\"/run/user/1001\"}\n"
+    );
+}
+
+#[test]
+fn fails_for_unresolved_dir() {
+    let output = dirs_lite()
+        .env_remove("XDG_RUNTIME_DIR")
+        .arg("runtime")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}